@@ -0,0 +1,166 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+use super::tar::tar;
+use super::{gzip, hex, PackageFile, PackageMetadata};
+use md5::{Digest, Md5};
+use std::fmt::Write;
+use std::path::PathBuf;
+
+/// A Debian binary package: an `ar` archive of `debian-binary`, `control.tar.gz` and
+/// `data.tar.gz`
+pub(crate) fn deb(metadata: &PackageMetadata) -> eyre::Result<Vec<u8>> {
+    let control_files = vec![
+        PackageFile {
+            path: PathBuf::from("control"),
+            mode: 0o644,
+            contents: control(metadata)?.into_bytes(),
+        },
+        PackageFile {
+            path: PathBuf::from("md5sums"),
+            mode: 0o644,
+            contents: md5sums(metadata)?.into_bytes(),
+        },
+    ];
+    let control_tar = gzip(&tar(&control_files, "./", metadata.mtime)?)?;
+    let data_tar = gzip(&tar(&metadata.files, "./", metadata.mtime)?)?;
+
+    let mut out = b"!<arch>\n".to_vec();
+    ar_member(&mut out, "debian-binary", b"2.0\n", metadata.mtime);
+    ar_member(&mut out, "control.tar.gz", &control_tar, metadata.mtime);
+    ar_member(&mut out, "data.tar.gz", &data_tar, metadata.mtime);
+    Ok(out)
+}
+
+/// `name_version_architecture.deb`, as Debian names its packages
+pub(crate) fn deb_file_name(metadata: &PackageMetadata) -> String {
+    format!("{}_{}_{}.deb", metadata.name, metadata.version, architecture())
+}
+
+/// The Debian name of the architecture this `cargo-ogx` was built for
+fn architecture() -> &'static str {
+    match std::env::consts::ARCH {
+        "x86_64" => "amd64",
+        "x86" => "i386",
+        "aarch64" => "arm64",
+        "powerpc64" => "ppc64el",
+        other => other,
+    }
+}
+
+fn control(metadata: &PackageMetadata) -> eyre::Result<String> {
+    let mut control = String::new();
+    writeln!(control, "Package: {}", metadata.name)?;
+    writeln!(control, "Version: {}", metadata.version)?;
+    writeln!(control, "Architecture: {}", architecture())?;
+    writeln!(control, "Maintainer: {}", metadata.maintainer.as_deref().unwrap_or("unknown"))?;
+    writeln!(control, "Installed-Size: {}", metadata.installed_size().div_ceil(1024))?;
+    writeln!(control, "Section: database")?;
+    writeln!(control, "Priority: optional")?;
+    if let Some(homepage) = &metadata.homepage {
+        writeln!(control, "Homepage: {}", homepage)?;
+    }
+    writeln!(control, "Description: {}", metadata.summary)?;
+    // the extended description is indented by a space, with `.` standing in for blank lines
+    for line in metadata.description.lines().skip(1) {
+        match line.trim() {
+            "" => writeln!(control, " .")?,
+            line => writeln!(control, " {}", line)?,
+        }
+    }
+    Ok(control)
+}
+
+fn md5sums(metadata: &PackageMetadata) -> eyre::Result<String> {
+    let mut md5sums = String::new();
+    for file in &metadata.files {
+        writeln!(md5sums, "{}  {}", hex(&Md5::digest(&file.contents)), file.path.display())?;
+    }
+    Ok(md5sums)
+}
+
+fn ar_member(out: &mut Vec<u8>, name: &str, contents: &[u8], mtime: u64) {
+    let header = format!(
+        "{:<16}{:<12}{:<6}{:<6}{:<8}{:<10}`\n",
+        name,
+        mtime,
+        0,
+        0,
+        "100644",
+        contents.len()
+    );
+    out.extend_from_slice(header.as_bytes());
+    out.extend_from_slice(contents);
+    // members start on even offsets
+    if contents.len() % 2 == 1 {
+        out.push(b'\n');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::deb;
+    use crate::archive::hex;
+    use crate::archive::tar::tests::entries;
+    use crate::archive::tests::{gunzip, metadata};
+    use md5::{Digest, Md5};
+
+    /// The members of an `ar` archive, as their name and contents, checking each header
+    fn members(archive: &[u8]) -> Vec<(String, Vec<u8>)> {
+        assert_eq!(&archive[..8], b"!<arch>\n");
+        let mut members = Vec::new();
+        let mut offset = 8;
+        while offset < archive.len() {
+            assert_eq!(offset % 2, 0, "members start on even offsets");
+            let header = std::str::from_utf8(&archive[offset..offset + 60]).unwrap();
+            assert_eq!(&header[58..], "`\n");
+            assert_eq!(header[16..28].trim_end(), metadata().mtime.to_string());
+            assert_eq!(header[40..48].trim_end(), "100644");
+            let size = header[48..58].trim_end().parse::<usize>().unwrap();
+            let start = offset + 60;
+            members
+                .push((header[..16].trim_end().to_string(), archive[start..start + size].to_vec()));
+            offset = start + size + size % 2;
+        }
+        assert_eq!(offset, archive.len());
+        members
+    }
+
+    #[test]
+    fn deb_round_trips() {
+        let metadata = metadata();
+        let members = members(&deb(&metadata).unwrap());
+        let names = members.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["debian-binary", "control.tar.gz", "data.tar.gz"]);
+        assert_eq!(members[0].1, b"2.0\n");
+
+        let control = entries(&gunzip(&members[1].1));
+        let paths = control.iter().map(|(path, ..)| path.as_str()).collect::<Vec<_>>();
+        assert_eq!(paths, ["./", "./control", "./md5sums"]);
+        let control_file = String::from_utf8(control[1].2.clone()).unwrap();
+        assert!(control_file.starts_with("Package: ext-og3\nVersion: 0.1.0\n"));
+        assert!(control_file.contains("\nMaintainer: Someone <someone@example.com>\n"));
+        assert!(control_file.ends_with("\nDescription: An extension\n .\n For testing\n"));
+        let md5sums = String::from_utf8(control[2].2.clone()).unwrap();
+        let expected = metadata
+            .files
+            .iter()
+            .map(|file| format!("{}  {}", hex(&Md5::digest(&file.contents)), file.path.display()))
+            .collect::<Vec<_>>();
+        assert_eq!(md5sums.lines().collect::<Vec<_>>(), expected);
+
+        let data = entries(&gunzip(&members[2].1));
+        let files = data.into_iter().filter(|(path, ..)| !path.ends_with('/')).collect::<Vec<_>>();
+        let expected = metadata
+            .files
+            .iter()
+            .map(|file| (format!("./{}", file.path.display()), file.mode, file.contents.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(files, expected);
+    }
+}
@@ -0,0 +1,141 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+
+//! Writers for the package formats of `cargo ogx package --format`
+//!
+//! Every format is written here directly, so packaging works without `dpkg-deb`, `rpmbuild` or
+//! any other packaging tool installed.
+mod deb;
+mod rpm;
+mod tar;
+
+pub(crate) use deb::{deb, deb_file_name};
+pub(crate) use rpm::{rpm, rpm_file_name};
+pub(crate) use tar::{tar_gz, tar_gz_file_name};
+
+use eyre::WrapErr;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::io::Write;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+/// A file to package
+#[derive(Debug, Clone)]
+pub(crate) struct PackageFile {
+    /// Where the file is installed, relative to `/`
+    pub(crate) path: PathBuf,
+    /// Permission bits, without the file type
+    pub(crate) mode: u32,
+    pub(crate) contents: Vec<u8>,
+}
+
+/// What a package contains and describes itself as
+#[derive(Debug, Clone)]
+pub(crate) struct PackageMetadata {
+    /// The package name, such as `extname-og3`
+    pub(crate) name: String,
+    pub(crate) version: String,
+    /// The first line of `description`
+    pub(crate) summary: String,
+    pub(crate) description: String,
+    pub(crate) maintainer: Option<String>,
+    pub(crate) license: Option<String>,
+    pub(crate) homepage: Option<String>,
+    /// The modification time of every packaged file, and the build time, in seconds since the
+    /// epoch
+    pub(crate) mtime: u64,
+    pub(crate) files: Vec<PackageFile>,
+}
+
+impl PackageMetadata {
+    /// The total size of the packaged files
+    pub(crate) fn installed_size(&self) -> u64 {
+        self.files.iter().map(|file| file.contents.len() as u64).sum()
+    }
+}
+
+/// Every file under `staging_dir`, which is laid out as if it were `/`, in path order
+pub(crate) fn collect_files(staging_dir: &Path) -> eyre::Result<Vec<PackageFile>> {
+    fn walk(staging_dir: &Path, dir: &Path, files: &mut Vec<PackageFile>) -> eyre::Result<()> {
+        for entry in std::fs::read_dir(dir)
+            .wrap_err_with(|| format!("unable to read directory {}", dir.display()))?
+        {
+            let entry = entry?;
+            let path = entry.path();
+            let metadata = entry.metadata()?;
+            if metadata.is_dir() {
+                walk(staging_dir, &path, files)?;
+            } else {
+                files.push(PackageFile {
+                    path: path.strip_prefix(staging_dir)?.to_path_buf(),
+                    mode: metadata.permissions().mode() & 0o7777,
+                    contents: std::fs::read(&path)
+                        .wrap_err_with(|| format!("unable to read {}", path.display()))?,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    let mut files = Vec::new();
+    walk(staging_dir, staging_dir, &mut files)?;
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+fn gzip(data: &[u8]) -> eyre::Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(data)?;
+    Ok(encoder.finish()?)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PackageFile, PackageMetadata};
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+    use std::path::PathBuf;
+
+    /// A package of an extension's library and control file, and of a file whose path is too
+    /// long for the name field of a tar header
+    pub(in crate::archive) fn metadata() -> PackageMetadata {
+        let file = |path: &str, mode, contents: &[u8]| PackageFile {
+            path: PathBuf::from(path),
+            mode,
+            contents: contents.to_vec(),
+        };
+        let long_path = format!("usr/share/opengauss/extension/{}.sql", "ext--0.1.0".repeat(9));
+        PackageMetadata {
+            name: String::from("ext-og3"),
+            version: String::from("0.1.0"),
+            summary: String::from("An extension"),
+            description: String::from("An extension\n\nFor testing"),
+            maintainer: Some(String::from("Someone <someone@example.com>")),
+            license: Some(String::from("MIT")),
+            homepage: None,
+            mtime: 1_600_000_000,
+            files: vec![
+                file("usr/lib/opengauss/ext.so", 0o755, b"\x7fELF library"),
+                file("usr/share/opengauss/extension/ext.control", 0o644, b"comment = 'ext'\n"),
+                file(&long_path, 0o644, b"CREATE FUNCTION ext();\n"),
+            ],
+        }
+    }
+
+    pub(in crate::archive) fn gunzip(data: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        GzDecoder::new(data).read_to_end(&mut out).unwrap();
+        out
+    }
+}
@@ -0,0 +1,464 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+use super::{gzip, hex, PackageFile, PackageMetadata};
+use eyre::eyre;
+use md5::Md5;
+use sha2::{Digest, Sha256};
+
+const RELEASE: &str = "1";
+
+// header tags, from rpm's `rpmtag.h`
+const RPMTAG_HEADERSIGNATURES: u32 = 62;
+const RPMTAG_HEADERIMMUTABLE: u32 = 63;
+const RPMTAG_HEADERI18NTABLE: u32 = 100;
+const RPMSIGTAG_SHA256: u32 = 273;
+const RPMSIGTAG_SIZE: u32 = 1000;
+const RPMSIGTAG_MD5: u32 = 1004;
+const RPMSIGTAG_PAYLOADSIZE: u32 = 1007;
+const RPMTAG_NAME: u32 = 1000;
+const RPMTAG_VERSION: u32 = 1001;
+const RPMTAG_RELEASE: u32 = 1002;
+const RPMTAG_SUMMARY: u32 = 1004;
+const RPMTAG_DESCRIPTION: u32 = 1005;
+const RPMTAG_BUILDTIME: u32 = 1006;
+const RPMTAG_SIZE: u32 = 1009;
+const RPMTAG_LICENSE: u32 = 1014;
+const RPMTAG_PACKAGER: u32 = 1015;
+const RPMTAG_GROUP: u32 = 1016;
+const RPMTAG_URL: u32 = 1020;
+const RPMTAG_OS: u32 = 1021;
+const RPMTAG_ARCH: u32 = 1022;
+const RPMTAG_FILESIZES: u32 = 1028;
+const RPMTAG_FILEMODES: u32 = 1030;
+const RPMTAG_FILERDEVS: u32 = 1033;
+const RPMTAG_FILEMTIMES: u32 = 1034;
+const RPMTAG_FILEDIGESTS: u32 = 1035;
+const RPMTAG_FILELINKTOS: u32 = 1036;
+const RPMTAG_FILEFLAGS: u32 = 1037;
+const RPMTAG_FILEUSERNAME: u32 = 1039;
+const RPMTAG_FILEGROUPNAME: u32 = 1040;
+const RPMTAG_SOURCERPM: u32 = 1044;
+const RPMTAG_PROVIDENAME: u32 = 1047;
+const RPMTAG_REQUIREFLAGS: u32 = 1048;
+const RPMTAG_REQUIRENAME: u32 = 1049;
+const RPMTAG_REQUIREVERSION: u32 = 1050;
+const RPMTAG_FILEDEVICES: u32 = 1095;
+const RPMTAG_FILEINODES: u32 = 1096;
+const RPMTAG_FILELANGS: u32 = 1097;
+const RPMTAG_PROVIDEFLAGS: u32 = 1112;
+const RPMTAG_PROVIDEVERSION: u32 = 1113;
+const RPMTAG_DIRINDEXES: u32 = 1116;
+const RPMTAG_BASENAMES: u32 = 1117;
+const RPMTAG_DIRNAMES: u32 = 1118;
+const RPMTAG_PAYLOADFORMAT: u32 = 1124;
+const RPMTAG_PAYLOADCOMPRESSOR: u32 = 1125;
+const RPMTAG_PAYLOADFLAGS: u32 = 1126;
+const RPMTAG_FILEDIGESTALGO: u32 = 5011;
+
+const PGPHASHALGO_SHA256: u32 = 8;
+const RPMSENSE_EQUAL: u32 = 1 << 3;
+const RPMSENSE_LESS: u32 = 1 << 1;
+const RPMSENSE_RPMLIB: u32 = 1 << 24;
+
+/// `name-version-release.architecture.rpm`, as rpm names its packages
+pub(crate) fn rpm_file_name(metadata: &PackageMetadata) -> String {
+    format!("{}-{}-{}.{}.rpm", metadata.name, metadata.version, RELEASE, std::env::consts::ARCH)
+}
+
+/// An RPM binary package: the lead, a signature header with the digests rpm checks, the package
+/// header, and a gzipped `cpio` payload
+pub(crate) fn rpm(metadata: &PackageMetadata) -> eyre::Result<Vec<u8>> {
+    let cpio = cpio(&metadata.files)?;
+    let payload = gzip(&cpio)?;
+    let header = header(metadata)?.into_bytes(RPMTAG_HEADERIMMUTABLE);
+
+    let mut signed = header.clone();
+    signed.extend_from_slice(&payload);
+    let mut signature = Header::default();
+    signature.string(RPMSIGTAG_SHA256, &hex(&Sha256::digest(&header)));
+    signature.int32(RPMSIGTAG_SIZE, &[signed.len() as u32]);
+    signature.bin(RPMSIGTAG_MD5, &Md5::digest(&signed));
+    signature.int32(RPMSIGTAG_PAYLOADSIZE, &[cpio.len() as u32]);
+    let mut signature = signature.into_bytes(RPMTAG_HEADERSIGNATURES);
+    // unlike the package header, the signature header is padded to a multiple of 8 bytes
+    signature.resize(signature.len().next_multiple_of(8), 0);
+
+    let mut out = lead(metadata);
+    out.extend_from_slice(&signature);
+    out.extend_from_slice(&signed);
+    Ok(out)
+}
+
+/// The obsolete fixed size lead every package still starts with
+fn lead(metadata: &PackageMetadata) -> Vec<u8> {
+    let mut lead = vec![0xed, 0xab, 0xee, 0xdb, 3, 0];
+    lead.extend_from_slice(&0u16.to_be_bytes()); // binary package
+    let archnum: u16 = match std::env::consts::ARCH {
+        "aarch64" => 19,
+        _ => 1,
+    };
+    lead.extend_from_slice(&archnum.to_be_bytes());
+    let mut name = [0u8; 66];
+    let full_name = format!("{}-{}-{}", metadata.name, metadata.version, RELEASE);
+    let len = full_name.len().min(65);
+    name[..len].copy_from_slice(&full_name.as_bytes()[..len]);
+    lead.extend_from_slice(&name);
+    lead.extend_from_slice(&1u16.to_be_bytes()); // linux
+    lead.extend_from_slice(&5u16.to_be_bytes()); // a header-style signature follows
+    lead.extend_from_slice(&[0; 16]);
+    lead
+}
+
+fn header(metadata: &PackageMetadata) -> eyre::Result<Header> {
+    let files = &metadata.files;
+    let mut dirnames: Vec<String> = Vec::new();
+    let mut dirindexes = Vec::new();
+    let mut basenames = Vec::new();
+    for file in files {
+        let path = format!("/{}", file.path.display());
+        let (dirname, basename) = path
+            .rsplit_once('/')
+            .map(|(dirname, basename)| (format!("{}/", dirname), basename.to_string()))
+            .ok_or_else(|| eyre!("`{}` has no directory", path))?;
+        let index = match dirnames.iter().position(|d| d == &dirname) {
+            Some(index) => index,
+            None => {
+                dirnames.push(dirname);
+                dirnames.len() - 1
+            }
+        };
+        dirindexes.push(index as u32);
+        basenames.push(basename);
+    }
+
+    let strings = |value: &str| vec![value.to_string(); files.len()];
+    let requires = [
+        ("rpmlib(CompressedFileNames)", "3.0.4-1"),
+        ("rpmlib(FileDigests)", "4.6.0-1"),
+        ("rpmlib(PayloadFilesHavePrefix)", "4.0-1"),
+    ];
+
+    let mut header = Header::default();
+    header.string_array(RPMTAG_HEADERI18NTABLE, &["C".to_string()]);
+    header.string(RPMTAG_NAME, &metadata.name);
+    header.string(RPMTAG_VERSION, &metadata.version);
+    header.string(RPMTAG_RELEASE, RELEASE);
+    header.i18n_string(RPMTAG_SUMMARY, &metadata.summary);
+    header.i18n_string(RPMTAG_DESCRIPTION, &metadata.description);
+    header.int32(RPMTAG_BUILDTIME, &[metadata.mtime as u32]);
+    header.int32(RPMTAG_SIZE, &[metadata.installed_size() as u32]);
+    header.string(RPMTAG_LICENSE, metadata.license.as_deref().unwrap_or("unknown"));
+    if let Some(maintainer) = &metadata.maintainer {
+        header.string(RPMTAG_PACKAGER, maintainer);
+    }
+    header.i18n_string(RPMTAG_GROUP, "Applications/Databases");
+    if let Some(homepage) = &metadata.homepage {
+        header.string(RPMTAG_URL, homepage);
+    }
+    header.string(RPMTAG_OS, "linux");
+    header.string(RPMTAG_ARCH, std::env::consts::ARCH);
+    header.int32(
+        RPMTAG_FILESIZES,
+        &files.iter().map(|file| file.contents.len() as u32).collect::<Vec<_>>(),
+    );
+    header.int16(
+        RPMTAG_FILEMODES,
+        &files.iter().map(|file| (0o100000 | file.mode) as u16).collect::<Vec<_>>(),
+    );
+    header.int16(RPMTAG_FILERDEVS, &vec![0; files.len()]);
+    header.int32(RPMTAG_FILEMTIMES, &vec![metadata.mtime as u32; files.len()]);
+    header.string_array(
+        RPMTAG_FILEDIGESTS,
+        &files.iter().map(|file| hex(&Sha256::digest(&file.contents))).collect::<Vec<_>>(),
+    );
+    header.string_array(RPMTAG_FILELINKTOS, &strings(""));
+    header.int32(RPMTAG_FILEFLAGS, &vec![0; files.len()]);
+    header.string_array(RPMTAG_FILEUSERNAME, &strings("root"));
+    header.string_array(RPMTAG_FILEGROUPNAME, &strings("root"));
+    header.string(
+        RPMTAG_SOURCERPM,
+        &format!("{}-{}-{}.src.rpm", metadata.name, metadata.version, RELEASE),
+    );
+    header.string_array(RPMTAG_PROVIDENAME, std::slice::from_ref(&metadata.name));
+    header.int32(
+        RPMTAG_REQUIREFLAGS,
+        &vec![RPMSENSE_LESS | RPMSENSE_EQUAL | RPMSENSE_RPMLIB; requires.len()],
+    );
+    header.string_array(
+        RPMTAG_REQUIRENAME,
+        &requires.iter().map(|(name, _)| name.to_string()).collect::<Vec<_>>(),
+    );
+    header.string_array(
+        RPMTAG_REQUIREVERSION,
+        &requires.iter().map(|(_, version)| version.to_string()).collect::<Vec<_>>(),
+    );
+    header.int32(RPMTAG_FILEDEVICES, &vec![1; files.len()]);
+    header.int32(RPMTAG_FILEINODES, &(1..=files.len() as u32).collect::<Vec<_>>());
+    header.string_array(RPMTAG_FILELANGS, &strings(""));
+    header.int32(RPMTAG_PROVIDEFLAGS, &[RPMSENSE_EQUAL]);
+    header.string_array(RPMTAG_PROVIDEVERSION, &[format!("{}-{}", metadata.version, RELEASE)]);
+    header.int32(RPMTAG_DIRINDEXES, &dirindexes);
+    header.string_array(RPMTAG_BASENAMES, &basenames);
+    header.string_array(RPMTAG_DIRNAMES, &dirnames);
+    header.string(RPMTAG_PAYLOADFORMAT, "cpio");
+    header.string(RPMTAG_PAYLOADCOMPRESSOR, "gzip");
+    header.string(RPMTAG_PAYLOADFLAGS, "9");
+    header.int32(RPMTAG_FILEDIGESTALGO, &[PGPHASHALGO_SHA256]);
+    Ok(header)
+}
+
+/// A `newc` format `cpio` archive, with inode numbers matching `RPMTAG_FILEINODES`
+fn cpio(files: &[PackageFile]) -> eyre::Result<Vec<u8>> {
+    fn entry(out: &mut Vec<u8>, ino: u32, mode: u32, name: &str, contents: &[u8]) {
+        let fields =
+            [ino, mode, 0, 0, 1, 0, contents.len() as u32, 0, 0, 0, 0, name.len() as u32 + 1, 0];
+        out.extend_from_slice(b"070701");
+        for field in fields {
+            out.extend_from_slice(format!("{:08x}", field).as_bytes());
+        }
+        out.extend_from_slice(name.as_bytes());
+        out.push(0);
+        out.resize(out.len().next_multiple_of(4), 0);
+        out.extend_from_slice(contents);
+        out.resize(out.len().next_multiple_of(4), 0);
+    }
+
+    let mut out = Vec::new();
+    for (ino, file) in files.iter().enumerate() {
+        let name = format!("./{}", file.path.display());
+        entry(&mut out, ino as u32 + 1, 0o100000 | file.mode, &name, &file.contents);
+    }
+    entry(&mut out, 0, 0, "TRAILER!!!", &[]);
+    Ok(out)
+}
+
+/// The tag/type/offset/count index and data store shared by the signature and package headers
+#[derive(Default)]
+struct Header {
+    entries: Vec<(u32, u32, u32, Vec<u8>)>,
+}
+
+impl Header {
+    const INT16: u32 = 3;
+    const INT32: u32 = 4;
+    const STRING: u32 = 6;
+    const BIN: u32 = 7;
+    const STRING_ARRAY: u32 = 8;
+    const I18NSTRING: u32 = 9;
+
+    fn string(&mut self, tag: u32, value: &str) {
+        self.entries.push((tag, Self::STRING, 1, nul_terminated(value)));
+    }
+
+    fn i18n_string(&mut self, tag: u32, value: &str) {
+        self.entries.push((tag, Self::I18NSTRING, 1, nul_terminated(value)));
+    }
+
+    fn string_array(&mut self, tag: u32, values: &[String]) {
+        let data = values.iter().flat_map(|value| nul_terminated(value)).collect();
+        self.entries.push((tag, Self::STRING_ARRAY, values.len() as u32, data));
+    }
+
+    fn int16(&mut self, tag: u32, values: &[u16]) {
+        let data = values.iter().flat_map(|value| value.to_be_bytes()).collect();
+        self.entries.push((tag, Self::INT16, values.len() as u32, data));
+    }
+
+    fn int32(&mut self, tag: u32, values: &[u32]) {
+        let data = values.iter().flat_map(|value| value.to_be_bytes()).collect();
+        self.entries.push((tag, Self::INT32, values.len() as u32, data));
+    }
+
+    fn bin(&mut self, tag: u32, value: &[u8]) {
+        self.entries.push((tag, Self::BIN, value.len() as u32, value.to_vec()));
+    }
+
+    /// The header, sealed by the `region_tag` entry rpm expects first, whose trailer in the data
+    /// store marks every other entry as belonging to the region
+    fn into_bytes(mut self, region_tag: u32) -> Vec<u8> {
+        self.entries.sort_by_key(|entry| entry.0);
+        let index_len = self.entries.len() as u32 + 1;
+
+        let mut index = Vec::new();
+        let mut store = Vec::new();
+        for (tag, kind, count, data) in &self.entries {
+            let align = match *kind {
+                Self::INT16 => 2,
+                Self::INT32 => 4,
+                _ => 1,
+            };
+            store.resize(store.len().next_multiple_of(align), 0);
+            index.extend_from_slice(&index_entry(*tag, *kind, store.len() as u32, *count));
+            store.extend_from_slice(data);
+        }
+        let trailer_offset = store.len() as u32;
+        store.extend_from_slice(&index_entry(
+            region_tag,
+            Self::BIN,
+            (-(index_len as i32 * 16)) as u32,
+            16,
+        ));
+
+        let mut out = vec![0x8e, 0xad, 0xe8, 0x01, 0, 0, 0, 0];
+        out.extend_from_slice(&index_len.to_be_bytes());
+        out.extend_from_slice(&(store.len() as u32).to_be_bytes());
+        out.extend_from_slice(&index_entry(region_tag, Self::BIN, trailer_offset, 16));
+        out.extend_from_slice(&index);
+        out.extend_from_slice(&store);
+        out
+    }
+}
+
+fn index_entry(tag: u32, kind: u32, offset: u32, count: u32) -> Vec<u8> {
+    [tag, kind, offset, count].iter().flat_map(|value| value.to_be_bytes()).collect()
+}
+
+fn nul_terminated(value: &str) -> Vec<u8> {
+    let mut bytes = value.as_bytes().to_vec();
+    bytes.push(0);
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archive::tests::{gunzip, metadata};
+    use std::collections::HashMap;
+
+    /// A header's entries by tag, as their type, count and data
+    type Entries = HashMap<u32, (u32, u32, Vec<u8>)>;
+
+    fn be32(bytes: &[u8], offset: usize) -> u32 {
+        u32::from_be_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    }
+
+    /// A header's entries and its total length, checking the magic and that the region entry and
+    /// its trailer cover the whole index
+    fn read_header(bytes: &[u8], region_tag: u32) -> (Entries, usize) {
+        assert_eq!(&bytes[..8], &[0x8e, 0xad, 0xe8, 0x01, 0, 0, 0, 0]);
+        let index_len = be32(bytes, 8) as usize;
+        let store_len = be32(bytes, 12) as usize;
+        let store = &bytes[16 + index_len * 16..16 + index_len * 16 + store_len];
+
+        let entry = |i: usize| {
+            let at = 16 + i * 16;
+            (be32(bytes, at), be32(bytes, at + 4), be32(bytes, at + 8), be32(bytes, at + 12))
+        };
+        let (tag, kind, trailer_offset, count) = entry(0);
+        assert_eq!((tag, kind, count), (region_tag, Header::BIN, 16));
+        assert_eq!(trailer_offset as usize, store_len - 16);
+        let trailer = &store[trailer_offset as usize..];
+        assert_eq!(be32(trailer, 0), region_tag);
+        assert_eq!(be32(trailer, 8) as i32, -(index_len as i32 * 16));
+
+        let mut entries = HashMap::new();
+        for i in 1..index_len {
+            let (tag, kind, offset, count) = entry(i);
+            let offset = offset as usize;
+            let len = match kind {
+                Header::INT16 => 2 * count as usize,
+                Header::INT32 => {
+                    assert_eq!(offset % 4, 0, "tag {} is aligned", tag);
+                    4 * count as usize
+                }
+                Header::BIN => count as usize,
+                _ => {
+                    let mut len = 0;
+                    for _ in 0..count {
+                        len += store[offset + len..].iter().position(|b| *b == 0).unwrap() + 1;
+                    }
+                    len
+                }
+            };
+            entries.insert(tag, (kind, count, store[offset..offset + len].to_vec()));
+        }
+        (entries, 16 + index_len * 16 + store_len)
+    }
+
+    fn strings(data: &[u8]) -> Vec<String> {
+        data.split(|b| *b == 0)
+            .take(data.iter().filter(|b| **b == 0).count())
+            .map(|string| String::from_utf8(string.to_vec()).unwrap())
+            .collect()
+    }
+
+    fn hex_field(bytes: &[u8], field: usize) -> usize {
+        let at = 6 + field * 8;
+        usize::from_str_radix(std::str::from_utf8(&bytes[at..at + 8]).unwrap(), 16).unwrap()
+    }
+
+    #[test]
+    fn rpm_round_trips() {
+        let metadata = metadata();
+        let rpm = rpm(&metadata).unwrap();
+
+        let lead = &rpm[..96];
+        assert_eq!(&lead[..6], &[0xed, 0xab, 0xee, 0xdb, 3, 0]);
+        assert_eq!(strings(&lead[10..76])[0], "ext-og3-0.1.0-1");
+        assert_eq!(&lead[76..80], &[0, 1, 0, 5]);
+
+        let (signature, signature_len) = read_header(&rpm[96..], RPMTAG_HEADERSIGNATURES);
+        let header_start = 96 + signature_len.next_multiple_of(8);
+        let (header, header_len) = read_header(&rpm[header_start..], RPMTAG_HEADERIMMUTABLE);
+        let header_bytes = &rpm[header_start..header_start + header_len];
+        let signed = &rpm[header_start..];
+        let payload = &rpm[header_start + header_len..];
+
+        assert_eq!(strings(&signature[&RPMSIGTAG_SHA256].2), [hex(&Sha256::digest(header_bytes))]);
+        assert_eq!(signature[&RPMSIGTAG_MD5].2, Md5::digest(signed).to_vec());
+        assert_eq!(be32(&signature[&RPMSIGTAG_SIZE].2, 0) as usize, signed.len());
+        let cpio = gunzip(payload);
+        assert_eq!(be32(&signature[&RPMSIGTAG_PAYLOADSIZE].2, 0) as usize, cpio.len());
+
+        assert_eq!(strings(&header[&RPMTAG_NAME].2), ["ext-og3"]);
+        assert_eq!(strings(&header[&RPMTAG_VERSION].2), ["0.1.0"]);
+        assert_eq!(
+            strings(&header[&RPMTAG_DIRNAMES].2),
+            ["/usr/lib/opengauss/", "/usr/share/opengauss/extension/"]
+        );
+        assert_eq!(header[&RPMTAG_DIRINDEXES].2, [[0u8; 4], [0, 0, 0, 1], [0, 0, 0, 1]].concat());
+        let basenames = strings(&header[&RPMTAG_BASENAMES].2);
+        let digests = strings(&header[&RPMTAG_FILEDIGESTS].2);
+        for (i, file) in metadata.files.iter().enumerate() {
+            assert_eq!(basenames[i], file.path.file_name().unwrap().to_str().unwrap());
+            assert_eq!(digests[i], hex(&Sha256::digest(&file.contents)));
+        }
+
+        let mut offset = 0;
+        let mut entries = Vec::new();
+        loop {
+            let entry = &cpio[offset..];
+            assert_eq!(&entry[..6], b"070701");
+            let name_len = hex_field(entry, 11);
+            let name = std::str::from_utf8(&entry[110..110 + name_len - 1]).unwrap().to_string();
+            let start = (110 + name_len).next_multiple_of(4);
+            let contents = entry[start..start + hex_field(entry, 6)].to_vec();
+            offset += (start + contents.len()).next_multiple_of(4);
+            if name == "TRAILER!!!" {
+                break;
+            }
+            entries.push((hex_field(entry, 0), hex_field(entry, 1) as u32, name, contents));
+        }
+        assert_eq!(offset, cpio.len());
+        let expected = metadata
+            .files
+            .iter()
+            .enumerate()
+            .map(|(i, file)| {
+                (
+                    i + 1,
+                    0o100000 | file.mode,
+                    format!("./{}", file.path.display()),
+                    file.contents.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(entries, expected);
+    }
+}
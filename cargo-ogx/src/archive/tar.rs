@@ -0,0 +1,178 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+use super::{gzip, PackageFile, PackageMetadata};
+use eyre::eyre;
+use std::collections::BTreeSet;
+
+const BLOCK_SIZE: usize = 512;
+
+/// `name-version-linux-architecture.tar.gz`
+pub(crate) fn tar_gz_file_name(metadata: &PackageMetadata) -> String {
+    format!("{}-{}-linux-{}.tar.gz", metadata.name, metadata.version, std::env::consts::ARCH)
+}
+
+/// A gzipped tarball of the packaged files, to be extracted into `/`
+pub(crate) fn tar_gz(metadata: &PackageMetadata) -> eyre::Result<Vec<u8>> {
+    gzip(&tar(&metadata.files, "", metadata.mtime)?)
+}
+
+/// A ustar archive of `files` and their parent directories, with every name starting with
+/// `prefix`
+pub(super) fn tar(files: &[PackageFile], prefix: &str, mtime: u64) -> eyre::Result<Vec<u8>> {
+    let mut out = Vec::new();
+    let mut dirs = BTreeSet::new();
+    if !prefix.is_empty() {
+        dirs.insert(prefix.to_string());
+        entry(&mut out, prefix, 0o755, b'5', &[], mtime)?;
+    }
+    for file in files {
+        let path = file
+            .path
+            .to_str()
+            .ok_or_else(|| eyre!("`{}` is not valid UTF-8", file.path.display()))?;
+
+        let components = path.split('/').collect::<Vec<_>>();
+        let mut dir = String::from(prefix);
+        for component in &components[..components.len() - 1] {
+            dir.push_str(component);
+            dir.push('/');
+            if dirs.insert(dir.clone()) {
+                entry(&mut out, &dir, 0o755, b'5', &[], mtime)?;
+            }
+        }
+        entry(&mut out, &format!("{}{}", prefix, path), file.mode, b'0', &file.contents, mtime)?;
+    }
+    // the end of the archive is marked by two empty blocks
+    out.resize(out.len() + 2 * BLOCK_SIZE, 0);
+    Ok(out)
+}
+
+fn entry(
+    out: &mut Vec<u8>,
+    name: &str,
+    mode: u32,
+    typeflag: u8,
+    contents: &[u8],
+    mtime: u64,
+) -> eyre::Result<()> {
+    let mut header = [0u8; BLOCK_SIZE];
+    let (prefix, name) = split_name(name)?;
+    header[..name.len()].copy_from_slice(name.as_bytes());
+    octal(&mut header[100..108], mode as u64);
+    octal(&mut header[108..116], 0);
+    octal(&mut header[116..124], 0);
+    octal(&mut header[124..136], contents.len() as u64);
+    octal(&mut header[136..148], mtime);
+    header[156] = typeflag;
+    header[257..263].copy_from_slice(b"ustar\0");
+    header[263..265].copy_from_slice(b"00");
+    header[265..269].copy_from_slice(b"root");
+    header[297..301].copy_from_slice(b"root");
+    header[345..345 + prefix.len()].copy_from_slice(prefix.as_bytes());
+
+    // the checksum is computed with its own field filled with spaces
+    header[148..156].copy_from_slice(b"        ");
+    let checksum = header.iter().map(|&byte| byte as u64).sum::<u64>();
+    header[148..154].copy_from_slice(format!("{:06o}", checksum).as_bytes());
+    header[154] = 0;
+
+    out.extend_from_slice(&header);
+    out.extend_from_slice(contents);
+    let padding = (BLOCK_SIZE - contents.len() % BLOCK_SIZE) % BLOCK_SIZE;
+    out.resize(out.len() + padding, 0);
+    Ok(())
+}
+
+/// Split a name too long for the 100 byte name field at a `/`, into the 155 byte prefix field
+fn split_name(name: &str) -> eyre::Result<(&str, &str)> {
+    if name.len() <= 100 {
+        return Ok(("", name));
+    }
+    name.char_indices()
+        .filter(|&(i, c)| c == '/' && i <= 155 && name.len() - i - 1 <= 100 && i + 1 < name.len())
+        .map(|(i, _)| (&name[..i], &name[i + 1..]))
+        .next()
+        .ok_or_else(|| eyre!("`{}` is too long for a tar archive", name))
+}
+
+/// A NUL terminated, zero padded octal number filling `field`
+fn octal(field: &mut [u8], value: u64) {
+    let digits = format!("{:0width$o}", value, width = field.len() - 1);
+    field[..digits.len()].copy_from_slice(digits.as_bytes());
+    field[digits.len()] = 0;
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::tar_gz;
+    use crate::archive::tests::{gunzip, metadata};
+    use std::io::Read;
+    use std::path::PathBuf;
+
+    /// Read back every entry of a ustar archive, as its path, mode and contents, checking that it
+    /// is owned by root and has the package's mtime
+    pub(in crate::archive) fn entries(archive: &[u8]) -> Vec<(String, u32, Vec<u8>)> {
+        let mut entries = Vec::new();
+        for entry in ::tar::Archive::new(archive).entries().unwrap() {
+            let mut entry = entry.unwrap();
+            let header = entry.header();
+            assert_eq!(header.mtime().unwrap(), metadata().mtime);
+            assert_eq!(header.uid().unwrap(), 0);
+            assert_eq!(header.username().unwrap(), Some("root"));
+            assert_eq!(header.groupname().unwrap(), Some("root"));
+            let mode = header.mode().unwrap();
+            let path = entry.path().unwrap().to_str().unwrap().to_string();
+            let mut contents = Vec::new();
+            entry.read_to_end(&mut contents).unwrap();
+            entries.push((path, mode, contents));
+        }
+        entries
+    }
+
+    #[test]
+    fn tar_gz_round_trips() {
+        let metadata = metadata();
+        let archive = gunzip(&tar_gz(&metadata).unwrap());
+        assert_eq!(archive.len() % 512, 0);
+        let entries = entries(&archive);
+
+        let dirs = entries
+            .iter()
+            .filter(|(path, ..)| path.ends_with('/'))
+            .map(|(path, mode, _)| (path.as_str(), *mode))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            dirs,
+            [
+                ("usr/", 0o755),
+                ("usr/lib/", 0o755),
+                ("usr/lib/opengauss/", 0o755),
+                ("usr/share/", 0o755),
+                ("usr/share/opengauss/", 0o755),
+                ("usr/share/opengauss/extension/", 0o755),
+            ]
+        );
+
+        let files =
+            entries.into_iter().filter(|(path, ..)| !path.ends_with('/')).collect::<Vec<_>>();
+        let expected = metadata
+            .files
+            .iter()
+            .map(|file| (file.path.display().to_string(), file.mode, file.contents.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(files, expected);
+    }
+
+    #[test]
+    fn tar_gz_name_too_long() {
+        let mut metadata = metadata();
+        metadata.files[0].path = PathBuf::from("x".repeat(101));
+        assert!(tar_gz(&metadata).is_err());
+    }
+}
@@ -0,0 +1,189 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+use crate::command::test::cargo_test_command;
+use crate::profile::CargoProfile;
+use crate::CommandExecute;
+use cargo_toml::Manifest;
+use eyre::{eyre, WrapErr};
+use ogx_pg_config::{Ogx, PgConfig, PgConfigSelector};
+use std::path::{Path, PathBuf};
+
+/// Run the `#[og_bench]` benchmarks of this crate and compare them with a saved baseline
+#[derive(clap::Args, Debug)]
+#[clap(author)]
+pub(crate) struct Bench {
+    /// Do you want to run against openGauss `og3`?
+    #[clap(env = "OG_VERSION")]
+    pg_version: Option<String>,
+    /// If specified, only run benchmarks containing this string in their names
+    benchname: Option<String>,
+    /// Package to build (see `cargo help pkgid`)
+    #[clap(long, short)]
+    package: Option<String>,
+    /// Path to Cargo.toml
+    #[clap(long, parse(from_os_str))]
+    manifest_path: Option<PathBuf>,
+    /// Compile for debug mode (default is release)
+    #[clap(long, short)]
+    debug: bool,
+    /// Specific profile to use (conflicts with `--debug`)
+    #[clap(long)]
+    profile: Option<String>,
+    /// Don't regenerate the schema
+    #[clap(long, short)]
+    no_schema: bool,
+    /// The saved baseline to compare against
+    #[clap(long, default_value = "base")]
+    baseline: String,
+    /// Save the results as this baseline (by default nothing is saved)
+    #[clap(long)]
+    save_baseline: Option<String>,
+    /// How many percent slower than the baseline a benchmark may get before it fails
+    #[clap(long, default_value = "5")]
+    threshold: f64,
+    /// How many timed samples to take of every benchmark
+    #[clap(long, default_value = "50")]
+    samples: usize,
+    #[clap(flatten)]
+    features: clap_cargo::Features,
+    #[clap(from_global, parse(from_occurrences))]
+    verbose: usize,
+}
+
+impl CommandExecute for Bench {
+    #[tracing::instrument(level = "error", skip(self))]
+    fn execute(self) -> eyre::Result<()> {
+        let ogx = Ogx::from_config()?;
+
+        let metadata = crate::metadata::metadata(&self.features, self.manifest_path.as_ref())
+            .wrap_err("couldn't get cargo metadata")?;
+        crate::metadata::validate(&metadata)?;
+        let package_manifest_path =
+            crate::manifest::manifest_path(&metadata, self.package.as_ref())
+                .wrap_err("Couldn't get manifest path")?;
+        let package_manifest =
+            Manifest::from_path(&package_manifest_path).wrap_err("Couldn't parse manifest")?;
+
+        let pg_version = match self.pg_version {
+            Some(ref s) => s.clone(),
+            None => crate::manifest::default_og_version(&package_manifest)
+                .ok_or(eyre!("No provided `og$VERSION` flag."))?,
+        };
+        // timings of a debug build say little, so unlike `test` this defaults to release
+        let release = !self.debug && self.profile.is_none();
+        let profile = CargoProfile::from_flags(release, self.profile.as_deref())?;
+
+        for pg_config in ogx.iter(PgConfigSelector::new(&pg_version)) {
+            let mut benchname = self.benchname.clone();
+            let pg_config = match pg_config {
+                Err(error) => {
+                    tracing::debug!(
+                        invalid_pg_version = %pg_version,
+                        error = %error,
+                        "Got invalid `og$VERSION` flag, assuming it is a benchname"
+                    );
+                    benchname = Some(pg_version.clone());
+                    ogx.get(
+                        &crate::manifest::default_og_version(&package_manifest)
+                            .ok_or(eyre!("No provided `og$VERSION` flag."))?,
+                    )?
+                }
+                Ok(config) => config,
+            };
+            let features = crate::manifest::features_for_version(
+                self.features.clone(),
+                &package_manifest,
+                pg_config,
+            )?;
+
+            bench_extension(
+                pg_config,
+                self.manifest_path.as_ref(),
+                self.package.as_ref(),
+                &profile,
+                self.no_schema,
+                &features,
+                benchname.as_deref(),
+                &BenchOptions {
+                    baseline: &self.baseline,
+                    save_baseline: self.save_baseline.as_deref(),
+                    threshold: self.threshold,
+                    samples: self.samples,
+                },
+            )?
+        }
+
+        Ok(())
+    }
+}
+
+/// How the `#[og_bench]`es sample and judge their timings
+#[derive(Debug)]
+pub(crate) struct BenchOptions<'a> {
+    pub(crate) baseline: &'a str,
+    pub(crate) save_baseline: Option<&'a str>,
+    pub(crate) threshold: f64,
+    pub(crate) samples: usize,
+}
+
+#[tracing::instrument(level = "error", skip_all, fields(
+    pg_version = %pg_config.version()?,
+    benchname = tracing::field::Empty,
+    profile = ?profile,
+    baseline = options.baseline,
+))]
+pub(crate) fn bench_extension(
+    pg_config: &PgConfig,
+    user_manifest_path: Option<impl AsRef<Path>>,
+    user_package: Option<&String>,
+    profile: &CargoProfile,
+    no_schema: bool,
+    features: &clap_cargo::Features,
+    benchname: Option<&str>,
+    options: &BenchOptions,
+) -> eyre::Result<()> {
+    if let Some(benchname) = benchname {
+        tracing::Span::current().record("benchname", tracing::field::display(benchname));
+    }
+
+    let mut command = cargo_test_command(
+        pg_config,
+        user_manifest_path,
+        user_package,
+        profile,
+        no_schema,
+        features,
+    )?;
+
+    // the `#[test]` generated for each `#[og_bench]` is named `og_bench_<name>`, and does
+    // nothing unless `OGX_BENCH` is set to a string `<name>` contains
+    command
+        .env("OGX_BENCH", benchname.unwrap_or_default())
+        .env("OGX_BENCH_BASELINE", options.baseline)
+        .env("OGX_BENCH_THRESHOLD", options.threshold.to_string())
+        .env("OGX_BENCH_SAMPLES", options.samples.to_string())
+        .arg("--")
+        .arg("og_bench_")
+        .arg("--nocapture")
+        // benchmarks sharing the server would disturb each other's timings
+        .arg("--test-threads=1");
+    if let Some(save_baseline) = options.save_baseline {
+        command.env("OGX_BENCH_SAVE_BASELINE", save_baseline);
+    }
+
+    tracing::debug!(command = ?command, "Running");
+    let status = command.status().wrap_err("failed to run cargo test")?;
+    tracing::trace!(status_code = %status, command = ?command, "Finished");
+    if !status.success() {
+        // We explicitly do not want to return a spantraced error here.
+        std::process::exit(1)
+    }
+
+    Ok(())
+}
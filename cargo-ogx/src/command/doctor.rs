@@ -0,0 +1,476 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+use crate::command::init::is_root_user;
+use crate::command::status::status_opengauss;
+use crate::CommandExecute;
+use eyre::eyre;
+use ogx_pg_config::{Ogx, PgConfig, PgConfigSelector};
+use owo_colors::OwoColorize;
+use std::collections::HashMap;
+use std::net::TcpListener;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The gcc openGauss is configured with by `cargo ogx init`, from its third-party binarylibs
+const BUNDLED_GCC_VERSION: &str = "7.3.0";
+
+/// Diagnose problems with the environment ogx builds, installs and runs openGauss in
+#[derive(clap::Args, Debug)]
+#[clap(author)]
+pub(crate) struct Doctor {
+    /// The openGauss version to check, or `all`
+    #[clap(env = "OG_VERSION")]
+    pg_version: Option<String>,
+    #[clap(from_global, parse(from_occurrences))]
+    verbose: usize,
+}
+
+impl CommandExecute for Doctor {
+    #[tracing::instrument(level = "error", skip(self))]
+    fn execute(self) -> eyre::Result<()> {
+        let mut report = Report::default();
+
+        report.section("build tools");
+        check_init_tools(&mut report);
+        check_system_compiler(&mut report);
+        check_libclang(&mut report);
+
+        report.section("configuration");
+        let ogx = check_config_toml(&mut report);
+        if is_root_user() {
+            report.warning(
+                "running as root",
+                "openGauss refuses to run as root, so `cargo ogx init` skips initdb and \
+                 `cargo ogx run`/`test` will fail; use an unprivileged user",
+            );
+        }
+
+        if let Some(ogx) = ogx {
+            let pg_version = self.pg_version.unwrap_or_else(|| "all".to_string());
+            for pg_config in ogx.iter(PgConfigSelector::new(&pg_version)) {
+                match pg_config {
+                    Ok(pg_config) => check_installation(&mut report, pg_config),
+                    Err(e) => report.error(
+                        format!("{}", e),
+                        "pass one of the versions in `config.toml`, or `all`",
+                    ),
+                }
+            }
+        }
+
+        report.finish()
+    }
+}
+
+/// What `cargo ogx doctor` found, printed as it goes
+#[derive(Default)]
+struct Report {
+    warnings: usize,
+    errors: usize,
+}
+
+impl Report {
+    fn section(&self, name: impl AsRef<str>) {
+        println!("{} {}", "    Checking".bold().green(), name.as_ref());
+    }
+
+    fn ok(&self, what: impl AsRef<str>) {
+        println!("{} {}", "          ok".bold().green(), what.as_ref());
+    }
+
+    fn warning(&mut self, what: impl AsRef<str>, fix: impl AsRef<str>) {
+        self.warnings += 1;
+        println!("{} {}", "     warning".bold().yellow(), what.as_ref());
+        println!("{} {}", "         fix".bold().cyan(), fix.as_ref());
+    }
+
+    fn error(&mut self, what: impl AsRef<str>, fix: impl AsRef<str>) {
+        self.errors += 1;
+        println!("{} {}", "       error".bold().red(), what.as_ref());
+        println!("{} {}", "         fix".bold().cyan(), fix.as_ref());
+    }
+
+    fn finish(self) -> eyre::Result<()> {
+        if self.errors > 0 {
+            return Err(eyre!("found {} problem(s) and {} warning(s)", self.errors, self.warnings));
+        }
+        println!("{} with {} warning(s)", "    Finished".bold().green(), self.warnings);
+        Ok(())
+    }
+}
+
+/// The tools `cargo ogx init` shells out to when it downloads and builds openGauss
+fn check_init_tools(report: &mut Report) {
+    for tool in ["wget", "unzip", "tar", "sed", "make"] {
+        match Command::new(tool).arg("--version").output() {
+            Ok(_) => report.ok(format!("`{}` found", tool)),
+            Err(_) => report.warning(
+                format!("`{}` not found on $PATH", tool),
+                format!(
+                    "install `{}` before running `cargo ogx init` with `download`ed versions",
+                    tool
+                ),
+            ),
+        }
+    }
+}
+
+fn check_system_compiler(report: &mut Report) {
+    match Command::new("cc").arg("-dumpfullversion").arg("-dumpversion").output() {
+        Ok(output) if output.status.success() => {
+            report.ok(format!("cc {}", String::from_utf8_lossy(&output.stdout).trim()))
+        }
+        _ => report.error(
+            "no C compiler `cc` on $PATH",
+            "install gcc, which cargo needs to link extensions",
+        ),
+    }
+}
+
+/// bindgen loads libclang at runtime to generate `ogx-pg-sys`' bindings
+fn check_libclang(report: &mut Report) {
+    if let Ok(dir) = std::env::var("LIBCLANG_PATH") {
+        match find_libclang(&[PathBuf::from(&dir)]) {
+            Some(found) => report.ok(format!("libclang at {}", found.display())),
+            None => report.error(
+                format!("LIBCLANG_PATH is `{}`, which has no libclang", dir),
+                "point LIBCLANG_PATH at the directory containing `libclang.so`, or unset it",
+            ),
+        }
+        return;
+    }
+
+    let mut dirs = Vec::new();
+    if let Ok(output) = Command::new("llvm-config").arg("--libdir").output() {
+        dirs.push(PathBuf::from(String::from_utf8_lossy(&output.stdout).trim()));
+    }
+    for dir in ["/usr/lib64", "/usr/lib", "/usr/local/lib", "/usr/local/lib64"] {
+        dirs.push(PathBuf::from(dir));
+    }
+    dirs.push(PathBuf::from(format!("/usr/lib/{}-linux-gnu", std::env::consts::ARCH)));
+    if let Ok(entries) = std::fs::read_dir("/usr/lib") {
+        for entry in entries.flatten() {
+            if entry.file_name().to_string_lossy().starts_with("llvm") {
+                dirs.push(entry.path().join("lib"));
+            }
+        }
+    }
+
+    match find_libclang(&dirs) {
+        Some(found) => report.ok(format!("libclang at {}", found.display())),
+        None => report.error(
+            "libclang not found, so bindgen can't generate openGauss' bindings",
+            "install clang (such as `yum install clang` or `apt install libclang-dev`), or set \
+             LIBCLANG_PATH to the directory containing `libclang.so`",
+        ),
+    }
+}
+
+fn find_libclang(dirs: &[PathBuf]) -> Option<PathBuf> {
+    dirs.iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.flatten())
+        .map(|entry| entry.path())
+        .find(|path| {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            // `libclang.so.14`, `libclang-14.so.1` and so on, but not `libclang-cpp.so`
+            name.starts_with("libclang.so")
+                || matches!(name.strip_prefix("libclang-"), Some(rest) if rest.starts_with(|c: char| c.is_ascii_digit()))
+        })
+}
+
+/// `~/.ogx/config.toml` exists, parses, and names working, distinct `pg_config`s on distinct ports
+fn check_config_toml(report: &mut Report) -> Option<Ogx> {
+    let path = match Ogx::config_toml() {
+        Ok(path) => path,
+        Err(e) => {
+            report.error(format!("{}", e), "set OGX_HOME to a writable directory");
+            return None;
+        }
+    };
+
+    let ogx = match Ogx::from_config() {
+        Ok(ogx) => ogx,
+        Err(e) => {
+            report.error(format!("{:#}", e), "run `cargo ogx init`");
+            return None;
+        }
+    };
+    report.ok(format!("{} parses", path.display()));
+
+    let mut seen = HashMap::new();
+    let mut ports = HashMap::new();
+    let mut broken = false;
+    for pg_config in ogx.pg_configs() {
+        let pg_config_path = location(pg_config);
+        if !pg_config_path.exists() {
+            report.error(
+                format!("{} doesn't exist", pg_config_path.display()),
+                "rerun `cargo ogx init`, or fix its path in `config.toml`",
+            );
+            broken = true;
+            continue;
+        }
+        match pg_config.label() {
+            Ok(label) => {
+                if let Some(other) = seen.insert(label.clone(), pg_config_path.clone()) {
+                    report.error(
+                        format!(
+                            "{} and {} are both {}",
+                            other.display(),
+                            pg_config_path.display(),
+                            label
+                        ),
+                        "remove one of them from `config.toml`",
+                    );
+                    broken = true;
+                }
+                for port in [pg_config.port(), pg_config.test_port()].into_iter().flatten() {
+                    if let Some(other) = ports.insert(port, label.clone()) {
+                        report.error(
+                            format!("{} and {} both use port {}", other, label, port),
+                            format!(
+                                "rerun `cargo ogx init` with both, or give {} its own ports under \
+                                 `[ports.{}]` in `config.toml`",
+                                label, label
+                            ),
+                        );
+                    }
+                }
+            }
+            Err(e) => {
+                report.error(
+                    format!("{} doesn't report its version: {}", pg_config_path.display(), e),
+                    "make sure it's an openGauss `pg_config`, and rerun `cargo ogx init`",
+                );
+                broken = true;
+            }
+        }
+    }
+
+    // every other check needs to know the versions
+    if broken {
+        None
+    } else {
+        Some(ogx)
+    }
+}
+
+#[tracing::instrument(level = "error", skip_all, fields(pg_version = %pg_config.label().unwrap_or_default()))]
+fn check_installation(report: &mut Report, pg_config: &PgConfig) {
+    let label = pg_config.label().unwrap_or_default();
+    report.section(format!("{} ({})", label, location(pg_config).display()));
+
+    match (pg_config.includedir_server(), pg_config.pkglibdir()) {
+        (Ok(include), Ok(lib)) if include.exists() && lib.exists() => {
+            report.ok(format!("headers in {}", include.display()))
+        }
+        _ => report.error(
+            "`pg_config` doesn't point at installed headers and libraries",
+            format!("rerun `cargo ogx init --og3 {}`", location(pg_config).display()),
+        ),
+    }
+
+    check_binarylibs(report, pg_config);
+    check_ports(report, pg_config);
+    check_data_dir(report, pg_config);
+}
+
+/// The `pg_config`, or the root of the installation registered without one
+fn location(pg_config: &PgConfig) -> PathBuf {
+    match pg_config.installation() {
+        Some(installation) => installation.root.clone(),
+        None => pg_config.path().unwrap_or_default(),
+    }
+}
+
+/// The openGauss third-party binarylibs `cargo ogx init` downloads next to the installation,
+/// whose gcc 7.3 both builds openGauss and provides headers to bindgen
+fn check_binarylibs(report: &mut Report, pg_config: &PgConfig) {
+    let tools_dir = match pg_config.bin_dir() {
+        Ok(mut dir) => {
+            dir.pop();
+            dir.pop();
+            dir.push("tools");
+            dir
+        }
+        Err(e) => {
+            report.error(format!("{}", e), "rerun `cargo ogx init`");
+            return;
+        }
+    };
+    let fix = format!(
+        "extract openGauss' third-party binarylibs to {}, or rerun `cargo ogx init --og3 download`",
+        tools_dir.display()
+    );
+    if let Some(installation) = pg_config.installation() {
+        // a package installation comes without binarylibs, which only bindgen's headers need
+        match &installation.gcc_include_dir {
+            Some(include) if include.is_dir() => {
+                report.ok(format!("gcc headers in {}", include.display()))
+            }
+            Some(include) => report.error(
+                format!("gcc_include_dir {} doesn't exist", include.display()),
+                "fix `gcc_include_dir` in `config.toml`",
+            ),
+            None if tools_dir.is_dir() => (),
+            None => report.warning(
+                "no binarylibs next to this installation, so bindgen uses the system's C++ headers",
+                format!(
+                    "if bindings fail to generate, set `gcc_include_dir` under \
+                     `[installations.{}]` in `config.toml` to the headers of the gcc that built it",
+                    pg_config.label().unwrap_or_default()
+                ),
+            ),
+        }
+        if !tools_dir.is_dir() {
+            return;
+        }
+    }
+    if !tools_dir.is_dir() {
+        report.error(format!("binarylibs directory {} is missing", tools_dir.display()), fix);
+        return;
+    }
+
+    let gcc_dir = tools_dir.join("buildtools").join("gcc7.3");
+    let gcc = gcc_dir.join("gcc").join("bin").join("gcc");
+    let version = Command::new(&gcc)
+        .arg("-dumpversion")
+        .env("LD_LIBRARY_PATH", gcc_library_path(&gcc_dir))
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string());
+    match version {
+        Some(version) if version == BUNDLED_GCC_VERSION => {
+            report.ok(format!("gcc {} at {}", version, gcc.display()))
+        }
+        Some(version) => report.error(
+            format!(
+                "{} is gcc {}, but openGauss is configured with `--gcc-version={}`",
+                gcc.display(),
+                version,
+                BUNDLED_GCC_VERSION
+            ),
+            fix.clone(),
+        ),
+        None => report.error(format!("{} doesn't run", gcc.display()), fix.clone()),
+    }
+
+    match pg_config.gcc_include_dir() {
+        Ok(include) if include.is_dir() => {
+            report.ok(format!("gcc headers in {}", include.display()))
+        }
+        _ => report.error("gcc 7.3's headers, which bindgen needs, are missing", fix),
+    }
+}
+
+fn gcc_library_path(gcc_dir: &Path) -> String {
+    ["gcc/lib64", "isl/lib", "mpc/lib", "mpfr/lib", "gmp/lib"]
+        .iter()
+        .map(|lib| gcc_dir.join(lib).display().to_string())
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// The ports of `cargo ogx run` and `cargo ogx test` are free, or held by ogx' own openGauss
+fn check_ports(report: &mut Report, pg_config: &PgConfig) {
+    let running = status_opengauss(pg_config).unwrap_or(false);
+    let ports = [("run", pg_config.port()), ("test", pg_config.test_port())];
+    for (purpose, port) in ports {
+        let port = match port {
+            Ok(port) => port,
+            Err(e) => {
+                report.error(format!("{}", e), "rerun `cargo ogx init`");
+                continue;
+            }
+        };
+        if purpose == "run" && running {
+            report.ok(format!("port {} is used by the running openGauss", port));
+            continue;
+        }
+        match TcpListener::bind(("localhost", port)) {
+            Ok(_) => report.ok(format!("{} port {} is free", purpose, port)),
+            Err(e) => report.error(
+                format!("{} port {} is unavailable: {}", purpose, port, e),
+                match pg_config.ports() {
+                    Some(_) => format!(
+                        "stop whatever listens on {}, or change the ports under `[ports.{}]` in \
+                         `config.toml`",
+                        port,
+                        pg_config.label().unwrap_or_default()
+                    ),
+                    None => format!(
+                        "stop whatever listens on {}, or pick other ports with `cargo ogx init \
+                         --base-port <PORT> --base-testing-port <PORT>`",
+                        port
+                    ),
+                },
+            ),
+        }
+    }
+}
+
+/// The data directory `cargo ogx init` creates with gs_initdb is intact and startable
+fn check_data_dir(report: &mut Report, pg_config: &PgConfig) {
+    let datadir = match pg_config.data_dir() {
+        Ok(datadir) => datadir,
+        Err(e) => {
+            report.error(format!("{}", e), "rerun `cargo ogx init`");
+            return;
+        }
+    };
+    if !datadir.exists() {
+        report.warning(
+            format!("data directory {} doesn't exist", datadir.display()),
+            "run `cargo ogx init` as an unprivileged user to create it",
+        );
+        return;
+    }
+
+    let mut healthy = true;
+    for file in ["PG_VERSION", "postgresql.conf", "pg_hba.conf"] {
+        if !datadir.join(file).exists() {
+            report.error(
+                format!("{} has no {}", datadir.display(), file),
+                format!("remove {} and rerun `cargo ogx init` to recreate it", datadir.display()),
+            );
+            healthy = false;
+        }
+    }
+
+    if let Ok(metadata) = std::fs::metadata(&datadir) {
+        let mode = metadata.permissions().mode() & 0o777;
+        if mode & 0o077 != 0 {
+            report.error(
+                format!(
+                    "{} has permissions {:o}, which openGauss refuses",
+                    datadir.display(),
+                    mode
+                ),
+                format!("chmod 700 {}", datadir.display()),
+            );
+            healthy = false;
+        }
+    }
+
+    let pid_file = datadir.join("postmaster.pid");
+    if pid_file.exists() && !status_opengauss(pg_config).unwrap_or(false) {
+        report.warning(
+            format!("{} is left over from a crashed openGauss", pid_file.display()),
+            format!("remove {} if no openGauss is using {}", pid_file.display(), datadir.display()),
+        );
+        healthy = false;
+    }
+
+    if healthy {
+        report.ok(format!("data directory {}", datadir.display()));
+    }
+}
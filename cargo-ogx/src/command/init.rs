@@ -12,15 +12,17 @@ use crate::command::version::ogx_default;
 use crate::CommandExecute;
 use eyre::{eyre, WrapErr};
 use owo_colors::OwoColorize;
-use ogx_pg_config::{prefix_path, PgConfig, PgConfigSelector, Ogx, SUPPORTED_MAJOR_VERSIONS};
+use ogx_pg_config::{
+    prefix_path, ConfigToml, Installation, PgConfig, PgConfigSelector, Ogx, Ports,
+    BASE_OPENGAUSS_PORT_NO, BASE_OPENGAUSS_TESTING_PORT_NO, PGXS_MK, SUPPORTED_MAJOR_VERSIONS,
+};
 use rayon::prelude::*;
 use sysinfo::{System, SystemExt};
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::env;
-use std::fs::File;
 use std::io::{Read, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
 
 use std::sync::{Arc, Mutex};
@@ -44,9 +46,12 @@ static PROCESS_ENV_DENYLIST: &'static [&'static str] = &[
 #[derive(clap::Args, Debug)]
 #[clap(author)]
 pub(crate) struct Init {
-    /// If installed locally, the path to OG3's `pgconfig` tool, or `download` to have ogx download/compile/install it
+    /// If installed locally, the path to OG3's `pgconfig` tool, or to the root or `gaussdb` of an installation without one, or `download` to have ogx download/compile/install it
     #[clap(env = "OG3_PG_CONFIG", long, help = "")]
     og3: Option<String>,
+    /// Also register the installation at PATH as NAME, like `og3-debug=/opt/og3-debug/bin/pg_config`, to keep several of one version
+    #[clap(long, value_name = "NAME=PATH")]
+    named: Vec<String>,
     #[clap(from_global, parse(from_occurrences))]
     verbose: usize,
     #[clap(long, help = "Base port number")]
@@ -64,7 +69,7 @@ impl CommandExecute for Init {
             versions.insert("og3", version.clone());
         }
 
-        if versions.is_empty() {
+        if versions.is_empty() && self.named.is_empty() {
             // no arguments specified, so we'll just install our defaults
             init_ogx(&ogx_default(SUPPORTED_MAJOR_VERSIONS)?, &self)
         } else {
@@ -84,16 +89,145 @@ impl CommandExecute for Init {
                         .wrap_err_with(|| format!("{} is not a known openGauss version", ogver))?
                         .clone()
                 } else {
-                    PgConfig::new_with_defaults(pg_config_path.into())
+                    pg_config_for_path(&pg_config_path)?
                 };
                 ogx.push(config);
             }
 
+            for named in &self.named {
+                let (name, path) = parse_named(named)?;
+                ogx.push(pg_config_for_path(path)?.with_name(name));
+            }
+
             init_ogx(&ogx, &self)
         }
     }
 }
 
+/// Split a `--named NAME=PATH` into its name and path
+fn parse_named(named: &str) -> eyre::Result<(&str, &str)> {
+    let (name, path) = named
+        .split_once('=')
+        .ok_or_else(|| eyre!("`--named {}` is not of the form NAME=PATH", named))?;
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(eyre!(
+            "`{}` is not a valid installation name, use letters, digits, `-` and `_`",
+            name
+        ));
+    }
+    if path == "download" {
+        return Err(eyre!("only `--og3` can download openGauss, `--named {}` needs a path", name));
+    }
+    Ok((name, path))
+}
+
+/// The `pg_config` at `path`, or the installation without one that `path` is the root or
+/// `gaussdb` binary of
+fn pg_config_for_path(path: &str) -> eyre::Result<PgConfig> {
+    let path = PathBuf::from(path);
+    if path.is_dir() || path.file_name() == Some("gaussdb".as_ref()) {
+        Ok(PgConfig::from_installation(
+            discover_installation(&path)?,
+            BASE_OPENGAUSS_PORT_NO,
+            BASE_OPENGAUSS_TESTING_PORT_NO,
+        ))
+    } else {
+        Ok(PgConfig::new_with_defaults(path))
+    }
+}
+
+/// Find the directories `pg_config` would report for the installation at `path`
+///
+/// The lite and enterprise packages lay their installation out like `make install` does, but
+/// don't ship `pg_config`.  `gs_om` installs into `app_<commit>/` with an `app` symlink to it next
+/// to it, and the directory containing `app` is accepted too.
+#[tracing::instrument(level = "error", skip_all, fields(path = %path.display()))]
+fn discover_installation(path: &Path) -> eyre::Result<Installation> {
+    let mut root = if path.is_dir() {
+        path.to_path_buf()
+    } else {
+        // `<root>/bin/gaussdb`
+        path.parent().and_then(Path::parent).map(Path::to_path_buf).unwrap_or_default()
+    };
+    if root.is_relative() {
+        root = env::current_dir()?.join(root);
+    }
+    if !root.join("bin/gaussdb").exists() && root.join("app/bin/gaussdb").exists() {
+        root.push("app");
+    }
+
+    let bindir = root.join("bin");
+    let gaussdb = bindir.join("gaussdb");
+    if !gaussdb.exists() {
+        return Err(eyre!(
+            "{} has no `bin/gaussdb`, is it an openGauss installation?",
+            root.display()
+        ));
+    }
+    println!("{} openGauss installation at {}", "  Discovering".bold().green(), root.display());
+
+    let includedir_server =
+        find_dir(&root, &["include/postgresql/server", "include/server"], "postgres.h")
+            .ok_or_else(|| {
+                eyre!(
+                    "{} has no server headers (`postgres.h`), which ogx needs to build extensions.  \
+                     Install the package's development headers into it.",
+                    root.display()
+                )
+            })?;
+    let pkglibdir = find_dir(&root, &["lib/postgresql", "lib"], "plpgsql.so")
+        .ok_or_else(|| eyre!("{} has no `plpgsql.so` under `lib/`", root.display()))?;
+    if !pkglibdir.join(PGXS_MK).exists() {
+        return Err(eyre!(
+            "{} has no `{}`, which ogx needs to build extensions.  Install the package's \
+             development files into it.",
+            pkglibdir.display(),
+            PGXS_MK
+        ));
+    }
+    let sharedir = find_dir(&root, &["share/postgresql", "share"], "extension")
+        .ok_or_else(|| eyre!("{} has no `extension/` directory under `share/`", root.display()))?;
+    let version = gaussdb_version(&gaussdb, &root)?;
+
+    Ok(Installation {
+        root,
+        version,
+        bindir,
+        includedir_server,
+        pkglibdir,
+        sharedir,
+        gcc_include_dir: None,
+    })
+}
+
+/// The first of `candidates` under `root` that contains `marker`
+fn find_dir(root: &Path, candidates: &[&str], marker: &str) -> Option<PathBuf> {
+    candidates.iter().map(|candidate| root.join(candidate)).find(|dir| dir.join(marker).exists())
+}
+
+/// The `X.Y.Z` of `gaussdb --version`, such as `gaussdb (openGauss 3.1.0 build 4e931f9a) ...`
+fn gaussdb_version(gaussdb: &Path, root: &Path) -> eyre::Result<String> {
+    let mut command = std::process::Command::new(gaussdb);
+    command
+        .arg("--version")
+        .env("GAUSSHOME", root)
+        .env("LD_LIBRARY_PATH", root.join("lib"));
+    let command_str = format!("{:?}", command);
+    tracing::debug!(command = %command_str, "Running");
+    let output = command.output().wrap_err_with(|| eyre!("unable to execute: {}", command_str))?;
+    let version_string = String::from_utf8_lossy(&output.stdout);
+
+    let version = regex::Regex::new(r"\b\d+\.\d+\.\d+\b")?;
+    match version.find(&version_string) {
+        Some(found) => Ok(found.as_str().to_string()),
+        None => Err(eyre!(
+            "couldn't find an openGauss version in the output of {}: {}",
+            command_str,
+            version_string.trim()
+        )),
+    }
+}
+
 #[tracing::instrument(skip_all, fields(ogx_home = %Ogx::home()?.display()))]
 pub(crate) fn init_ogx(ogx: &Ogx, init: &Init) -> eyre::Result<()> {
     let dir = Ogx::home()?;
@@ -568,11 +702,11 @@ fn make_install_opengauss(version: &PgConfig, ogdir: &PathBuf) -> eyre::Result<P
 }
 
 fn validate_pg_config(pg_config: &PgConfig) -> eyre::Result<()> {
-    println!(
-        "{} {}",
-        "   Validating".bold().green(),
-        pg_config.path().expect("no path for pg_config").display()
-    );
+    let location = match pg_config.installation() {
+        Some(installation) => installation.root.clone(),
+        None => pg_config.path().expect("no path for pg_config"),
+    };
+    println!("{} {}", "   Validating".bold().green(), location.display());
 
     pg_config.includedir_server()?;
     pg_config.pkglibdir()?;
@@ -580,31 +714,73 @@ fn validate_pg_config(pg_config: &PgConfig) -> eyre::Result<()> {
 }
 
 fn write_config(pg_configs: &Vec<PgConfig>, init: &Init) -> eyre::Result<()> {
-    let config_path = Ogx::config_toml()?;
-    let mut file = File::create(&config_path)?;
-
-    if let Some(port) = init.base_port {
-        file.write_all(format!("base_port = {}\n", port).as_bytes())?;
-    }
-    if let Some(port) = init.base_testing_port {
-        file.write_all(format!("base_testing_port = {}\n", port).as_bytes())?;
-    }
-
-    file.write_all(b"[configs]\n")?;
+    let mut config = ConfigToml {
+        base_port: init.base_port,
+        base_testing_port: init.base_testing_port,
+        ..Default::default()
+    };
+    config.ports = assign_ports(
+        pg_configs,
+        init.base_port.unwrap_or(BASE_OPENGAUSS_PORT_NO),
+        init.base_testing_port.unwrap_or(BASE_OPENGAUSS_TESTING_PORT_NO),
+    )?;
     for pg_config in pg_configs {
-        file.write_all(
-            format!(
-                "{}=\"{}\"\n",
-                pg_config.label()?,
-                pg_config.path().ok_or(eyre!("no path for pg_config"))?.display()
-            )
-            .as_bytes(),
-        )?;
+        match pg_config.installation() {
+            Some(installation) => {
+                config.installations.insert(pg_config.label()?, installation.clone());
+            }
+            None => {
+                let path = pg_config.path().ok_or(eyre!("no path for pg_config"))?;
+                config.configs.insert(pg_config.label()?, path);
+            }
+        }
     }
 
+    let config_path = Ogx::config_toml()?;
+    std::fs::write(&config_path, toml::to_string(&config)?)
+        .wrap_err_with(|| format!("Could not write `{}`", config_path.display()))?;
     Ok(())
 }
 
+/// Ports for the installations which can't use those of their version, because another installation
+/// of it already does
+///
+/// The installations going by `og$VERSION` keep its ports, and the others take the next free ones,
+/// so several installations of a version can run at once.
+fn assign_ports(
+    pg_configs: &[PgConfig],
+    base_port: u16,
+    base_testing_port: u16,
+) -> eyre::Result<BTreeMap<String, Ports>> {
+    let mut ordered = Vec::new();
+    for pg_config in pg_configs {
+        let major = pg_config.major_version()?;
+        let label = pg_config.label()?;
+        ordered.push((label != format!("og{}", major), label, major));
+    }
+    ordered.sort();
+
+    // `cargo ogx upgrade-test` runs its server on the port after the test port
+    let wanted =
+        |offset| [base_port + offset, base_testing_port + offset, base_testing_port + offset + 1];
+    let mut taken = HashSet::new();
+    let mut ports = BTreeMap::new();
+    for (_, label, major) in ordered {
+        let mut offset = major;
+        while wanted(offset).iter().any(|port| taken.contains(port)) {
+            offset += 1;
+        }
+        taken.extend(wanted(offset));
+        if offset != major {
+            ports.insert(
+                label,
+                Ports { port: base_port + offset, test_port: base_testing_port + offset },
+            );
+        }
+    }
+    Ok(ports)
+}
+
 fn get_opengauss_installdir(ogdir: &PathBuf) -> PathBuf {
     let mut dir = PathBuf::from(ogdir);
     dir.pop();
@@ -629,7 +805,7 @@ fn is_openeuler_2203() -> bool {
     }
 }
 
-fn is_root_user() -> bool {
+pub(crate) fn is_root_user() -> bool {
     match env::var("USER") {
         Ok(val) => val == "root",
         Err(_) => false,
@@ -667,3 +843,43 @@ pub(crate) fn initdb(bindir: &PathBuf, datadir: &PathBuf) -> eyre::Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::assign_ports;
+    use ogx_pg_config::{Installation, PgConfig, Ports};
+
+    fn installation(name: &str, version: &str) -> PgConfig {
+        let root = format!("/opt/{}", name);
+        let installation = Installation {
+            root: root.clone().into(),
+            version: version.to_string(),
+            bindir: format!("{}/bin", root).into(),
+            includedir_server: format!("{}/include/postgresql/server", root).into(),
+            pkglibdir: format!("{}/lib/postgresql", root).into(),
+            sharedir: format!("{}/share/postgresql", root).into(),
+            gcc_include_dir: None,
+        };
+        PgConfig::from_installation(installation, 28800, 32200).with_name(name)
+    }
+
+    #[test]
+    fn each_installation_of_a_version_has_its_own_ports() {
+        let pg_configs = [
+            installation("og3-debug", "3.1.0"),
+            installation("og3", "3.1.0"),
+            installation("og5", "5.0.1"),
+            installation("og3-release", "3.0.0"),
+        ];
+        let ports = assign_ports(&pg_configs, 28800, 32200).unwrap();
+        // `og3` and `og5` keep their version's ports, and the others skip the ports those, and
+        // their `upgrade-test` servers, use
+        assert_eq!(
+            ports.into_iter().collect::<Vec<_>>(),
+            [
+                (String::from("og3-debug"), Ports { port: 28807, test_port: 32207 }),
+                (String::from("og3-release"), Ports { port: 28809, test_port: 32209 }),
+            ]
+        );
+    }
+}
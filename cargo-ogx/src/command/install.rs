@@ -13,7 +13,7 @@ use crate::CommandExecute;
 use cargo_toml::Manifest;
 use eyre::{eyre, WrapErr};
 use owo_colors::OwoColorize;
-use ogx_pg_config::{get_target_dir, PgConfig};
+use ogx_pg_config::{get_target_dir, Ogx, PgConfig};
 use std::io::BufReader;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
@@ -37,7 +37,7 @@ pub(crate) struct Install {
     /// Build in test mode (for `cargo ogx test`)
     #[clap(long)]
     test: bool,
-    /// The `pg_config` path (default is first in $PATH)
+    /// The `pg_config` path (default is first in $PATH), or the version, like `og3`, or name of an installation `cargo ogx init` registered
     #[clap(long, short = 'c')]
     pg_config: Option<String>,
     #[clap(flatten)]
@@ -58,15 +58,11 @@ impl CommandExecute for Install {
         let package_manifest =
             Manifest::from_path(&package_manifest_path).wrap_err("Couldn't parse manifest")?;
 
-        let pg_config = match self.pg_config {
-            None => PgConfig::from_path(),
-            Some(config) => PgConfig::new_with_defaults(PathBuf::from(config)),
-        };
-        let pg_version = format!("pg{}", pg_config.major_version()?);
+        let pg_config = pg_config_from_arg(self.pg_config.as_ref())?;
         let profile = CargoProfile::from_flags(self.release, self.profile.as_deref())?;
 
         let features =
-            crate::manifest::features_for_version(self.features, &package_manifest, &pg_version);
+            crate::manifest::features_for_version(self.features, &package_manifest, &pg_config)?;
 
         install_extension(
             self.manifest_path.as_ref(),
@@ -81,6 +77,20 @@ impl CommandExecute for Install {
     }
 }
 
+/// The `pg_config` given by `--pg-config`, or the installation it names by version or name
+pub(crate) fn pg_config_from_arg(pg_config: Option<impl AsRef<Path>>) -> eyre::Result<PgConfig> {
+    let pg_config = match pg_config {
+        Some(pg_config) => pg_config.as_ref().to_path_buf(),
+        None => return Ok(PgConfig::from_path()),
+    };
+    match pg_config.to_str() {
+        Some(label) if !pg_config.exists() && !label.contains('/') => {
+            Ok(Ogx::from_config()?.get(label)?.clone())
+        }
+        _ => Ok(PgConfig::new_with_defaults(pg_config)),
+    }
+}
+
 #[tracing::instrument(skip_all, fields(
     pg_version = %pg_config.version()?,
     profile = ?profile,
@@ -115,7 +125,7 @@ pub(crate) fn install_extension(
     let versioned_so = get_property(&package_manifest_path, "module_pathname")?.is_none();
 
     let build_command_output =
-        build_extension(user_manifest_path.as_ref(), user_package, &profile, &features)?;
+        build_extension(pg_config, user_manifest_path.as_ref(), user_package, &profile, &features)?;
     let build_command_bytes = build_command_output.stdout;
     let build_command_reader = BufReader::new(build_command_bytes.as_slice());
     let build_command_stream = cargo_metadata::Message::parse_stream(build_command_reader);
@@ -214,6 +224,7 @@ fn copy_file(
 }
 
 pub(crate) fn build_extension(
+    pg_config: &PgConfig,
     user_manifest_path: Option<impl AsRef<Path>>,
     user_package: Option<&String>,
     profile: &CargoProfile,
@@ -251,6 +262,11 @@ pub(crate) fn build_extension(
 
     command.arg("--message-format=json-render-diagnostics");
 
+    // `ogx-pg-sys` has to bind against the same installation we're installing into
+    if let Some(name) = pg_config.name() {
+        command.env("OGX_INSTALLATION", name);
+    }
+
     for arg in flags.split_ascii_whitespace() {
         command.arg(arg);
     }
@@ -309,6 +325,7 @@ fn copy_sql_files(
         features,
         Some(&dest),
         Option::<String>::None,
+        Option::<String>::None,
         None,
         skip_build,
     )?;
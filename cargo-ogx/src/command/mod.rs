@@ -7,12 +7,15 @@ All rights reserved.
 Use of this source code is governed by the MIT license that can be found in the LICENSE file.
 */
 
+pub(crate) mod bench;
 pub(crate) mod connect;
+pub(crate) mod doctor;
 pub(crate) mod get;
 pub(crate) mod init;
 pub(crate) mod install;
 pub(crate) mod new;
 pub(crate) mod package;
+pub(crate) mod regress;
 pub(crate) mod ogx;
 pub(crate) mod run;
 pub(crate) mod schema;
@@ -20,4 +23,5 @@ pub(crate) mod start;
 pub(crate) mod status;
 pub(crate) mod stop;
 pub(crate) mod test;
+pub(crate) mod upgrade_test;
 pub(crate) mod version;
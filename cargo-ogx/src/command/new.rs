@@ -7,9 +7,9 @@ All rights reserved.
 Use of this source code is governed by the MIT license that can be found in the LICENSE file.
 */
 
-use eyre::eyre;
+use eyre::{eyre, WrapErr};
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 use crate::CommandExecute;
@@ -20,16 +20,80 @@ use crate::CommandExecute;
 pub(crate) struct New {
     /// The name of the extension
     name: String,
+    /// The kind of extension to start from
+    #[clap(long, arg_enum, default_value = "basic")]
+    template: Template,
+    /// Copy this directory instead of a built-in template, replacing `{name}` in its paths and files
+    #[clap(long, parse(from_os_str), conflicts_with = "template")]
+    template_dir: Option<PathBuf>,
     #[clap(from_global, parse(from_occurrences))]
     verbose: usize,
 }
 
+#[derive(clap::ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Template {
+    /// A function and its test
+    Basic,
+    /// A `#[derive(OgType)]` type stored in a table
+    CustomType,
+    /// A background worker, loaded through `shared_preload_libraries`
+    Bgworker,
+    /// A foreign data wrapper
+    Fdw,
+    /// An executor hook, loaded through `shared_preload_libraries`
+    Hooks,
+    /// Counters in shared memory, loaded through `shared_preload_libraries`
+    SharedMemory,
+    /// A `#[pg_aggregate]`
+    Aggregate,
+}
+
+impl Template {
+    fn lib_rs(self, name: &str) -> String {
+        match self {
+            Template::Basic => format!(include_str!("../templates/lib_rs"), name = name),
+            Template::CustomType => {
+                format!(include_str!("../templates/custom_type_lib_rs"), name = name)
+            }
+            Template::Bgworker => {
+                format!(include_str!("../templates/bgworker_lib_rs"), name = name)
+            }
+            Template::Fdw => format!(include_str!("../templates/fdw_lib_rs"), name = name),
+            Template::Hooks => format!(include_str!("../templates/hooks_lib_rs"), name = name),
+            Template::SharedMemory => {
+                format!(include_str!("../templates/shared_memory_lib_rs"), name = name)
+            }
+            Template::Aggregate => {
+                format!(include_str!("../templates/aggregate_lib_rs"), name = name)
+            }
+        }
+    }
+
+    /// Dependencies the template's `lib.rs` needs besides `ogx`
+    fn dependencies(self) -> &'static str {
+        match self {
+            Template::CustomType | Template::Aggregate => {
+                "serde = { version = \"1.0\", features = [\"derive\"] }\n"
+            }
+            _ => "",
+        }
+    }
+
+    /// Whether the extension only works when loaded through `shared_preload_libraries`
+    fn needs_preload(self) -> bool {
+        matches!(self, Template::Bgworker | Template::Hooks | Template::SharedMemory)
+    }
+}
+
 impl CommandExecute for New {
     #[tracing::instrument(level = "error", skip(self))]
     fn execute(self) -> eyre::Result<()> {
         validate_extension_name(&self.name)?;
         let path = PathBuf::from_str(&format!("{}/", self.name)).unwrap();
-        create_crate_template(path, &self.name)
+        match self.template_dir {
+            Some(template_dir) => copy_template_dir(&template_dir, &path, &self.name),
+            None => create_crate_template(path, &self.name, self.template),
+        }
     }
 }
 
@@ -45,15 +109,60 @@ fn validate_extension_name(extname: &str) -> eyre::Result<()> {
 #[tracing::instrument(skip_all, fields(path, name))]
 pub(crate) fn create_crate_template(
     path: PathBuf,
-    name: &str
+    name: &str,
+    template: Template,
 ) -> eyre::Result<()> {
     create_directory_structure(&path)?;
     create_control_file(&path, name)?;
-    create_cargo_toml(&path, name)?;
+    create_cargo_toml(&path, name, template)?;
     create_dotcargo_config_toml(&path, name)?;
-    create_lib_rs(&path, name)?;
+    create_lib_rs(&path, name, template)?;
     create_git_ignore(&path, name)?;
+    if template.needs_preload() {
+        create_postgresql_conf(&path, name)?;
+    }
+
+    Ok(())
+}
+
+/// Copy the user's `template_dir` to `path`, replacing `{name}` with the extension's name in
+/// every file and directory name, and in the contents of every UTF-8 file
+#[tracing::instrument(skip_all, fields(template_dir = %template_dir.display(), path, name))]
+fn copy_template_dir(template_dir: &Path, path: &Path, name: &str) -> eyre::Result<()> {
+    if !template_dir.join("Cargo.toml").is_file() {
+        return Err(eyre!(
+            "`{}` is not a template directory, it has no Cargo.toml",
+            template_dir.display()
+        ));
+    }
+    copy_template_entries(template_dir, path, name)
+        .wrap_err_with(|| format!("couldn't copy the template `{}`", template_dir.display()))
+}
+
+fn copy_template_entries(from: &Path, to: &Path, name: &str) -> eyre::Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let file_name = entry.file_name();
+        let file_name = file_name
+            .to_str()
+            .ok_or_else(|| eyre!("`{}` is not a UTF-8 path", entry.path().display()))?;
+        if file_name == ".git" || file_name == "target" {
+            continue;
+        }
 
+        let dest = to.join(file_name.replace("{name}", name));
+        if entry.file_type()?.is_dir() {
+            copy_template_entries(&entry.path(), &dest, name)?;
+        } else {
+            let contents = std::fs::read(entry.path())?;
+            let contents = match String::from_utf8(contents) {
+                Ok(text) => text.replace("{name}", name).into_bytes(),
+                Err(not_text) => not_text.into_bytes(),
+            };
+            std::fs::write(dest, contents)?;
+        }
+    }
     Ok(())
 }
 
@@ -83,13 +192,20 @@ fn create_control_file(path: &PathBuf, name: &str) -> Result<(), std::io::Error>
     Ok(())
 }
 
-fn create_cargo_toml(path: &PathBuf, name: &str) -> Result<(), std::io::Error> {
+fn create_cargo_toml(path: &PathBuf, name: &str, template: Template) -> Result<(), std::io::Error> {
     let mut filename = path.clone();
 
     filename.push("Cargo.toml");
     let mut file = std::fs::File::create(filename)?;
 
-    file.write_all(&format!(include_str!("../templates/cargo_toml"), name = name).as_bytes())?;
+    file.write_all(
+        &format!(
+            include_str!("../templates/cargo_toml"),
+            name = name,
+            dependencies = template.dependencies()
+        )
+        .as_bytes(),
+    )?;
 
     Ok(())
 }
@@ -106,13 +222,13 @@ fn create_dotcargo_config_toml(path: &PathBuf, _name: &str) -> Result<(), std::i
     Ok(())
 }
 
-fn create_lib_rs(path: &PathBuf, name: &str) -> Result<(), std::io::Error> {
+fn create_lib_rs(path: &PathBuf, name: &str, template: Template) -> Result<(), std::io::Error> {
     let mut filename = path.clone();
 
     filename.push("src");
     filename.push("lib.rs");
     let mut file = std::fs::File::create(filename)?;
-    file.write_all(&format!(include_str!("../templates/lib_rs"), name = name).as_bytes())?;
+    file.write_all(template.lib_rs(name).as_bytes())?;
 
     Ok(())
 }
@@ -127,3 +243,11 @@ fn create_git_ignore(path: &PathBuf, _name: &str) -> Result<(), std::io::Error>
 
     Ok(())
 }
+
+fn create_postgresql_conf(path: &Path, name: &str) -> Result<(), std::io::Error> {
+    let mut file = std::fs::File::create(path.join("postgresql.conf"))?;
+
+    file.write_all(format!(include_str!("../templates/postgresql_conf"), name = name).as_bytes())?;
+
+    Ok(())
+}
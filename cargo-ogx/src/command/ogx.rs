@@ -35,8 +35,12 @@ enum CargoOgxSubCommands {
     Schema(super::schema::Schema),
     Run(super::run::Run),
     Connect(super::connect::Connect),
+    Regress(super::regress::Regress),
+    Bench(super::bench::Bench),
     Test(super::test::Test),
+    UpgradeTest(super::upgrade_test::UpgradeTest),
     Get(super::get::Get),
+    Doctor(super::doctor::Doctor),
 }
 
 impl CommandExecute for CargoOgxSubCommands {
@@ -53,8 +57,12 @@ impl CommandExecute for CargoOgxSubCommands {
             Schema(c) => c.execute(),
             Run(c) => c.execute(),
             Connect(c) => c.execute(),
+            Regress(c) => c.execute(),
+            Bench(c) => c.execute(),
             Test(c) => c.execute(),
+            UpgradeTest(c) => c.execute(),
             Get(c) => c.execute(),
+            Doctor(c) => c.execute(),
         }
     }
 }
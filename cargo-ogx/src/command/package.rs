@@ -7,13 +7,18 @@ All rights reserved.
 Use of this source code is governed by the MIT license that can be found in the LICENSE file.
 */
 
-use crate::command::install::install_extension;
+use crate::archive::{self, PackageMetadata};
+use crate::command::install::{
+    format_display_path, get_version, install_extension, pg_config_from_arg,
+};
 use crate::CommandExecute;
 use crate::{command::get::get_property, profile::CargoProfile};
 use cargo_toml::Manifest;
 use eyre::{eyre, WrapErr};
 use ogx_pg_config::{get_target_dir, PgConfig};
+use owo_colors::OwoColorize;
 use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 /// Create an installation package directory.
 #[derive(clap::Args, Debug)]
@@ -34,12 +39,15 @@ pub(crate) struct Package {
     /// Build in test mode (for `cargo ogx test`)
     #[clap(long)]
     test: bool,
-    /// The `pg_config` path (default is first in $PATH)
+    /// The `pg_config` path (default is first in $PATH), or the version, like `og3`, or name of an installation `cargo ogx init` registered
     #[clap(long, short = 'c', parse(from_os_str))]
     pg_config: Option<PathBuf>,
-    /// The directory to output the package (default is `./target/[debug|release]/extname-ogXX/`)
+    /// The directory to output the package (default is `./target/[debug|release]/extname-ogXX/`, or `./target/[debug|release]/` for the `deb`, `rpm` and `tar.gz` formats)
     #[clap(long, parse(from_os_str))]
     out_dir: Option<PathBuf>,
+    /// Package as a directory laid out like the `pg_config` installation, or as a `.deb`, `.rpm` or `.tar.gz` of it
+    #[clap(long, arg_enum, default_value = "dir")]
+    format: PackageFormat,
     #[clap(flatten)]
     features: clap_cargo::Features,
     #[clap(from_global, parse(from_occurrences))]
@@ -58,15 +66,25 @@ impl CommandExecute for Package {
         let package_manifest =
             Manifest::from_path(&package_manifest_path).wrap_err("Couldn't parse manifest")?;
 
-        let pg_config = match self.pg_config {
-            None => PgConfig::from_path(),
-            Some(config) => PgConfig::new_with_defaults(PathBuf::from(config)),
-        };
-        let pg_version = format!("og{}", pg_config.major_version()?);
-
+        let pg_config = pg_config_from_arg(self.pg_config.as_ref())?;
         let features =
-            crate::manifest::features_for_version(self.features, &package_manifest, &pg_version);
+            crate::manifest::features_for_version(self.features, &package_manifest, &pg_config)?;
         let profile = CargoProfile::from_flags(!self.debug, self.profile.as_deref())?;
+        if self.format != PackageFormat::Dir {
+            return archive_extension(
+                self.manifest_path.as_ref(),
+                self.package.as_ref(),
+                &package_manifest_path,
+                &package_manifest,
+                &pg_config,
+                self.out_dir,
+                &profile,
+                self.test,
+                &features,
+                self.format,
+            );
+        }
+
         let out_dir = if let Some(out_dir) = self.out_dir {
             out_dir
         } else {
@@ -85,6 +103,15 @@ impl CommandExecute for Package {
     }
 }
 
+#[derive(clap::ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum PackageFormat {
+    Dir,
+    Deb,
+    Rpm,
+    #[clap(name = "tar.gz")]
+    TarGz,
+}
+
 #[tracing::instrument(level = "error", skip_all, fields(
     pg_version = %pg_config.version()?,
     profile = ?profile,
@@ -116,6 +143,103 @@ pub(crate) fn package_extension(
     )
 }
 
+/// Package the extension into a single `.deb`, `.rpm` or `.tar.gz` file, by laying it out in the
+/// same staging directory the `dir` format uses and archiving everything under it
+#[tracing::instrument(level = "error", skip_all, fields(
+    pg_version = %pg_config.version()?,
+    profile = ?profile,
+    test = is_test,
+    format = ?format,
+))]
+pub(crate) fn archive_extension(
+    user_manifest_path: Option<impl AsRef<Path>>,
+    user_package: Option<&String>,
+    package_manifest_path: impl AsRef<Path>,
+    package_manifest: &Manifest,
+    pg_config: &PgConfig,
+    out_dir: Option<PathBuf>,
+    profile: &CargoProfile,
+    is_test: bool,
+    features: &clap_cargo::Features,
+    format: PackageFormat,
+) -> eyre::Result<()> {
+    let staging_dir = build_base_path(pg_config, &package_manifest_path, profile)?;
+    // files from earlier builds, such as the schema of an older version, mustn't be packaged
+    if staging_dir.exists() {
+        std::fs::remove_dir_all(&staging_dir)
+            .wrap_err_with(|| format!("unable to remove {}", staging_dir.display()))?;
+    }
+    package_extension(
+        user_manifest_path,
+        user_package,
+        &package_manifest_path,
+        pg_config,
+        staging_dir.clone(),
+        profile,
+        is_test,
+        features,
+    )?;
+
+    let metadata =
+        package_metadata(&package_manifest_path, package_manifest, pg_config, &staging_dir)?;
+    let (file_name, contents) = match format {
+        PackageFormat::Dir => unreachable!("the `dir` format isn't an archive"),
+        PackageFormat::Deb => (archive::deb_file_name(&metadata), archive::deb(&metadata)?),
+        PackageFormat::Rpm => (archive::rpm_file_name(&metadata), archive::rpm(&metadata)?),
+        PackageFormat::TarGz => (archive::tar_gz_file_name(&metadata), archive::tar_gz(&metadata)?),
+    };
+
+    let out_dir = match out_dir {
+        Some(out_dir) => out_dir,
+        None => staging_dir.parent().ok_or(eyre!("staging directory has no parent"))?.to_path_buf(),
+    };
+    std::fs::create_dir_all(&out_dir)?;
+    let out_path = out_dir.join(file_name);
+    println!("{} {}", "     Writing".bold().green(), format_display_path(&out_path)?.cyan());
+    std::fs::write(&out_path, contents)
+        .wrap_err_with(|| format!("unable to write {}", out_path.display()))?;
+    Ok(())
+}
+
+/// Describe the package from `Cargo.toml` and the extension's `.control` file.  It's named after
+/// the extension and the openGauss major version it was built for, as it only works with that one
+fn package_metadata(
+    package_manifest_path: impl AsRef<Path>,
+    package_manifest: &Manifest,
+    pg_config: &PgConfig,
+    staging_dir: &Path,
+) -> eyre::Result<PackageMetadata> {
+    let extname = get_property(&package_manifest_path, "extname")?
+        .ok_or(eyre!("could not determine extension name"))?;
+    let cargo_package = package_manifest.package.as_ref();
+
+    let description = cargo_package
+        .and_then(|package| package.description.clone())
+        .or(get_property(&package_manifest_path, "comment")?)
+        .unwrap_or_else(|| format!("The {} extension for openGauss", extname));
+    let mtime = match std::env::var("SOURCE_DATE_EPOCH") {
+        Ok(epoch) => epoch.parse().wrap_err("SOURCE_DATE_EPOCH is not a number")?,
+        Err(_) => SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+    };
+
+    Ok(PackageMetadata {
+        // both Debian and rpm allow lowercase letters, digits and `-` in package names, and
+        // treat `~` as sorting before anything, as a pre-release version should
+        name: format!("{}-og{}", extname, pg_config.major_version()?)
+            .to_lowercase()
+            .replace('_', "-"),
+        version: get_version(&package_manifest_path)?.replace('-', "~"),
+        summary: description.lines().next().unwrap_or_default().to_string(),
+        description,
+        maintainer: cargo_package.and_then(|package| package.authors.first().cloned()),
+        license: cargo_package.and_then(|package| package.license.clone()),
+        homepage: cargo_package
+            .and_then(|package| package.homepage.clone().or_else(|| package.repository.clone())),
+        mtime,
+        files: archive::collect_files(staging_dir)?,
+    })
+}
+
 fn build_base_path(
     pg_config: &PgConfig,
     manifest_path: impl AsRef<Path>,
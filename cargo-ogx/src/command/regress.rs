@@ -0,0 +1,299 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+use crate::command::get::find_control_file;
+use crate::command::install::{format_display_path, install_extension};
+use crate::profile::CargoProfile;
+use crate::scratch::ScratchServer;
+use crate::CommandExecute;
+use cargo_toml::Manifest;
+use eyre::{eyre, WrapErr};
+use ogx_pg_config::{get_target_dir, Ogx, PgConfig, PgConfigSelector};
+use owo_colors::OwoColorize;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+const REGRESS_DATABASE: &str = "ogx_regress";
+
+/// Run the `sql/*.sql` regression tests of this crate and compare their output with `expected/*.out`
+#[derive(clap::Args, Debug)]
+#[clap(author)]
+pub(crate) struct Regress {
+    /// Do you want to run against openGauss `og3`?
+    #[clap(env = "OG_VERSION")]
+    pg_version: Option<String>,
+    /// If specified, only run regression tests containing this string in their names
+    testname: Option<String>,
+    /// Package to build (see `cargo help pkgid`)
+    #[clap(long, short)]
+    package: Option<String>,
+    /// Path to Cargo.toml
+    #[clap(long, parse(from_os_str))]
+    manifest_path: Option<PathBuf>,
+    /// Compile for release mode (default is debug)
+    #[clap(long, short)]
+    release: bool,
+    /// Specific profile to use (conflicts with `--release`)
+    #[clap(long)]
+    profile: Option<String>,
+    /// Accept the output of every test as its new expected output
+    #[clap(long)]
+    auto_accept: bool,
+    #[clap(flatten)]
+    features: clap_cargo::Features,
+    #[clap(from_global, parse(from_occurrences))]
+    verbose: usize,
+}
+
+impl CommandExecute for Regress {
+    #[tracing::instrument(level = "error", skip(self))]
+    fn execute(self) -> eyre::Result<()> {
+        let ogx = Ogx::from_config()?;
+
+        let metadata = crate::metadata::metadata(&self.features, self.manifest_path.as_ref())
+            .wrap_err("couldn't get cargo metadata")?;
+        crate::metadata::validate(&metadata)?;
+        let package_manifest_path =
+            crate::manifest::manifest_path(&metadata, self.package.as_ref())
+                .wrap_err("Couldn't get manifest path")?;
+        let package_manifest =
+            Manifest::from_path(&package_manifest_path).wrap_err("Couldn't parse manifest")?;
+
+        let pg_version = match self.pg_version {
+            Some(ref s) => s.clone(),
+            None => crate::manifest::default_og_version(&package_manifest)
+                .ok_or(eyre!("No provided `og$VERSION` flag."))?,
+        };
+        let profile = CargoProfile::from_flags(self.release, self.profile.as_deref())?;
+
+        for pg_config in ogx.iter(PgConfigSelector::new(&pg_version)) {
+            let mut testname = self.testname.clone();
+            let pg_config = match pg_config {
+                Err(error) => {
+                    tracing::debug!(
+                        invalid_pg_version = %pg_version,
+                        error = %error,
+                        "Got invalid `og$VERSION` flag, assuming it is a testname"
+                    );
+                    testname = Some(pg_version.clone());
+                    ogx.get(
+                        &crate::manifest::default_og_version(&package_manifest)
+                            .ok_or(eyre!("No provided `og$VERSION` flag."))?,
+                    )?
+                }
+                Ok(config) => config,
+            };
+            let features = crate::manifest::features_for_version(
+                self.features.clone(),
+                &package_manifest,
+                pg_config,
+            )?;
+
+            regress_extension(
+                pg_config,
+                self.manifest_path.as_ref(),
+                self.package.as_ref(),
+                &package_manifest_path,
+                &profile,
+                &features,
+                testname.as_deref(),
+                self.auto_accept,
+            )?
+        }
+
+        Ok(())
+    }
+}
+
+#[tracing::instrument(level = "error", skip_all, fields(
+    pg_version = %pg_config.version()?,
+    testname = tracing::field::Empty,
+    profile = ?profile,
+    auto_accept,
+))]
+pub(crate) fn regress_extension(
+    pg_config: &PgConfig,
+    user_manifest_path: Option<impl AsRef<Path>>,
+    user_package: Option<&String>,
+    package_manifest_path: impl AsRef<Path>,
+    profile: &CargoProfile,
+    features: &clap_cargo::Features,
+    testname: Option<&str>,
+    auto_accept: bool,
+) -> eyre::Result<()> {
+    if let Some(testname) = testname {
+        tracing::Span::current().record("testname", tracing::field::display(testname));
+    }
+    let (_, extname) = find_control_file(&package_manifest_path)?;
+    let package_dir = package_manifest_path
+        .as_ref()
+        .parent()
+        .ok_or(eyre!("manifest has no parent"))?
+        .to_path_buf();
+
+    let tests = find_tests(&package_dir.join("sql"), &extname, testname)?;
+    if tests.is_empty() {
+        println!("{} no regression tests in {}", "    Finished".bold().green(), "sql/".cyan());
+        return Ok(());
+    }
+
+    install_extension(
+        user_manifest_path,
+        user_package,
+        &package_manifest_path,
+        pg_config,
+        profile,
+        false,
+        None,
+        features,
+    )?;
+
+    let mut scratch_dir = get_target_dir()?;
+    scratch_dir.push("ogx-regress");
+    let results_dir = scratch_dir.join("results");
+    std::fs::create_dir_all(&results_dir)?;
+    let expected_dir = package_dir.join("expected");
+
+    let server = ScratchServer::init(pg_config, &scratch_dir, pg_config.test_port()?)?;
+    server.start()?;
+
+    let mut failed = Vec::new();
+    for (name, sql) in &tests {
+        // every test gets a database of its own, so they can't depend on each other's leftovers
+        server.sql("postgres", &format!("DROP DATABASE IF EXISTS {}", REGRESS_DATABASE))?;
+        server.sql("postgres", &format!("CREATE DATABASE {}", REGRESS_DATABASE))?;
+        server.sql(REGRESS_DATABASE, &format!("CREATE EXTENSION \"{}\"", extname))?;
+
+        let result = results_dir.join(format!("{}.out", name));
+        run_test(&server, sql, &result)?;
+
+        let expected = expected_dir.join(format!("{}.out", name));
+        if !expected.exists() {
+            if auto_accept {
+                std::fs::create_dir_all(&expected_dir)?;
+                std::fs::copy(&result, &expected)?;
+                println!("{} {} (new)", "    Accepted".bold().green(), name);
+            } else {
+                println!(
+                    "{} {}: {} does not exist",
+                    "      Failed".bold().red(),
+                    name,
+                    format_display_path(&expected)?.cyan()
+                );
+                failed.push(name);
+            }
+            continue;
+        }
+
+        match diff(&expected, &result)? {
+            None => println!("{} {}", "          ok".bold().green(), name),
+            Some(_) if auto_accept => {
+                std::fs::copy(&result, &expected)?;
+                println!("{} {}", "    Accepted".bold().green(), name);
+            }
+            Some(diff) => {
+                println!("{} {}", "      Failed".bold().red(), name);
+                print!("{}", diff);
+                failed.push(name);
+            }
+        }
+    }
+    server.stop()?;
+
+    if !failed.is_empty() {
+        return Err(eyre!(
+            "{} of {} regression tests failed, their output is in {}",
+            failed.len(),
+            tests.len(),
+            format_display_path(&results_dir)?
+        ));
+    }
+
+    println!("{} {} regression tests", "    Finished".bold().green(), tests.len());
+    Ok(())
+}
+
+/// The `sql/*.sql` files to run, by name, leaving out the extension's own upgrade scripts
+fn find_tests(
+    sql_dir: &Path,
+    extname: &str,
+    testname: Option<&str>,
+) -> eyre::Result<Vec<(String, PathBuf)>> {
+    let dir = match std::fs::read_dir(sql_dir) {
+        Ok(dir) => dir,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).wrap_err_with(|| format!("unable to read {}", sql_dir.display())),
+    };
+
+    let mut tests = Vec::new();
+    for entry in dir {
+        let path = entry?.path();
+        let name = match (path.file_stem(), path.extension()) {
+            (Some(name), Some(extension)) if extension == "sql" => {
+                name.to_string_lossy().into_owned()
+            }
+            _ => continue,
+        };
+        if name.starts_with(&format!("{}--", extname)) {
+            continue;
+        }
+        match testname {
+            Some(testname) if !name.contains(testname) => continue,
+            _ => tests.push((name, path)),
+        }
+    }
+    tests.sort();
+    Ok(tests)
+}
+
+/// Feed `sql` to `gsql` the way `pg_regress` does, echoing every statement and interleaving
+/// errors and NOTICEs with the output, and write all of it to `result`
+fn run_test(server: &ScratchServer, sql: &Path, result: &Path) -> eyre::Result<()> {
+    let mut command = server.gsql(REGRESS_DATABASE)?;
+    let output =
+        File::create(result).wrap_err_with(|| format!("unable to create {}", result.display()))?;
+    command
+        .arg("-a")
+        .arg("-q")
+        .env("PGTZ", "PST8PDT")
+        .env("PGDATESTYLE", "Postgres, MDY")
+        .stdin(File::open(sql).wrap_err_with(|| format!("unable to open {}", sql.display()))?)
+        .stderr(output.try_clone()?)
+        .stdout(output);
+
+    let command_str = format!("{:?}", command);
+    tracing::debug!(command = %command_str, "Running");
+    // errors raised by the test are part of its output and don't change the exit status, only
+    // failing to connect or read the file does
+    let status = command.status().wrap_err_with(|| eyre!("unable to execute: {}", command_str))?;
+    if !status.success() {
+        return Err(eyre!(
+            "problem running gsql: {}, see {}",
+            command_str,
+            format_display_path(result)?
+        ));
+    }
+    Ok(())
+}
+
+/// A unified diff from `expected` to `result`, if they differ
+fn diff(expected: &Path, result: &Path) -> eyre::Result<Option<String>> {
+    let output = Command::new("diff")
+        .arg("-U3")
+        .arg(expected)
+        .arg(result)
+        .stderr(Stdio::inherit())
+        .output()
+        .wrap_err("unable to execute diff")?;
+    match output.status.code() {
+        Some(0) => Ok(None),
+        Some(1) => Ok(Some(String::from_utf8_lossy(&output.stdout).into_owned())),
+        _ => Err(eyre!("diff exited with {}", output.status)),
+    }
+}
@@ -64,10 +64,10 @@ impl CommandExecute for Run {
 
         let ogx = Ogx::from_config()?;
 
-        let (pg_config, pg_version) = match self.pg_version {
+        let pg_config = match self.pg_version {
             Some(pg_version) => {
                 match ogx.get(&pg_version) {
-                    Ok(pg_config) => (pg_config, pg_version),
+                    Ok(pg_config) => pg_config,
                     Err(err) => {
                         if self.dbname.is_some() {
                             return Err(err);
@@ -77,7 +77,7 @@ impl CommandExecute for Run {
                         let default_og_version =
                             crate::manifest::default_og_version(&package_manifest)
                                 .ok_or(eyre!("No provided `og$VERSION` flag."))?;
-                        (ogx.get(&default_og_version)?, default_og_version)
+                        ogx.get(&default_og_version)?
                     }
                 }
             }
@@ -85,12 +85,12 @@ impl CommandExecute for Run {
                 // We should infer from the manifest.
                 let default_og_version = crate::manifest::default_og_version(&package_manifest)
                     .ok_or(eyre!("No provided `og$VERSION` flag."))?;
-                (ogx.get(&default_og_version)?, default_og_version)
+                ogx.get(&default_og_version)?
             }
         };
 
         let features =
-            crate::manifest::features_for_version(self.features, &package_manifest, &pg_version);
+            crate::manifest::features_for_version(self.features, &package_manifest, &pg_config)?;
 
         let dbname = match self.dbname {
             Some(dbname) => dbname,
@@ -7,9 +7,10 @@ All rights reserved.
 Use of this source code is governed by the MIT license that can be found in the LICENSE file.
 */
 use crate::command::get::{find_control_file, get_property};
-use crate::command::install::format_display_path;
+use crate::command::install::{format_display_path, get_version};
 use crate::ogx_pg_sys_stub::OgxPgSysStub;
 use crate::profile::CargoProfile;
+use crate::worktree::GitWorktree;
 use crate::CommandExecute;
 use cargo_toml::Manifest;
 use eyre::{eyre, WrapErr};
@@ -34,6 +35,9 @@ static POSTMASTER_LIBRARY: OnceCell<libloading::os::unix::Library> = OnceCell::n
 // otherwise users find issues such as https://github.com/tcdi/pgx/issues/572
 static EXTENSION_LIBRARY: OnceCell<libloading::os::unix::Library> = OnceCell::new();
 
+// The extension shared object of the version `--from` upgrades from, for the same reason
+static FROM_EXTENSION_LIBRARY: OnceCell<libloading::os::unix::Library> = OnceCell::new();
+
 /// Generate extension schema files
 #[derive(clap::Args, Debug)]
 #[clap(author)]
@@ -66,11 +70,19 @@ pub(crate) struct Schema {
     /// A path to output a produced GraphViz DOT file
     #[clap(long, short, parse(from_os_str))]
     dot: Option<PathBuf>,
+    /// A path to output the entity graph as JSON, with every entity's Rust path, SQL and
+    /// dependencies
+    #[clap(long, parse(from_os_str))]
+    json: Option<PathBuf>,
     #[clap(from_global, parse(from_occurrences))]
     verbose: usize,
     /// Skip building a fresh extension shared object.
     #[clap(long)]
     skip_build: bool,
+    /// Generate an `extname--old--new.sql` upgrade script from this version instead, given as a
+    /// git ref or a version tagged `v<VERSION>` (`--out` may be a directory)
+    #[clap(long, value_name = "VERSION|GIT_REF", conflicts_with_all = &["dot", "json"])]
+    from: Option<String>,
 }
 
 impl CommandExecute for Schema {
@@ -96,7 +108,7 @@ impl CommandExecute for Schema {
             }
         };
 
-        let (pg_config, pg_version) = match self.pg_config {
+        let pg_config = match self.pg_config {
             None => match self.pg_version {
                 None => {
                     let pg_version = match self.pg_version {
@@ -104,22 +116,32 @@ impl CommandExecute for Schema {
                         None => crate::manifest::default_og_version(&package_manifest)
                             .ok_or(eyre!("No provided `og$VERSION` flag."))?,
                     };
-                    (Ogx::from_config()?.get(&pg_version)?.clone(), pg_version)
+                    Ogx::from_config()?.get(&pg_version)?.clone()
                 }
-                Some(pgver) => (Ogx::from_config()?.get(&pgver)?.clone(), pgver),
+                Some(pgver) => Ogx::from_config()?.get(&pgver)?.clone(),
             },
-            Some(config) => {
-                let pg_config = PgConfig::new_with_defaults(PathBuf::from(config));
-                let pg_version = format!("og{}", pg_config.major_version()?);
-                (pg_config, pg_version)
-            }
+            Some(config) => PgConfig::new_with_defaults(PathBuf::from(config)),
         };
 
         let features =
-            crate::manifest::features_for_version(self.features, &package_manifest, &pg_version);
+            crate::manifest::features_for_version(self.features, &package_manifest, &pg_config)?;
 
         let profile = CargoProfile::from_flags(self.release, self.profile.as_deref())?;
 
+        if let Some(from) = &self.from {
+            return generate_upgrade_schema(
+                from,
+                &pg_config,
+                self.package.as_ref(),
+                package_manifest_path,
+                &profile,
+                self.test,
+                &features,
+                self.out.as_ref(),
+                log_level,
+            );
+        }
+
         generate_schema(
             &pg_config,
             self.manifest_path.as_ref(),
@@ -130,6 +152,7 @@ impl CommandExecute for Schema {
             &features,
             self.out.as_ref(),
             self.dot,
+            self.json,
             log_level,
             self.skip_build,
         )
@@ -142,6 +165,7 @@ impl CommandExecute for Schema {
     test = is_test,
     path = path.as_ref().map(|path| tracing::field::display(path.as_ref().display())),
     dot,
+    json,
     features = ?features.features,
 ))]
 pub(crate) fn generate_schema(
@@ -154,9 +178,183 @@ pub(crate) fn generate_schema(
     features: &clap_cargo::Features,
     path: Option<impl AsRef<std::path::Path>>,
     dot: Option<impl AsRef<std::path::Path>>,
+    json: Option<impl AsRef<std::path::Path>>,
     log_level: Option<String>,
     skip_build: bool,
 ) -> eyre::Result<()> {
+    let ogx_sql = build_ogx_sql(
+        pg_config,
+        user_manifest_path,
+        user_package,
+        package_manifest_path,
+        profile,
+        is_test,
+        features,
+        log_level,
+        skip_build,
+        None,
+        &EXTENSION_LIBRARY,
+    )?;
+
+    if let Some(out_path) = path {
+        let out_path = out_path.as_ref();
+
+        eprintln!(
+            "{} SQL entities to {}",
+            "     Writing".bold().green(),
+            format_display_path(out_path)?.cyan()
+        );
+
+        if let Some(parent) = out_path.parent() {
+            std::fs::create_dir_all(parent).wrap_err("Could not create parent directory")?
+        }
+        ogx_sql
+            .to_file(out_path)
+            .wrap_err_with(|| eyre!("Could not write SQL to {}", out_path.display()))?;
+    } else {
+        eprintln!("{} SQL entities to {}", "     Writing".bold().green(), "/dev/stdout".cyan(),);
+        ogx_sql
+            .write(&mut std::io::stdout())
+            .wrap_err_with(|| eyre!("Could not write SQL to stdout"))?;
+    }
+
+    if let Some(dot_path) = dot {
+        let dot_path = dot_path.as_ref();
+        tracing::info!(dot = %dot_path.display(), "Writing Graphviz DOT");
+        ogx_sql.to_dot(dot_path)?;
+    }
+
+    if let Some(json_path) = json {
+        let json_path = json_path.as_ref();
+        tracing::info!(json = %json_path.display(), "Writing entity graph JSON");
+        ogx_sql.to_json(json_path)?;
+    }
+    Ok(())
+}
+
+/// Generate the upgrade script from the extension as it was at `from`, a git ref or a version
+/// tagged `v<from>`, to the current source tree.
+///
+/// The old version is checked out into a git worktree and built in its own target directory, so
+/// the current build is left alone.
+#[tracing::instrument(level = "error", skip_all, fields(
+    from = %from,
+    pg_version = %pg_config.version()?,
+    profile = ?profile,
+    test = is_test,
+    path = path.as_ref().map(|path| tracing::field::display(path.as_ref().display())),
+    features = ?features.features,
+))]
+pub(crate) fn generate_upgrade_schema(
+    from: &str,
+    pg_config: &PgConfig,
+    user_package: Option<&String>,
+    package_manifest_path: impl AsRef<Path>,
+    profile: &CargoProfile,
+    is_test: bool,
+    features: &clap_cargo::Features,
+    path: Option<impl AsRef<std::path::Path>>,
+    log_level: Option<String>,
+) -> eyre::Result<()> {
+    let (_, extname) = find_control_file(&package_manifest_path)?;
+
+    let mut upgrade_dir = get_target_dir()?;
+    upgrade_dir.push("ogx-upgrade");
+    let worktree = GitWorktree::checkout(&package_manifest_path, from, &upgrade_dir)?;
+    let from_sql = build_ogx_sql(
+        pg_config,
+        Some(worktree.manifest_path()),
+        user_package,
+        worktree.manifest_path(),
+        profile,
+        is_test,
+        features,
+        log_level.clone(),
+        false,
+        Some(&upgrade_dir.join("target")),
+        &FROM_EXTENSION_LIBRARY,
+    )?;
+    let from_version = get_version(worktree.manifest_path())?;
+    drop(worktree);
+
+    let to_sql = build_ogx_sql(
+        pg_config,
+        None::<&Path>,
+        user_package,
+        &package_manifest_path,
+        profile,
+        is_test,
+        features,
+        log_level,
+        false,
+        None,
+        &EXTENSION_LIBRARY,
+    )?;
+    let to_version = get_version(&package_manifest_path)?;
+
+    let upgrade = to_sql.upgrade_from(&from_sql)?;
+    for item in &upgrade.needs_review {
+        eprintln!("{} {}", "      Review".bold().yellow(), item);
+    }
+
+    let filename = format!("{}--{}--{}.sql", extname, from_version, to_version);
+    match path {
+        Some(out_path) => {
+            let mut out_path = out_path.as_ref().to_path_buf();
+            if out_path.is_dir() {
+                out_path.push(&filename);
+            }
+            eprintln!(
+                "{} upgrade script to {}",
+                "     Writing".bold().green(),
+                format_display_path(&out_path)?.cyan()
+            );
+            std::fs::write(&out_path, upgrade.to_sql())
+                .wrap_err_with(|| eyre!("Could not write SQL to {}", out_path.display()))?;
+        }
+        None => {
+            eprintln!(
+                "{} upgrade script {} to {}",
+                "     Writing".bold().green(),
+                filename.cyan(),
+                "/dev/stdout".cyan(),
+            );
+            print!("{}", upgrade.to_sql());
+        }
+    }
+
+    if !upgrade.needs_review.is_empty() {
+        eprintln!(
+            "{} {} change(s) need a manual review, see the top of the script",
+            "     Warning".bold().yellow(),
+            upgrade.needs_review.len().to_string().bold().cyan()
+        );
+    }
+    Ok(())
+}
+
+/// Build the extension, load it, and collect its SQL entities into an [`OgxSql`].
+///
+/// `target_dir` overrides where the extension is built, and `library` keeps it loaded.
+#[tracing::instrument(level = "error", skip_all, fields(
+    pg_version = %pg_config.version()?,
+    profile = ?profile,
+    test = is_test,
+    features = ?features.features,
+))]
+fn build_ogx_sql(
+    pg_config: &PgConfig,
+    user_manifest_path: Option<impl AsRef<Path>>,
+    user_package: Option<&String>,
+    package_manifest_path: impl AsRef<Path>,
+    profile: &CargoProfile,
+    is_test: bool,
+    features: &clap_cargo::Features,
+    log_level: Option<String>,
+    skip_build: bool,
+    target_dir: Option<&Path>,
+    library: &'static OnceCell<libloading::os::unix::Library>,
+) -> eyre::Result<OgxSql> {
     let manifest = Manifest::from_path(&package_manifest_path)?;
     let (control_file, _extname) = find_control_file(&package_manifest_path)?;
     let package_name = &manifest
@@ -176,7 +374,10 @@ pub(crate) fn generate_schema(
 
     let flags = std::env::var("OGX_BUILD_FLAGS").unwrap_or_default();
 
-    let mut target_dir_with_profile = get_target_dir()?;
+    let mut target_dir_with_profile = match target_dir {
+        Some(target_dir) => target_dir.to_path_buf(),
+        None => get_target_dir()?,
+    };
     target_dir_with_profile.push(profile.target_subdir());
 
     // First, build the SQL generator so we can get a look at the symbol table
@@ -207,6 +408,14 @@ pub(crate) fn generate_schema(
             command.env("RUST_LOG", log_level);
         }
 
+        if let Some(target_dir) = target_dir {
+            command.env("CARGO_TARGET_DIR", target_dir);
+        }
+
+        if let Some(name) = pg_config.name() {
+            command.env("OGX_INSTALLATION", name);
+        }
+
         let features_arg = features.features.join(" ");
         if !features_arg.trim().is_empty() {
             command.arg("--features");
@@ -302,6 +511,7 @@ pub(crate) fn generate_schema(
     let mut num_triggers = 0_usize;
     let mut num_types = 0_usize;
     let mut num_enums = 0_usize;
+    let mut num_composites = 0_usize;
     let mut num_sqls = 0_usize;
     let mut num_ords = 0_usize;
     let mut num_hashes = 0_usize;
@@ -322,6 +532,8 @@ pub(crate) fn generate_schema(
             num_types += 1;
         } else if func.starts_with("__ogx_internals_enum_") {
             num_enums += 1;
+        } else if func.starts_with("__ogx_internals_composite_") {
+            num_composites += 1;
         } else if func.starts_with("__ogx_internals_sql_") {
             num_sqls += 1;
         } else if func.starts_with("__ogx_internals_ord_") {
@@ -334,7 +546,7 @@ pub(crate) fn generate_schema(
     }
 
     eprintln!(
-        "{} {} SQL entities: {} schemas ({} unique), {} functions, {} types, {} enums, {} sqls, {} ords, {} hashes, {} aggregates, {} triggers",
+        "{} {} SQL entities: {} schemas ({} unique), {} functions, {} types, {} enums, {} composites, {} sqls, {} ords, {} hashes, {} aggregates, {} triggers",
         "  Discovered".bold().green(),
         fns_to_call.len().to_string().bold().cyan(),
         seen_schemas.iter().count().to_string().bold().cyan(),
@@ -342,6 +554,7 @@ pub(crate) fn generate_schema(
         num_funcs.to_string().bold().cyan(),
         num_types.to_string().bold().cyan(),
         num_enums.to_string().bold().cyan(),
+        num_composites.to_string().bold().cyan(),
         num_sqls.to_string().bold().cyan(),
         num_ords.to_string().bold().cyan(),
         num_hashes.to_string().bold().cyan(),
@@ -369,7 +582,7 @@ pub(crate) fn generate_schema(
             })
             .wrap_err_with(|| format!("Couldn't libload {}", postmaster_stub_built.display()))?;
 
-        let lib = EXTENSION_LIBRARY
+        let lib = library
             .get_or_try_init(|| {
                 libloading::os::unix::Library::open(Some(&lib_so), libloading::os::unix::RTLD_LAZY)
             })
@@ -401,38 +614,8 @@ pub(crate) fn generate_schema(
         }
     };
 
-    let ogx_sql =
-        OgxSql::build(sql_mapping, entities.into_iter(), package_name.to_string(), versioned_so)
-            .wrap_err("SQL generation error")?;
-
-    if let Some(out_path) = path {
-        let out_path = out_path.as_ref();
-
-        eprintln!(
-            "{} SQL entities to {}",
-            "     Writing".bold().green(),
-            format_display_path(out_path)?.cyan()
-        );
-
-        if let Some(parent) = out_path.parent() {
-            std::fs::create_dir_all(parent).wrap_err("Could not create parent directory")?
-        }
-        ogx_sql
-            .to_file(out_path)
-            .wrap_err_with(|| eyre!("Could not write SQL to {}", out_path.display()))?;
-    } else {
-        eprintln!("{} SQL entities to {}", "     Writing".bold().green(), "/dev/stdout".cyan(),);
-        ogx_sql
-            .write(&mut std::io::stdout())
-            .wrap_err_with(|| eyre!("Could not write SQL to stdout"))?;
-    }
-
-    if let Some(dot_path) = dot {
-        let dot_path = dot_path.as_ref();
-        tracing::info!(dot = %dot_path.display(), "Writing Graphviz DOT");
-        ogx_sql.to_dot(dot_path)?;
-    }
-    Ok(())
+    OgxSql::build(sql_mapping, entities.into_iter(), package_name.to_string(), versioned_so)
+        .wrap_err("SQL generation error")
 }
 
 #[tracing::instrument(level = "error", skip_all, fields(
@@ -84,13 +84,11 @@ impl CommandExecute for Test {
                 }
                 Ok(config) => config,
             };
-            let pg_version = format!("pg{}", pg_config.major_version()?);
-
             let features = crate::manifest::features_for_version(
                 self.features.clone(),
                 &package_manifest,
-                &pg_version,
-            );
+                pg_config,
+            )?;
 
             test_extension(
                 pg_config,
@@ -124,6 +122,44 @@ pub fn test_extension(
     if let Some(ref testname) = testname {
         tracing::Span::current().record("testname", &tracing::field::display(&testname.as_ref()));
     }
+
+    let mut command = cargo_test_command(
+        pg_config,
+        user_manifest_path,
+        user_package,
+        profile,
+        no_schema,
+        features,
+    )?;
+
+    if let Some(testname) = testname {
+        command.arg(testname.as_ref());
+    }
+
+    eprintln!("{:?}", command);
+
+    tracing::debug!(command = ?command, "Running");
+    let status = command.status().wrap_err("failed to run cargo test")?;
+    tracing::trace!(status_code = %status, command = ?command, "Finished");
+    if !status.success() {
+        if !status.success() {
+            // We explicitly do not want to return a spantraced error here.
+            std::process::exit(1)
+        }
+    }
+
+    Ok(())
+}
+
+/// The `cargo test` command which runs the `#[og_test]`s (or `#[og_bench]`es) of the extension
+pub(crate) fn cargo_test_command(
+    pg_config: &PgConfig,
+    user_manifest_path: Option<impl AsRef<Path>>,
+    user_package: Option<&String>,
+    profile: &CargoProfile,
+    no_schema: bool,
+    features: &clap_cargo::Features,
+) -> eyre::Result<Command> {
     let target_dir = get_target_dir()?;
 
     let mut command = Command::new("cargo");
@@ -149,6 +185,10 @@ pub fn test_extension(
         command.env("RUST_LOG", rust_log);
     }
 
+    if let Some(name) = pg_config.name() {
+        command.env("OGX_INSTALLATION", name);
+    }
+
     if !features_arg.trim().is_empty() {
         command.arg("--features");
         command.arg(&features_arg);
@@ -174,21 +214,5 @@ pub fn test_extension(
         command.arg(user_package);
     }
 
-    if let Some(testname) = testname {
-        command.arg(testname.as_ref());
-    }
-
-    eprintln!("{:?}", command);
-
-    tracing::debug!(command = ?command, "Running");
-    let status = command.status().wrap_err("failed to run cargo test")?;
-    tracing::trace!(status_code = %status, command = ?command, "Finished");
-    if !status.success() {
-        if !status.success() {
-            // We explicitly do not want to return a spantraced error here.
-            std::process::exit(1)
-        }
-    }
-
-    Ok(())
+    Ok(command)
 }
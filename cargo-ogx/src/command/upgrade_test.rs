@@ -0,0 +1,327 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+use crate::command::get::find_control_file;
+use crate::command::install::{format_display_path, get_version, install_extension};
+use crate::profile::CargoProfile;
+use crate::scratch::ScratchServer;
+use crate::worktree::GitWorktree;
+use crate::CommandExecute;
+use cargo_toml::Manifest;
+use eyre::{eyre, WrapErr};
+use ogx_pg_config::{get_target_dir, Ogx, PgConfig};
+use owo_colors::OwoColorize;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const UPGRADED_DATABASE: &str = "ogx_upgrade_test_upgraded";
+const FRESH_DATABASE: &str = "ogx_upgrade_test_fresh";
+
+/// Install an earlier version of the extension into a scratch openGauss, `ALTER EXTENSION ... UPDATE` it, and compare the result against a fresh install
+#[derive(clap::Args, Debug)]
+#[clap(author)]
+pub(crate) struct UpgradeTest {
+    /// Do you want to run against openGauss `og3`?
+    #[clap(env = "OG_VERSION")]
+    pg_version: Option<String>,
+    /// The version to upgrade from, as a git ref or a version tagged `v<VERSION>`
+    #[clap(long, value_name = "VERSION|GIT_REF")]
+    from: String,
+    /// A SQL file to run after installing the earlier version, to create objects that depend on the extension
+    #[clap(long, parse(from_os_str))]
+    setup: Option<PathBuf>,
+    /// The port for the scratch openGauss (default is one above the `cargo ogx test` port)
+    #[clap(long)]
+    port: Option<u16>,
+    /// Package to build (see `cargo help pkgid`)
+    #[clap(long, short)]
+    package: Option<String>,
+    /// Path to Cargo.toml
+    #[clap(long, parse(from_os_str))]
+    manifest_path: Option<PathBuf>,
+    /// Compile for release mode (default is debug)
+    #[clap(long, short)]
+    release: bool,
+    /// Specific profile to use (conflicts with `--release`)
+    #[clap(long)]
+    profile: Option<String>,
+    #[clap(flatten)]
+    features: clap_cargo::Features,
+    #[clap(from_global, parse(from_occurrences))]
+    verbose: usize,
+}
+
+impl CommandExecute for UpgradeTest {
+    #[tracing::instrument(level = "error", skip(self))]
+    fn execute(self) -> eyre::Result<()> {
+        let metadata = crate::metadata::metadata(&self.features, self.manifest_path.as_ref())
+            .wrap_err("couldn't get cargo metadata")?;
+        crate::metadata::validate(&metadata)?;
+        let package_manifest_path =
+            crate::manifest::manifest_path(&metadata, self.package.as_ref())
+                .wrap_err("Couldn't get manifest path")?;
+        let package_manifest =
+            Manifest::from_path(&package_manifest_path).wrap_err("Couldn't parse manifest")?;
+
+        let pg_version = match self.pg_version {
+            Some(pg_version) => pg_version,
+            None => crate::manifest::default_og_version(&package_manifest)
+                .ok_or(eyre!("No provided `og$VERSION` flag."))?,
+        };
+        let pg_config = Ogx::from_config()?.get(&pg_version)?.clone();
+
+        let features =
+            crate::manifest::features_for_version(self.features, &package_manifest, &pg_config)?;
+        let profile = CargoProfile::from_flags(self.release, self.profile.as_deref())?;
+        let port = match self.port {
+            Some(port) => port,
+            None => pg_config.test_port()? + 1,
+        };
+
+        upgrade_test(
+            &self.from,
+            self.setup.as_ref(),
+            port,
+            &pg_config,
+            self.manifest_path.as_ref(),
+            self.package.as_ref(),
+            package_manifest_path,
+            &profile,
+            &features,
+        )
+    }
+}
+
+#[tracing::instrument(level = "error", skip_all, fields(
+    pg_version = %pg_config.version()?,
+    from = %from,
+    port,
+    profile = ?profile,
+    features = ?features.features,
+))]
+pub(crate) fn upgrade_test(
+    from: &str,
+    setup: Option<impl AsRef<Path>>,
+    port: u16,
+    pg_config: &PgConfig,
+    user_manifest_path: Option<impl AsRef<Path>>,
+    user_package: Option<&String>,
+    package_manifest_path: impl AsRef<Path>,
+    profile: &CargoProfile,
+    features: &clap_cargo::Features,
+) -> eyre::Result<()> {
+    let (_, extname) = find_control_file(&package_manifest_path)?;
+    let to_version = get_version(&package_manifest_path)?;
+    let setup = match setup {
+        Some(setup) => Some(
+            setup
+                .as_ref()
+                .canonicalize()
+                .wrap_err_with(|| format!("couldn't find `{}`", setup.as_ref().display()))?,
+        ),
+        None => None,
+    };
+
+    let mut scratch_dir = get_target_dir()?;
+    scratch_dir.push("ogx-upgrade-test");
+    let server = ScratchServer::init(pg_config, &scratch_dir, port)?;
+
+    // the earlier version is installed by a `cargo ogx install` of its own checkout, as schema
+    // generation works relative to the package being built
+    let worktree = GitWorktree::checkout(&package_manifest_path, from, &scratch_dir)?;
+    let from_version = get_version(worktree.manifest_path())?;
+    if from_version == to_version {
+        return Err(eyre!(
+            "`{}` is also version {} of {}, there is nothing to upgrade",
+            from,
+            to_version,
+            extname
+        ));
+    }
+    install_from_worktree(&worktree, &scratch_dir, pg_config, user_package, profile, features)?;
+    drop(worktree);
+
+    server.start()?;
+    server.sql("postgres", &format!("CREATE DATABASE {}", UPGRADED_DATABASE))?;
+    server.sql(UPGRADED_DATABASE, &format!("CREATE EXTENSION {}", quote_ident(&extname)))?;
+    if let Some(setup) = &setup {
+        println!("{} {}", "     Running".bold().green(), format_display_path(setup)?.cyan());
+        server.sql_file(UPGRADED_DATABASE, setup)?;
+    }
+    server.stop()?;
+
+    install_extension(
+        user_manifest_path,
+        user_package,
+        &package_manifest_path,
+        pg_config,
+        profile,
+        false,
+        None,
+        features,
+    )?;
+
+    server.start()?;
+    println!(
+        "{} {} from {} to {}",
+        "   Upgrading".bold().green(),
+        extname,
+        from_version.cyan(),
+        to_version.cyan()
+    );
+    server.sql(
+        UPGRADED_DATABASE,
+        &format!(
+            "ALTER EXTENSION {} UPDATE TO {}",
+            quote_ident(&extname),
+            quote_literal(&to_version)
+        ),
+    )?;
+    server.sql("postgres", &format!("CREATE DATABASE {}", FRESH_DATABASE))?;
+    server.sql(FRESH_DATABASE, &format!("CREATE EXTENSION {}", quote_ident(&extname)))?;
+
+    println!("{} the upgrade against a fresh install", "   Comparing".bold().green());
+    let upgraded = extension_members(&server, UPGRADED_DATABASE, &extname)?;
+    let fresh = extension_members(&server, FRESH_DATABASE, &extname)?;
+    server.stop()?;
+
+    let missing = fresh.difference(&upgraded).collect::<Vec<_>>();
+    let unexpected = upgraded.difference(&fresh).collect::<Vec<_>>();
+    for member in &missing {
+        println!("{} {}", "     Missing".bold().red(), member);
+    }
+    for member in &unexpected {
+        println!("{} {}", "  Unexpected".bold().red(), member);
+    }
+    if !missing.is_empty() || !unexpected.is_empty() {
+        return Err(eyre!(
+            "upgrading {} from {} to {} left {} object(s) different from a fresh install",
+            extname,
+            from_version,
+            to_version,
+            missing.len() + unexpected.len()
+        ));
+    }
+
+    println!(
+        "{} upgrading {} from {} to {} matches a fresh install",
+        "    Finished".bold().green(),
+        extname,
+        from_version,
+        to_version
+    );
+    Ok(())
+}
+
+/// Run `cargo ogx install` in the checkout of the earlier version, with its own target directory
+fn install_from_worktree(
+    worktree: &GitWorktree,
+    scratch_dir: &Path,
+    pg_config: &PgConfig,
+    user_package: Option<&String>,
+    profile: &CargoProfile,
+    features: &clap_cargo::Features,
+) -> eyre::Result<()> {
+    let mut command = Command::new(std::env::current_exe()?);
+    command
+        .current_dir(worktree.package_dir())
+        .env("CARGO_TARGET_DIR", scratch_dir.join("target"))
+        .arg("ogx")
+        .arg("install")
+        .arg("--manifest-path")
+        .arg(worktree.manifest_path());
+    if let Some(pg_config) = pg_config.path() {
+        command.arg("--pg-config").arg(pg_config);
+    }
+    if let Some(user_package) = user_package {
+        command.arg("--package").arg(user_package);
+    }
+    match profile {
+        CargoProfile::Dev => {}
+        CargoProfile::Release => {
+            command.arg("--release");
+        }
+        CargoProfile::Profile(profile) => {
+            command.arg("--profile").arg(profile);
+        }
+    }
+    if features.no_default_features {
+        command.arg("--no-default-features");
+    }
+    if features.all_features {
+        command.arg("--all-features");
+    }
+    if !features.features.is_empty() {
+        command.arg("--features").arg(features.features.join(" "));
+    }
+
+    let command_str = format!("{:?}", command);
+    tracing::debug!(command = %command_str, "Running");
+    let status = command.status().wrap_err_with(|| eyre!("unable to execute: {}", command_str))?;
+    if !status.success() {
+        return Err(eyre!("installing the earlier version failed: {}", command_str));
+    }
+    Ok(())
+}
+
+/// One line per function, type and operator that belongs to `extname`, describing everything
+/// an upgrade script is expected to get right
+fn extension_members(
+    server: &ScratchServer,
+    dbname: &str,
+    extname: &str,
+) -> eyre::Result<BTreeSet<String>> {
+    let members = format!(
+        "FROM pg_depend d WHERE d.refclassid = 'pg_extension'::regclass AND d.deptype = 'e' \
+         AND d.refobjid = (SELECT oid FROM pg_extension WHERE extname = {})",
+        quote_literal(extname)
+    );
+    let query = format!(
+        "SELECT 'function ' || p.oid::regprocedure || ' RETURNS ' || pg_get_function_result(p.oid) \
+             || ' VOLATILITY ' || p.provolatile || ' STRICT ' || p.proisstrict \
+             || ' BIN ' || coalesce(p.probin, '') || ' SRC ' || p.prosrc \
+         FROM pg_proc p WHERE p.oid IN (SELECT d.objid {members} AND d.classid = 'pg_proc'::regclass) \
+         UNION ALL \
+         SELECT 'type ' || t.oid::regtype || ' TYPTYPE ' || t.typtype \
+             || coalesce(' LABELS (' || (SELECT string_agg(e.enumlabel, ', ' ORDER BY e.enumsortorder) \
+                 FROM pg_enum e WHERE e.enumtypid = t.oid) || ')', '') \
+             || coalesce(' ATTRIBUTES (' || (SELECT string_agg(a.attname || ' ' || format_type(a.atttypid, a.atttypmod), ', ' ORDER BY a.attnum) \
+                 FROM pg_attribute a WHERE a.attrelid = t.typrelid AND a.attnum > 0 AND NOT a.attisdropped) || ')', '') \
+         FROM pg_type t WHERE t.oid IN (SELECT d.objid {members} AND d.classid = 'pg_type'::regclass) \
+         UNION ALL \
+         SELECT 'operator ' || o.oid::regoperator || ' PROCEDURE ' || o.oprcode::oid::regprocedure \
+         FROM pg_operator o WHERE o.oid IN (SELECT d.objid {members} AND d.classid = 'pg_operator'::regclass)",
+        members = members
+    );
+
+    // `prosrc` may span lines, but every member line starts with its kind
+    let mut lines = BTreeSet::new();
+    let mut current: Option<String> = None;
+    for line in server.sql(dbname, &query)?.lines() {
+        if line.starts_with("function ")
+            || line.starts_with("type ")
+            || line.starts_with("operator ")
+        {
+            lines.extend(current.replace(line.to_string()));
+        } else if let Some(current) = current.as_mut() {
+            current.push_str("\\n");
+            current.push_str(line);
+        }
+    }
+    lines.extend(current);
+    Ok(lines)
+}
+
+fn quote_ident(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
+fn quote_literal(literal: &str) -> String {
+    format!("'{}'", literal.replace('\'', "''"))
+}
@@ -7,10 +7,13 @@ All rights reserved.
 Use of this source code is governed by the MIT license that can be found in the LICENSE file.
 */
 
+mod archive;
 mod command;
 mod manifest;
 mod metadata;
 mod ogx_pg_sys_stub;
+mod scratch;
+mod worktree;
 
 pub(crate) mod profile;
 
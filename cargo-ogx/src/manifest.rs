@@ -9,7 +9,7 @@ Use of this source code is governed by the MIT license that can be found in the
 use cargo_metadata::Metadata;
 use cargo_toml::Manifest;
 use eyre::eyre;
-use ogx_pg_config::SUPPORTED_MAJOR_VERSIONS;
+use ogx_pg_config::{PgConfig, SUPPORTED_MAJOR_VERSIONS};
 use std::path::PathBuf;
 
 #[tracing::instrument(skip_all)]
@@ -48,18 +48,18 @@ pub(crate) fn default_og_version(manifest: &Manifest) -> Option<String> {
     None
 }
 
+/// The features to build for `pg_config`: its `og$VERSION` instead of any other default one, and
+/// the version-range gates it falls in
 pub(crate) fn features_for_version(
     mut features: clap_cargo::Features,
     manifest: &Manifest,
-    pg_version: &String,
-) -> clap_cargo::Features {
+    pg_config: &PgConfig,
+) -> eyre::Result<clap_cargo::Features> {
+    let pg_version = format!("og{}", pg_config.major_version()?);
     let default_features = manifest.features.get("default");
 
     match default_features {
-        Some(default_features) => {
-            if default_features.contains(&pg_version) {
-                return features;
-            }
+        Some(default_features) if !default_features.contains(&pg_version) => {
             let default_features = default_features
                 .iter()
                 .filter(|default_feature| {
@@ -74,12 +74,48 @@ pub(crate) fn features_for_version(
                 .collect::<Vec<_>>();
             features.no_default_features = true;
             features.features.extend(default_features);
-            if features.features.iter().all(|f| f != pg_version) {
+            if features.features.iter().all(|f| *f != pg_version) {
                 features.features.push(pg_version.clone());
             }
         }
-        None => (),
+        _ => (),
     };
 
-    features
+    let version =
+        (pg_config.major_version()?, pg_config.middle_version()?, pg_config.minor_version()?);
+    let mut gates = manifest
+        .features
+        .keys()
+        .filter(|feature| version_gate_matches(feature, version) == Some(true))
+        .cloned()
+        .collect::<Vec<_>>();
+    gates.sort();
+    for gate in gates {
+        if features.features.iter().all(|f| *f != gate) {
+            features.features.push(gate);
+        }
+    }
+
+    Ok(features)
+}
+
+/// Whether `version` falls in the range of `feature`, if it's a version-range gate
+///
+/// `og_since_3_1` is enabled for openGauss 3.1.0 and later, and `og_before_3_1_2` for anything
+/// before 3.1.2.  Left out parts of the version are zero.
+fn version_gate_matches(feature: &str, version: (u16, u16, u16)) -> Option<bool> {
+    let (since, gate) =
+        match (feature.strip_prefix("og_since_"), feature.strip_prefix("og_before_")) {
+            (Some(gate), _) => (true, gate),
+            (_, Some(gate)) => (false, gate),
+            _ => return None,
+        };
+    let parts = gate.split('_').map(|part| part.parse::<u16>().ok()).collect::<Option<Vec<_>>>()?;
+    let gate = match parts.as_slice() {
+        [major] => (*major, 0, 0),
+        [major, middle] => (*major, *middle, 0),
+        [major, middle, minor] => (*major, *middle, *minor),
+        _ => return None,
+    };
+    Some(if since { version >= gate } else { version < gate })
 }
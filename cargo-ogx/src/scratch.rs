@@ -0,0 +1,173 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+use crate::command::init::initdb;
+use eyre::{eyre, WrapErr};
+use ogx_pg_config::PgConfig;
+use owo_colors::OwoColorize;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// A throwaway openGauss cluster under the target directory, so that commands which need a
+/// clean instance never touch the databases of `cargo ogx run` or `cargo ogx test`.  Stopped when
+/// dropped
+pub(crate) struct ScratchServer<'a> {
+    pg_config: &'a PgConfig,
+    datadir: PathBuf,
+    logfile: PathBuf,
+    socket_dir: PathBuf,
+    port: u16,
+}
+
+impl<'a> ScratchServer<'a> {
+    /// Initialize a cluster in `scratch_dir`, replacing whatever a previous run left there
+    pub(crate) fn init(
+        pg_config: &'a PgConfig,
+        scratch_dir: &Path,
+        port: u16,
+    ) -> eyre::Result<Self> {
+        let major_version = pg_config.major_version()?;
+        let server = ScratchServer {
+            pg_config,
+            datadir: scratch_dir.join(format!("data-{}", major_version)),
+            logfile: scratch_dir.join(format!("{}.log", major_version)),
+            socket_dir: scratch_dir.to_path_buf(),
+            port,
+        };
+
+        if server.datadir.join("postmaster.pid").exists() {
+            server.stop()?;
+        }
+        if server.datadir.exists() {
+            std::fs::remove_dir_all(&server.datadir)
+                .wrap_err_with(|| format!("unable to remove {}", server.datadir.display()))?;
+        }
+        std::fs::create_dir_all(scratch_dir)?;
+        initdb(&pg_config.bin_dir()?, &server.datadir)?;
+        Ok(server)
+    }
+
+    pub(crate) fn start(&self) -> eyre::Result<()> {
+        println!(
+            "{} scratch openGauss v{} on port {}",
+            "    Starting".bold().green(),
+            self.pg_config.major_version()?,
+            self.port.to_string().bold().cyan()
+        );
+        self.gs_ctl(|command| {
+            command
+                .arg("start")
+                .arg("-w")
+                .arg(format!(
+                    "-o -i -p {} -c unix_socket_directory={}",
+                    self.port,
+                    self.socket_dir.display()
+                ))
+                .arg("-Z")
+                .arg("single_node")
+                .arg("-l")
+                .arg(&self.logfile);
+        })
+    }
+
+    pub(crate) fn stop(&self) -> eyre::Result<()> {
+        println!("{} scratch openGauss", "    Stopping".bold().green());
+        self.gs_ctl(|command| {
+            command.arg("stop").arg("-m").arg("fast");
+        })
+    }
+
+    fn gs_ctl(&self, args: impl FnOnce(&mut Command)) -> eyre::Result<()> {
+        let bindir = self.pg_config.bin_dir()?;
+        let mut libpath = bindir.clone();
+        libpath.pop();
+        libpath.push("lib");
+        let mut gauss_home = bindir.clone();
+        gauss_home.pop();
+
+        let mut command = Command::new(format!("{}/gs_ctl", bindir.display()));
+        command.stdout(Stdio::piped()).stderr(Stdio::piped());
+        args(&mut command);
+        command
+            .arg("-D")
+            .arg(&self.datadir)
+            .env("LD_LIBRARY_PATH", &libpath)
+            .env("GAUSSHOME", &gauss_home);
+
+        let command_str = format!("{:?}", command);
+        let output = command.output()?;
+        if !output.status.success() {
+            return Err(eyre!(
+                "problem running gs_ctl: {}\n\n{}",
+                command_str,
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn sql(&self, dbname: &str, sql: &str) -> eyre::Result<String> {
+        self.run_gsql(dbname, |command| {
+            command.arg("-c").arg(sql);
+        })
+    }
+
+    pub(crate) fn sql_file(&self, dbname: &str, path: &Path) -> eyre::Result<String> {
+        self.run_gsql(dbname, |command| {
+            command.arg("-f").arg(path);
+        })
+    }
+
+    /// A `gsql` command connected to `dbname` on this instance, without any output options
+    pub(crate) fn gsql(&self, dbname: &str) -> eyre::Result<Command> {
+        let mut command = Command::new(self.pg_config.gsql_path()?);
+        command
+            .env_remove("PGDATABASE")
+            .env_remove("PGHOST")
+            .env_remove("PGPORT")
+            .env_remove("PGUSER")
+            .arg("-X")
+            .arg("-h")
+            .arg(self.pg_config.host())
+            .arg("-p")
+            .arg(self.port.to_string())
+            .arg("-d")
+            .arg(dbname);
+        Ok(command)
+    }
+
+    fn run_gsql(&self, dbname: &str, args: impl FnOnce(&mut Command)) -> eyre::Result<String> {
+        let mut command = self.gsql(dbname)?;
+        command.arg("-qAt").arg("-v").arg("ON_ERROR_STOP=1");
+        args(&mut command);
+
+        let command_str = format!("{:?}", command);
+        tracing::debug!(command = %command_str, "Running");
+        let output =
+            command.output().wrap_err_with(|| eyre!("unable to execute: {}", command_str))?;
+        if !output.status.success() {
+            return Err(eyre!(
+                "problem running gsql: {}\n\n{}{}",
+                command_str,
+                String::from_utf8_lossy(&output.stdout),
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+        Ok(String::from_utf8(output.stdout)?)
+    }
+}
+
+impl Drop for ScratchServer<'_> {
+    fn drop(&mut self) {
+        if self.datadir.join("postmaster.pid").exists() {
+            if let Err(e) = self.stop() {
+                tracing::warn!(error = %e, "Couldn't stop the scratch openGauss");
+            }
+        }
+    }
+}
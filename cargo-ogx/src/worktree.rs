@@ -0,0 +1,101 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+use eyre::{eyre, WrapErr};
+use owo_colors::OwoColorize;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// An earlier version of the extension, checked out into a detached git worktree which is
+/// removed again when this is dropped
+#[derive(Debug)]
+pub(crate) struct GitWorktree {
+    toplevel: PathBuf,
+    path: PathBuf,
+    manifest_path: PathBuf,
+}
+
+impl GitWorktree {
+    /// Check out `from`, a git ref or a version tagged `v<from>`, of the repository containing
+    /// `package_manifest_path`, into a directory under `parent_dir`
+    #[tracing::instrument(level = "error", skip_all, fields(from = %from))]
+    pub(crate) fn checkout(
+        package_manifest_path: impl AsRef<Path>,
+        from: &str,
+        parent_dir: impl AsRef<Path>,
+    ) -> eyre::Result<Self> {
+        let package_manifest_path = package_manifest_path
+            .as_ref()
+            .canonicalize()
+            .wrap_err("couldn't canonicalize the manifest path")?;
+        let package_dir = package_manifest_path.parent().ok_or(eyre!("manifest has no parent"))?;
+
+        let toplevel = PathBuf::from(git(package_dir, &["rev-parse", "--show-toplevel"])?);
+        let commit = [from.to_string(), format!("v{}", from)]
+            .iter()
+            .find_map(|candidate| {
+                git(
+                    &toplevel,
+                    &["rev-parse", "--verify", "--quiet", &format!("{}^{{commit}}", candidate)],
+                )
+                .ok()
+            })
+            .ok_or_else(|| {
+                eyre!("`{}` is neither a git ref nor a version tagged `v{}`", from, from)
+            })?;
+
+        let path = parent_dir.as_ref().join(&commit);
+        if path.exists() {
+            git(&toplevel, &["worktree", "remove", "--force", &path.display().to_string()]).ok();
+            std::fs::remove_dir_all(&path).ok();
+        }
+
+        eprintln!("{} `{}` ({})", " Checking out".bold().green(), from, &commit[..12]);
+        git(&toplevel, &["worktree", "add", "--detach", &path.display().to_string(), &commit])?;
+
+        let manifest_path = path.join(
+            package_manifest_path
+                .strip_prefix(&toplevel)
+                .wrap_err("the manifest isn't inside its git repository")?,
+        );
+        Ok(GitWorktree { toplevel, path, manifest_path })
+    }
+
+    /// The checked out copy of the package's `Cargo.toml`
+    pub(crate) fn manifest_path(&self) -> &Path {
+        &self.manifest_path
+    }
+
+    /// The checked out copy of the package's directory
+    pub(crate) fn package_dir(&self) -> &Path {
+        self.manifest_path.parent().unwrap_or(&self.path)
+    }
+}
+
+impl Drop for GitWorktree {
+    fn drop(&mut self) {
+        let path = self.path.display().to_string();
+        if let Err(e) = git(&self.toplevel, &["worktree", "remove", "--force", &path]) {
+            tracing::warn!(worktree = %path, error = %e, "Couldn't remove git worktree");
+        }
+    }
+}
+
+fn git(dir: &Path, args: &[&str]) -> eyre::Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .stderr(Stdio::inherit())
+        .output()
+        .wrap_err("failed to spawn git")?;
+    if !output.status.success() {
+        return Err(eyre!("`git {}` exited with {}", args.join(" "), output.status));
+    }
+    Ok(String::from_utf8(output.stdout).wrap_err("git output is not UTF8")?.trim().to_string())
+}
@@ -14,7 +14,7 @@ use operators::{impl_opengauss_eq, impl_opengauss_hash, impl_opengauss_ord};
 
 use ogx_utils::rewriter::*;
 use ogx_utils::sql_entity_graph::{
-    ExtensionSql, ExtensionSqlFile, OgAggregate, OgExtern, OgEnum, OgType, Schema,
+    ExtensionSql, ExtensionSqlFile, OgAggregate, OgComposite, OgExtern, OgEnum, OgType, Schema,
 };
 use ogx_utils::*;
 use proc_macro::TokenStream;
@@ -139,6 +139,95 @@ pub fn og_test(attr: TokenStream, item: TokenStream) -> TokenStream {
     stream.into()
 }
 
+/// `#[og_bench]` functions are benchmarks, which run in-process inside openGauss during
+/// `cargo ogx bench`.  The function body is called repeatedly inside the server and timed there,
+/// so only the body is measured, not the round trip from the client.
+///
+/// During `cargo ogx test` the generated test does nothing, so benchmarks can live next to tests.
+#[proc_macro_attribute]
+pub fn og_bench(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(item as syn::Item);
+
+    match ast {
+        Item::Fn(mut func) => {
+            if !func.sig.inputs.is_empty() {
+                panic!("#[og_bench] functions can't take arguments");
+            }
+
+            // as with #[og_test], `#[ignore]` belongs on the generated #[test], and any other
+            // attributes are passed to `og_test::setup()`
+            let mut test_attributes = Vec::new();
+            let mut non_test_attributes = Vec::new();
+            for attribute in func.attrs.iter() {
+                match attribute.path.get_ident() {
+                    Some(ident) if ident == "ignore" => test_attributes.push(attribute.clone()),
+                    _ => non_test_attributes.push(attribute.clone()),
+                }
+            }
+            func.attrs = non_test_attributes;
+
+            let bench_func_name = func.sig.ident.clone();
+            let sql_funcname = format!("ogx_bench_{}", func.sig.ident);
+            let sql_func_ident = Ident::new(&sql_funcname, func.span());
+            let test_func_name = Ident::new(&format!("og_bench_{}", func.sig.ident), func.span());
+
+            let mut att_stream = proc_macro2::TokenStream::new();
+            for a in func.attrs.iter() {
+                let as_str = a.tokens.to_string();
+                att_stream.extend(quote! {
+                    options.push(#as_str);
+                });
+            }
+
+            // the SQL function runs the body `iterations` times and returns the elapsed nanoseconds
+            let timer = quote! {
+                fn #sql_func_ident(iterations: i64) -> i64 {
+                    let start = std::time::Instant::now();
+                    for _ in 0..iterations {
+                        std::hint::black_box(#bench_func_name());
+                    }
+                    start.elapsed().as_nanos() as i64
+                }
+            };
+
+            let mut stream = func.to_token_stream();
+            stream.extend(proc_macro2::TokenStream::from(og_extern(
+                TokenStream::new(),
+                timer.into(),
+            )));
+            stream.extend(quote! {
+                #[test]
+                #(#test_attributes)*
+                fn #test_func_name() {
+                    // only `cargo ogx bench` runs benchmarks, and it sets `OGX_BENCH` to the
+                    // string their names must contain
+                    match std::env::var("OGX_BENCH") {
+                        Ok(filter) if stringify!(#bench_func_name).contains(filter.as_str()) => (),
+                        _ => return,
+                    }
+
+                    let mut options = Vec::new();
+                    #att_stream
+
+                    crate::og_test::setup(options);
+                    let res = ogx_tests::run_bench(
+                        #sql_funcname,
+                        concat!(module_path!(), "::", stringify!(#bench_func_name)),
+                        crate::og_test::opengauss_conf_options(),
+                    );
+                    match res {
+                        Ok(()) => (),
+                        Err(e) => panic!("{:?}", e)
+                    }
+                }
+            });
+            stream.into()
+        }
+
+        _ => panic!("#[og_bench] can only be applied to top-level functions"),
+    }
+}
+
 /// Associated macro for `#[og_test]` to provide context back to your test framework to indicate
 /// that the test system is being initialized
 #[proc_macro_attribute]
@@ -195,6 +284,13 @@ pub fn merges(_attr: TokenStream, item: TokenStream) -> TokenStream {
     item
 }
 
+/// Used with `#[og_extern]` to also create a `CREATE CAST` from the function's first argument
+/// type to its return type.  Optionally one of `explicit` (the default), `assignment` or `implicit`
+#[proc_macro_attribute]
+pub fn og_cast(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    item
+}
+
 /**
 Declare a Rust module and its contents to be in a schema.
 
@@ -659,6 +755,172 @@ fn impl_postgres_enum(ast: DeriveInput) -> proc_macro2::TokenStream {
     stream
 }
 
+/**
+Generate necessary bindings for using the struct as an openGauss composite type.
+
+```rust,ignore
+# use ogx_pg_sys as pg_sys;
+use ogx::*;
+#[derive(Debug, OgComposite)]
+struct Dog {
+    name: String,
+    scritches: Option<i32>,
+}
+```
+
+This creates the type with `CREATE TYPE Dog AS (name text, scritches integer)`, and maps each
+field to the attribute of the same name when converting to and from a `Datum`.
+
+Fields which are not `Option<T>` must never be `NULL`.
+
+The type is looked up in the schema of its `#[og_schema]` module, or else in the schema the
+extension is installed in, so it's found whatever the `search_path` is.
+*/
+#[proc_macro_derive(OgComposite, attributes(requires, ogx, ogx_schema))]
+pub fn postgres_composite(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as syn::DeriveInput);
+
+    impl_postgres_composite(ast).into()
+}
+
+fn impl_postgres_composite(ast: DeriveInput) -> proc_macro2::TokenStream {
+    let mut stream = proc_macro2::TokenStream::new();
+    let sql_graph_entity_ast = ast.clone();
+    let composite_ident = ast.ident;
+    let composite_name = composite_ident.to_string();
+
+    if !ast.generics.params.is_empty() {
+        return syn::Error::new(
+            ast.generics.span(),
+            "#[derive(OgComposite)] does not support generics or lifetimes",
+        )
+        .to_compile_error();
+    }
+
+    // validate that we're only operating on a struct with named fields
+    let fields = match ast.data {
+        Data::Struct(s) => match s.fields {
+            syn::Fields::Named(named) => named.named,
+            fields => {
+                return syn::Error::new_spanned(
+                    fields,
+                    "#[derive(OgComposite)] can only be applied to structs with named fields",
+                )
+                .to_compile_error()
+            }
+        },
+        _ => {
+            return syn::Error::new_spanned(
+                composite_ident,
+                "#[derive(OgComposite)] can only be applied to structs",
+            )
+            .to_compile_error()
+        }
+    };
+
+    // `#[og_schema]` marks the composites it creates in its schema with `#[ogx_schema = "name"]`
+    let mut schema = quote! { None };
+    for attr in ast.attrs.iter().filter(|attr| attr.path.is_ident("ogx_schema")) {
+        match attr.parse_meta() {
+            Ok(syn::Meta::NameValue(syn::MetaNameValue { lit: syn::Lit::Str(name), .. })) => {
+                schema = quote! { Some(#name) }
+            }
+            _ => {
+                return syn::Error::new_spanned(attr, "expected `#[ogx_schema = \"name\"]`")
+                    .to_compile_error()
+            }
+        }
+    }
+    let type_name = quote! {
+        ::ogx::composite_type_name(env!("CARGO_CRATE_NAME"), #schema, #composite_name)
+    };
+
+    let mut from_datum = proc_macro2::TokenStream::new();
+    let mut into_datum = proc_macro2::TokenStream::new();
+
+    for field in fields {
+        let field_ident = field.ident.expect("named fields always have an ident");
+        let field_name = field_ident.to_string();
+
+        match option_inner_type(&field.ty) {
+            Some(inner_ty) => from_datum.extend(quote! {
+                #field_ident: tuple.get_by_name::<#inner_ty>(#field_name).unwrap_or_else(|e| {
+                    panic!("could not read `{}.{}`: {}", #composite_name, #field_name, e)
+                }),
+            }),
+            None => {
+                let field_ty = &field.ty;
+                from_datum.extend(quote! {
+                    #field_ident: tuple.get_by_name::<#field_ty>(#field_name).unwrap_or_else(|e| {
+                        panic!("could not read `{}.{}`: {}", #composite_name, #field_name, e)
+                    }).unwrap_or_else(|| {
+                        panic!("`{}.{}` is NULL, but is not an `Option`", #composite_name, #field_name)
+                    }),
+                })
+            }
+        }
+        into_datum.extend(quote! {
+            tuple.set_by_name(#field_name, self.#field_ident).unwrap_or_else(|e| {
+                panic!("could not write `{}.{}`: {}", #composite_name, #field_name, e)
+            });
+        });
+    }
+
+    stream.extend(quote! {
+        impl ::ogx::datum::FromDatum for #composite_ident {
+            #[inline]
+            unsafe fn from_polymorphic_datum(datum: ::ogx::pg_sys::Datum, is_null: bool, _typeoid: ::ogx::pg_sys::Oid) -> Option<#composite_ident> {
+                if is_null {
+                    None
+                } else {
+                    let tuple = ::ogx::heap_tuple::PgHeapTuple::from_composite_datum(datum);
+                    Some(#composite_ident {
+                        #from_datum
+                    })
+                }
+            }
+        }
+
+        impl ::ogx::datum::IntoDatum for #composite_ident {
+            #[inline]
+            fn into_datum(self) -> Option<::ogx::pg_sys::Datum> {
+                let mut tuple = ::ogx::heap_tuple::PgHeapTuple::new_composite_type(&#type_name)
+                    .unwrap_or_else(|e| panic!("{}", e));
+                #into_datum
+                tuple.into_composite_datum()
+            }
+
+            fn type_oid() -> ::ogx::pg_sys::Oid {
+                ::ogx::regtypein(&#type_name)
+            }
+        }
+    });
+
+    let sql_graph_entity_item = OgComposite::from_derive_input(sql_graph_entity_ast).unwrap();
+    sql_graph_entity_item.to_tokens(&mut stream);
+
+    stream
+}
+
+/// Returns `T` if `ty` is spelled as an `Option<T>`.
+fn option_inner_type(ty: &syn::Type) -> Option<&syn::Type> {
+    let path = match ty {
+        syn::Type::Path(type_path) if type_path.qself.is_none() => &type_path.path,
+        _ => return None,
+    };
+    let segment = path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) if args.args.len() == 1 => match &args.args[0] {
+            syn::GenericArgument::Type(inner) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
 /**
 Generate necessary bindings for using the type with openGauss.
 
@@ -677,9 +939,14 @@ Optionally accepts the following attributes:
 
 * `inoutfuncs(some_in_fn, some_out_fn)`: Define custom in/out functions for the type.
 * `pgvarlena_inoutfuncs(some_in_fn, some_out_fn)`: Define custom in/out functions for the `PgVarlena` of this type.
+* `sendrecvfuncs`: Define binary send/receive functions for the type via `ogx::SendRecvFuncs`.
+* `typmod_inoutfuncs`: Accept a type modifier, and coerce values to it, via `ogx::TypmodInOutFuncs`.
 * `sql`: Same arguments as [`#[ogx(sql = ..)]`](macro@ogx).
 */
-#[proc_macro_derive(OgType, attributes(inoutfuncs, pgvarlena_inoutfuncs, requires, ogx))]
+#[proc_macro_derive(
+    OgType,
+    attributes(inoutfuncs, pgvarlena_inoutfuncs, sendrecvfuncs, typmod_inoutfuncs, requires, ogx)
+)]
 pub fn postgres_type(input: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(input as syn::DeriveInput);
 
@@ -692,6 +959,13 @@ fn impl_postgres_type(ast: DeriveInput) -> proc_macro2::TokenStream {
     let has_lifetimes = generics.lifetimes().next();
     let funcname_in = Ident::new(&format!("{}_in", name).to_lowercase(), name.span());
     let funcname_out = Ident::new(&format!("{}_out", name).to_lowercase(), name.span());
+    let funcname_send = Ident::new(&format!("{}_send", name).to_lowercase(), name.span());
+    let funcname_recv = Ident::new(&format!("{}_recv", name).to_lowercase(), name.span());
+    let funcname_typmod_in = Ident::new(&format!("{}_typmod_in", name).to_lowercase(), name.span());
+    let funcname_typmod_out =
+        Ident::new(&format!("{}_typmod_out", name).to_lowercase(), name.span());
+    let funcname_typmod_coerce =
+        Ident::new(&format!("{}_typmod_coerce", name).to_lowercase(), name.span());
     let mut args = parse_postgres_type_args(&ast.attrs);
     let mut stream = proc_macro2::TokenStream::new();
 
@@ -701,7 +975,9 @@ fn impl_postgres_type(ast: DeriveInput) -> proc_macro2::TokenStream {
         _ => panic!("#[derive(OgType)] can only be applied to structs"),
     }
 
-    if args.is_empty() {
+    if !args.contains(&OgTypeAttribute::InOutFuncs)
+        && !args.contains(&OgTypeAttribute::PgVarlenaInOutFuncs)
+    {
         // assume the user wants us to implement the InOutFuncs
         args.insert(OgTypeAttribute::Default);
     }
@@ -732,7 +1008,7 @@ fn impl_postgres_type(ast: DeriveInput) -> proc_macro2::TokenStream {
             #[og_extern(immutable,parallel_safe)]
             pub fn #funcname_in #generics(input: Option<&#lifetime ::ogx::cstr_core::CStr>) -> Option<#name #generics> {
                 input.map_or_else(|| {
-                    for m in <#name as ::ogx::JsonInOutFuncs>::NULL_ERROR_MESSAGE {
+                    if let Some(m) = <#name as ::ogx::JsonInOutFuncs>::NULL_ERROR_MESSAGE {
                         ::ogx::error!("{}", m);
                     }
                     None
@@ -794,6 +1070,90 @@ fn impl_postgres_type(ast: DeriveInput) -> proc_macro2::TokenStream {
         });
     }
 
+    if args.contains(&OgTypeAttribute::SendRecvFuncs) {
+        // send/recv operate on the same representation the _in/_out functions do
+        if args.contains(&OgTypeAttribute::PgVarlenaInOutFuncs) {
+            stream.extend(quote! {
+                #[doc(hidden)]
+                #[og_extern(immutable,parallel_safe)]
+                pub fn #funcname_send #generics(input: ::ogx::PgVarlena<#name #generics>) -> Vec<u8> {
+                    ::ogx::SendRecvFuncs::send(&*input)
+                }
+
+                #[doc(hidden)]
+                #[og_extern(immutable,parallel_safe)]
+                pub fn #funcname_recv #generics(input: ::ogx::Internal) -> ::ogx::PgVarlena<#name #generics> {
+                    let buffer = unsafe { ::ogx::recv_buffer(&input) };
+                    let mut result = ::ogx::PgVarlena::<#name #generics>::new();
+                    *result = <#name as ::ogx::SendRecvFuncs>::recv(buffer);
+                    result
+                }
+            });
+        } else {
+            stream.extend(quote! {
+                #[doc(hidden)]
+                #[og_extern(immutable,parallel_safe)]
+                pub fn #funcname_send #generics(input: #name #generics) -> Vec<u8> {
+                    ::ogx::SendRecvFuncs::send(&input)
+                }
+
+                #[doc(hidden)]
+                #[og_extern(immutable,parallel_safe)]
+                pub fn #funcname_recv #generics(input: ::ogx::Internal) -> #name #generics {
+                    let buffer = unsafe { ::ogx::recv_buffer(&input) };
+                    <#name as ::ogx::SendRecvFuncs>::recv(buffer)
+                }
+            });
+        }
+    }
+
+    if args.contains(&OgTypeAttribute::TypmodInOutFuncs) {
+        stream.extend(quote! {
+            #[doc(hidden)]
+            #[og_extern(immutable,parallel_safe)]
+            pub fn #funcname_typmod_in(input: ::ogx::Array<&::ogx::cstr_core::CStr>) -> i32 {
+                let modifiers = input
+                    .iter()
+                    .map(|m| {
+                        m.expect("type modifiers cannot be NULL")
+                            .to_str()
+                            .expect("type modifier is not valid UTF8")
+                    })
+                    .collect::<Vec<_>>();
+                <#name as ::ogx::TypmodInOutFuncs>::typmod_in(&modifiers)
+            }
+
+            #[doc(hidden)]
+            #[og_extern(immutable,parallel_safe)]
+            pub fn #funcname_typmod_out(typmod: i32) -> &'static ::ogx::cstr_core::CStr {
+                let mut buffer = ::ogx::StringInfo::new();
+                buffer.push_str(&<#name as ::ogx::TypmodInOutFuncs>::typmod_out(typmod));
+                buffer.into()
+            }
+        });
+
+        // the coercion function operates on the same representation the _in/_out functions do
+        if args.contains(&OgTypeAttribute::PgVarlenaInOutFuncs) {
+            stream.extend(quote! {
+                #[doc(hidden)]
+                #[og_extern(immutable,parallel_safe)]
+                pub fn #funcname_typmod_coerce #generics(input: ::ogx::PgVarlena<#name #generics>, typmod: i32, explicit: bool) -> ::ogx::PgVarlena<#name #generics> {
+                    let mut result = ::ogx::PgVarlena::<#name #generics>::new();
+                    *result = ::ogx::TypmodInOutFuncs::typmod_coerce(*input, typmod, explicit);
+                    result
+                }
+            });
+        } else {
+            stream.extend(quote! {
+                #[doc(hidden)]
+                #[og_extern(immutable,parallel_safe)]
+                pub fn #funcname_typmod_coerce #generics(input: #name #generics, typmod: i32, explicit: bool) -> #name #generics {
+                    ::ogx::TypmodInOutFuncs::typmod_coerce(input, typmod, explicit)
+                }
+            });
+        }
+    }
+
     let sql_graph_entity_item = OgType::from_derive_input(ast).unwrap();
     sql_graph_entity_item.to_tokens(&mut stream);
 
@@ -886,6 +1246,8 @@ fn impl_guc_enum(ast: DeriveInput) -> proc_macro2::TokenStream {
 enum OgTypeAttribute {
     InOutFuncs,
     PgVarlenaInOutFuncs,
+    SendRecvFuncs,
+    TypmodInOutFuncs,
     Default,
 }
 
@@ -904,6 +1266,14 @@ fn parse_postgres_type_args(attributes: &[Attribute]) -> HashSet<OgTypeAttribute
                 categorized_attributes.insert(OgTypeAttribute::PgVarlenaInOutFuncs);
             }
 
+            "sendrecvfuncs" => {
+                categorized_attributes.insert(OgTypeAttribute::SendRecvFuncs);
+            }
+
+            "typmod_inoutfuncs" => {
+                categorized_attributes.insert(OgTypeAttribute::TypmodInOutFuncs);
+            }
+
             _ => {
                 // we can just ignore attributes we don't understand
             }
@@ -1069,3 +1439,37 @@ pub fn og_trigger(attrs: TokenStream, input: TokenStream) -> TokenStream {
         }
     }
 }
+
+/**
+Create a [PostgreSQL event trigger function](https://www.postgresql.org/docs/current/event-triggers.html)
+
+Review the `ogx::trigger_support::OgEventTrigger` documentation for use.
+
+ */
+#[proc_macro_attribute]
+pub fn og_event_trigger(attrs: TokenStream, input: TokenStream) -> TokenStream {
+    fn wrapped(attrs: TokenStream, input: TokenStream) -> Result<TokenStream, syn::Error> {
+        use ogx_utils::sql_entity_graph::{OgTrigger, OgTriggerAttribute};
+        use syn::parse::Parser;
+        use syn::punctuated::Punctuated;
+        use syn::Token;
+
+        let attributes =
+            Punctuated::<OgTriggerAttribute, Token![,]>::parse_terminated.parse(attrs)?;
+        let item_fn: syn::ItemFn = syn::parse(input)?;
+        let trigger_item = OgTrigger::new_event_trigger(item_fn, attributes)?;
+        let trigger_tokens = trigger_item.to_token_stream();
+
+        Ok(trigger_tokens.into())
+    }
+
+    match wrapped(attrs, input) {
+        Ok(tokens) => tokens,
+        Err(e) => {
+            let msg = e.to_string();
+            TokenStream::from(quote! {
+              compile_error!(#msg);
+            })
+        }
+    }
+}
@@ -10,7 +10,7 @@ Use of this source code is governed by the MIT license that can be found in the
 use eyre::{eyre, WrapErr};
 use owo_colors::OwoColorize;
 use serde_derive::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::ffi::OsString;
 use std::fmt::{self, Display, Formatter};
 use std::io::ErrorKind;
@@ -46,10 +46,40 @@ impl Display for OgVersion {
     }
 }
 
+/// An openGauss installation registered without a `pg_config`, such as the lite and enterprise
+/// packages which only ship `gaussdb`, described by the directories `pg_config` would report
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Installation {
+    /// The installation's root, or `$GAUSSHOME`
+    pub root: PathBuf,
+    /// The `X.Y.Z` version of its `gaussdb`
+    pub version: String,
+    pub bindir: PathBuf,
+    pub includedir_server: PathBuf,
+    pub pkglibdir: PathBuf,
+    pub sharedir: PathBuf,
+    /// The headers of the gcc the installation was built with, if not the binarylibs' gcc 7.3
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gcc_include_dir: Option<PathBuf>,
+}
+
+/// The ports of an installation that can't use the `og$VERSION` ones, because another installation
+/// of its version already does
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ports {
+    pub port: u16,
+    pub test_port: u16,
+}
+
 #[derive(Clone)]
 pub struct PgConfig {
     version: Option<OgVersion>,
     pg_config: Option<PathBuf>,
+    installation: Option<Installation>,
+    /// What `config.toml` calls it, such as `og3` or `og3-debug`
+    name: Option<String>,
+    /// Set if the installation doesn't use the `base_port`/`base_testing_port` of its version
+    ports: Option<Ports>,
     base_port: u16,
     base_testing_port: u16,
 }
@@ -59,9 +89,10 @@ impl Display for PgConfig {
         let major = self.major_version().expect("could not determine major version");
         let middle = self.middle_version().expect("could not determine middle version");
         let minor = self.minor_version().expect("could not determine minor version");
-        let path = match self.pg_config.as_ref() {
-            Some(path) => path.display().to_string(),
-            None => self.version.as_ref().unwrap().url.to_string(),
+        let path = match (self.pg_config.as_ref(), self.installation.as_ref()) {
+            (Some(path), _) => path.display().to_string(),
+            (None, Some(installation)) => installation.root.display().to_string(),
+            (None, None) => self.version.as_ref().unwrap().url.to_string(),
         };
         write!(f, "{}.{}.{}={}", major, middle, minor, path)
     }
@@ -72,6 +103,9 @@ impl Default for PgConfig {
         PgConfig {
             version: None,
             pg_config: None,
+            installation: None,
+            name: None,
+            ports: None,
             base_port: BASE_OPENGAUSS_PORT_NO,
             base_testing_port: BASE_OPENGAUSS_TESTING_PORT_NO,
         }
@@ -86,28 +120,92 @@ impl From<OgVersion> for PgConfig {
 
 impl PgConfig {
     pub fn new(pg_config: PathBuf, base_port: u16, base_testing_port: u16) -> Self {
-        PgConfig { version: None, pg_config: Some(pg_config), base_port, base_testing_port }
+        PgConfig {
+            version: None,
+            pg_config: Some(pg_config),
+            installation: None,
+            name: None,
+            ports: None,
+            base_port,
+            base_testing_port,
+        }
     }
 
     pub fn new_with_defaults(pg_config: PathBuf) -> Self {
         PgConfig {
             version: None,
             pg_config: Some(pg_config),
+            installation: None,
+            name: None,
+            ports: None,
             base_port: BASE_OPENGAUSS_PORT_NO,
             base_testing_port: BASE_OPENGAUSS_TESTING_PORT_NO,
         }
     }
 
+    pub fn from_installation(
+        installation: Installation,
+        base_port: u16,
+        base_testing_port: u16,
+    ) -> Self {
+        PgConfig {
+            version: None,
+            pg_config: None,
+            installation: Some(installation),
+            name: None,
+            ports: None,
+            base_port,
+            base_testing_port,
+        }
+    }
+
     pub fn from_path() -> Self {
         Self::new_with_defaults("pg_config".into())
     }
 
+    /// Name this installation, so it can be told apart from others of the same major version
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Run and test on `ports` instead of the ones of its version
+    pub fn with_ports(mut self, ports: Ports) -> Self {
+        self.ports = Some(ports);
+        self
+    }
+
+    /// The ports it was given, if it doesn't use the ones of its version
+    pub fn ports(&self) -> Option<Ports> {
+        self.ports
+    }
+
+    /// The name, if it's other than the `og$VERSION` an unnamed installation goes by
+    fn custom_name(&self) -> eyre::Result<Option<&str>> {
+        match self.name.as_deref() {
+            Some(name) if name != format!("og{}", self.major_version()?) => Ok(Some(name)),
+            _ => Ok(None),
+        }
+    }
+
     pub fn is_real(&self) -> bool {
-        self.pg_config.is_some()
+        self.pg_config.is_some() || self.installation.is_some()
+    }
+
+    /// The installation this was registered as, if it has no `pg_config`
+    pub fn installation(&self) -> Option<&Installation> {
+        self.installation.as_ref()
     }
 
     pub fn label(&self) -> eyre::Result<String> {
-        Ok(format!("og{}", self.major_version()?))
+        match &self.name {
+            Some(name) => Ok(name.clone()),
+            None => Ok(format!("og{}", self.major_version()?)),
+        }
     }
 
     pub fn path(&self) -> Option<PathBuf> {
@@ -122,12 +220,11 @@ impl PgConfig {
         match &self.version {
             Some(version) => Ok(version.major),
             None => {
-                let version_string = self.run("--version")?;
-                let version_parts = version_string.split_whitespace().collect::<Vec<&str>>();
-                let version = match version_parts.get(3) {
-                    Some(v) => v.split('.').nth(0).unwrap(),
+                let version_number = self.version_number()?;
+                let version = match version_number.split('.').nth(0) {
+                    Some(v) => v,
                     None => {
-                        return Err(eyre!("invalid version string: {}", version_string));
+                        return Err(eyre!("invalid version number: {}", version_number));
                     }
                 };
                 let version = match f64::from_str(version) {
@@ -145,12 +242,11 @@ impl PgConfig {
         match &self.version {
             Some(version) => Ok(version.middle),
             None => {
-                let version_string = self.run("--version")?;
-                let version_parts = version_string.split_whitespace().collect::<Vec<&str>>();
-                let version = match version_parts.get(3) {
-                    Some(v) => v.split('.').nth(1).unwrap(),
+                let version_number = self.version_number()?;
+                let version = match version_number.split('.').nth(1) {
+                    Some(v) => v,
                     None => {
-                        return Err(eyre!("invalid version string: {}", version_string));
+                        return Err(eyre!("invalid version number: {}", version_number));
                     }
                 };
                 let version = match f64::from_str(version) {
@@ -168,12 +264,11 @@ impl PgConfig {
         match &self.version {
             Some(version) => Ok(version.minor),
             None => {
-                let version_string = self.run("--version")?;
-                let version_parts = version_string.split_whitespace().collect::<Vec<&str>>();
-                let version = match version_parts.get(3) {
-                    Some(v) => v.split('.').nth(2).unwrap(),
+                let version_number = self.version_number()?;
+                let version = match version_number.split('.').nth(2) {
+                    Some(v) => v,
                     None => {
-                        return Err(eyre!("invalid version string: {}", version_string));
+                        return Err(eyre!("invalid version number: {}", version_number));
                     }
                 };
                 let version = match u16::from_str(version) {
@@ -187,6 +282,18 @@ impl PgConfig {
         }
     }
 
+    /// The `X.Y.Z` part of `pg_config --version`
+    fn version_number(&self) -> eyre::Result<String> {
+        if let Some(installation) = &self.installation {
+            return Ok(installation.version.clone());
+        }
+        let version_string = self.run("--version")?;
+        match version_string.split_whitespace().nth(3) {
+            Some(version_number) => Ok(version_number.to_string()),
+            None => Err(eyre!("invalid version string: {}", version_string)),
+        }
+    }
+
     pub fn version(&self) -> eyre::Result<String> {
         let major = self.major_version()?;
         let middle = self.middle_version()?;
@@ -210,11 +317,17 @@ impl PgConfig {
     }
 
     pub fn port(&self) -> eyre::Result<u16> {
-        Ok(self.base_port + self.major_version()?)
+        match self.ports {
+            Some(ports) => Ok(ports.port),
+            None => Ok(self.base_port + self.major_version()?),
+        }
     }
 
     pub fn test_port(&self) -> eyre::Result<u16> {
-        Ok(self.base_testing_port + self.major_version()?)
+        match self.ports {
+            Some(ports) => Ok(ports.test_port),
+            None => Ok(self.base_testing_port + self.major_version()?),
+        }
     }
 
     pub fn host(&self) -> &'static str {
@@ -245,11 +358,19 @@ impl PgConfig {
 
     pub fn data_dir(&self) -> eyre::Result<PathBuf> {
         let mut path = Ogx::home()?;
-        path.push(format!("data-{}", self.major_version()?));
+        match self.custom_name()? {
+            Some(name) => path.push(format!("data-{}", name)),
+            None => path.push(format!("data-{}", self.major_version()?)),
+        }
         Ok(path)
     }
 
     pub fn gcc_include_dir(&self) -> eyre::Result<PathBuf> {
+        if let Some(gcc_include_dir) =
+            self.installation.as_ref().and_then(|installation| installation.gcc_include_dir.clone())
+        {
+            return Ok(gcc_include_dir);
+        }
         let mut path = self.bin_dir()?;
         path.pop();
         path.pop();
@@ -259,7 +380,10 @@ impl PgConfig {
 
     pub fn log_file(&self) -> eyre::Result<PathBuf> {
         let mut path = Ogx::home()?;
-        path.push(format!("{}.log", self.major_version()?));
+        match self.custom_name()? {
+            Some(name) => path.push(format!("{}.log", name)),
+            None => path.push(format!("{}.log", self.major_version()?)),
+        }
         Ok(path)
     }
 
@@ -279,6 +403,11 @@ impl PgConfig {
         Ok(self.run("--cppflags")?.into())
     }
 
+    /// The `pgxs.mk` extension makefiles include
+    pub fn pgxs(&self) -> eyre::Result<PathBuf> {
+        Ok(self.run("--pgxs")?.into())
+    }
+
     pub fn extension_dir(&self) -> eyre::Result<PathBuf> {
         let mut path = self.sharedir()?;
         path.push("extension");
@@ -286,6 +415,39 @@ impl PgConfig {
     }
 
     fn run(&self, arg: &str) -> eyre::Result<String> {
+        if let Some(installation) = &self.installation {
+            // answer what `pg_config` would have from what `cargo ogx init` discovered
+            let path = match arg {
+                "--bindir" => installation.bindir.clone(),
+                "--includedir-server" => installation.includedir_server.clone(),
+                "--pkglibdir" => installation.pkglibdir.clone(),
+                "--sharedir" => installation.sharedir.clone(),
+                "--pgxs" => {
+                    let pgxs = installation.pkglibdir.join(PGXS_MK);
+                    if !pgxs.exists() {
+                        return Err(eyre!(
+                            "{} doesn't exist, so extensions can't be built against the openGauss \
+                             installation at {}.  Install the package's development files into \
+                             it, then run `{}` again.",
+                            pgxs.display(),
+                            installation.root.display(),
+                            "cargo ogx init".bold().yellow()
+                        ));
+                    }
+                    pgxs
+                }
+                "--cppflags" => return Ok(String::new()),
+                _ => {
+                    return Err(eyre!(
+                        "`pg_config {}` isn't known for the openGauss installation at {}",
+                        arg,
+                        installation.root.display()
+                    ))
+                }
+            };
+            return Ok(path.display().to_string());
+        }
+
         let pg_config = self.pg_config.clone().unwrap_or_else(|| {
             std::env::var("PG_CONFIG").unwrap_or_else(|_| "pg_config".to_string()).into()
         });
@@ -321,13 +483,21 @@ impl Default for Ogx {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct ConfigToml {
-    configs: HashMap<String, PathBuf>,
+/// `cargo-ogx`'s `config.toml`, as written by `cargo ogx init`
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ConfigToml {
+    // toml can't write values after tables, so the ports come first
     #[serde(skip_serializing_if = "Option::is_none")]
-    base_port: Option<u16>,
+    pub base_port: Option<u16>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    base_testing_port: Option<u16>,
+    pub base_testing_port: Option<u16>,
+    #[serde(default)]
+    pub configs: BTreeMap<String, PathBuf>,
+    #[serde(default)]
+    pub installations: BTreeMap<String, Installation>,
+    /// The ports of the installations which can't use those of their version
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub ports: BTreeMap<String, Ports>,
 }
 
 pub enum PgConfigSelector<'a> {
@@ -376,8 +546,28 @@ impl Ogx {
                             configs.base_testing_port.unwrap_or(BASE_OPENGAUSS_TESTING_PORT_NO),
                         );
 
-                        for (_, v) in configs.configs {
-                            ogx.push(PgConfig::new(v, ogx.base_port, ogx.base_testing_port));
+                        let with_ports = |pg_config: PgConfig, name: String| {
+                            match configs.ports.get(&name) {
+                                Some(ports) => pg_config.with_ports(*ports),
+                                None => pg_config,
+                            }
+                            .with_name(name)
+                        };
+                        for (k, v) in configs.configs {
+                            ogx.push(with_ports(
+                                PgConfig::new(v, ogx.base_port, ogx.base_testing_port),
+                                k,
+                            ));
+                        }
+                        for (k, v) in configs.installations {
+                            ogx.push(with_ports(
+                                PgConfig::from_installation(
+                                    v,
+                                    ogx.base_port,
+                                    ogx.base_testing_port,
+                                ),
+                                k,
+                            ));
                         }
                         Ok(ogx)
                     }
@@ -404,6 +594,7 @@ impl Ogx {
                     a.major_version()
                         .expect("no major version")
                         .cmp(&b.major_version().expect("no major version"))
+                        .then_with(|| a.name.cmp(&b.name))
                 });
 
                 configs.into_iter().map(|c| Ok(c)).collect::<Vec<_>>().into_iter()
@@ -412,13 +603,52 @@ impl Ogx {
         }
     }
 
+    /// Every `pg_config`, in no particular order and without asking any of them for its version
+    pub fn pg_configs(&self) -> &[PgConfig] {
+        &self.pg_configs
+    }
+
+    /// The installation named `label`, or the only installation of the `og$VERSION` it is
+    ///
+    /// When there are several installations of a version, `$OGX_INSTALLATION` names the one
+    /// `og$VERSION` means.
     pub fn get(&self, label: &str) -> eyre::Result<&PgConfig> {
-        for pg_config in self.pg_configs.iter() {
-            if pg_config.label()? == label {
+        if let Ok(selected) = std::env::var("OGX_INSTALLATION") {
+            let pg_config = self.named(&selected).ok_or_else(|| {
+                eyre!("OGX_INSTALLATION is `{}`, which is not managed by ogx", selected)
+            })?;
+            if selected == label || format!("og{}", pg_config.major_version()?) == label {
                 return Ok(pg_config);
             }
         }
-        Err(eyre!("openGauss `{}` is not managed by ogx", label))
+        if let Some(pg_config) = self.named(label) {
+            return Ok(pg_config);
+        }
+
+        let mut found = Vec::new();
+        for pg_config in self.pg_configs.iter() {
+            if format!("og{}", pg_config.major_version()?) == label {
+                found.push(pg_config);
+            }
+        }
+        match found.as_slice() {
+            [] => Err(eyre!("openGauss `{}` is not managed by ogx", label)),
+            [pg_config] => Ok(pg_config),
+            _ => Err(eyre!(
+                "there are several openGauss `{}` installations ({}), name one of them or set \
+                 OGX_INSTALLATION",
+                label,
+                found
+                    .iter()
+                    .filter_map(|pg_config| pg_config.name())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        }
+    }
+
+    fn named(&self, name: &str) -> Option<&PgConfig> {
+        self.pg_configs.iter().find(|pg_config| pg_config.name() == Some(name))
     }
 
     pub fn home() -> Result<PathBuf, std::io::Error> {
@@ -469,6 +699,9 @@ impl Ogx {
 
 pub const SUPPORTED_MAJOR_VERSIONS: &[u16] = &[3];
 
+/// Where `pgxs.mk` lives under an installation's `pkglibdir`
+pub const PGXS_MK: &str = "pgxs/src/makefiles/pgxs.mk";
+
 pub fn createdb(
     pg_config: &PgConfig,
     dbname: &str,
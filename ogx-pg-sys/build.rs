@@ -94,6 +94,7 @@ fn main() -> eyre::Result<()> {
     let is_for_release =
         std::env::var("OGX_PG_SYS_GENERATE_BINDINGS_FOR_RELEASE").unwrap_or("0".to_string()) == "1";
     println!("cargo:rerun-if-env-changed=OGX_PG_SYS_GENERATE_BINDINGS_FOR_RELEASE");
+    println!("cargo:rerun-if-env-changed=OGX_INSTALLATION");
 
     // Do nightly detection to suppress silly warnings.
     if is_nightly() {
@@ -614,7 +615,11 @@ fn build_shim_for_version(
     shim_dst: &PathBuf,
     pg_config: &PgConfig,
 ) -> eyre::Result<()> {
-    let path_env = prefix_path(pg_config.parent_path());
+    // an installation registered without a `pg_config` can't have the Makefile ask it for PGXS
+    let (path_env, pgxs) = match pg_config.path() {
+        Some(_) => (prefix_path(pg_config.parent_path()), None),
+        None => (prefix_path(pg_config.bin_dir()?), Some(pg_config.pgxs()?)),
+    };
     let major_version = pg_config.major_version()?;
 
     eprintln!("PATH for build_shim={}", path_env);
@@ -640,15 +645,17 @@ fn build_shim_for_version(
     }
 
     let make = option_env!("MAKE").unwrap_or("make").to_string();
-    let rc = run_command(
-        Command::new(make)
-            .arg("clean")
-            .arg(&format!("libogx-cshim-{}.a", major_version))
-            .env("OG_TARGET_VERSION", format!("{}", major_version))
-            .env("PATH", path_env)
-            .current_dir(shim_dst),
-        &format!("shim for OG v{}", major_version),
-    )?;
+    let mut command = Command::new(make);
+    command
+        .arg("clean")
+        .arg(&format!("libogx-cshim-{}.a", major_version))
+        .env("OG_TARGET_VERSION", format!("{}", major_version))
+        .env("PATH", path_env)
+        .current_dir(shim_dst);
+    if let Some(pgxs) = pgxs {
+        command.arg(format!("PGXS={}", pgxs.display()));
+    }
+    let rc = run_command(&mut command, &format!("shim for OG v{}", major_version))?;
 
     if rc.status.code().unwrap() != 0 {
         return Err(eyre!("failed to make ogx-cshim for v{}", major_version));
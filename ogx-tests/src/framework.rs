@@ -198,6 +198,199 @@ pub fn run_test(
     }
 }
 
+/// How long one sample of a benchmark should take at least, so timer resolution doesn't matter
+const BENCH_SAMPLE_NANOS: i64 = 10_000_000;
+
+/// Run the `#[og_bench]` benchmark timed by `sql_funcname`, report its statistics, and compare its
+/// median against a saved baseline.
+///
+/// The baseline compared against is `OGX_BENCH_BASELINE` (default `base`), and a benchmark whose
+/// median grew by more than `OGX_BENCH_THRESHOLD` percent (default 5) since then fails.  The
+/// results are only saved when `OGX_BENCH_SAVE_BASELINE` names a baseline to save them as
+pub fn run_bench(
+    sql_funcname: &str,
+    name: &str,
+    postgresql_conf: Vec<&'static str>,
+) -> eyre::Result<()> {
+    initialize_test_framework(postgresql_conf)?;
+
+    let (mut client, _) = client()?;
+    let schema = "tests"; // get_extension_schema();
+    let query = format!("SELECT \"{schema}\".\"{sql_funcname}\"($1);");
+
+    // like tests, benchmarks run in a transaction that's rolled back when they're done
+    let mut tx = client.transaction()?;
+    let mut time = |iterations: i64| -> eyre::Result<i64> {
+        Ok(tx.query_one(&query, &[&iterations])?.get::<_, i64>(0))
+    };
+
+    // double the iterations per sample until a sample is long enough, which also warms up
+    let mut iterations = 1;
+    while time(iterations)? < BENCH_SAMPLE_NANOS && iterations < 1 << 30 {
+        iterations *= 2;
+    }
+
+    let samples = match std::env::var("OGX_BENCH_SAMPLES") {
+        Ok(samples) => samples.parse().wrap_err("OGX_BENCH_SAMPLES is not a number")?,
+        Err(_) => 50,
+    };
+    let mut nanos = Vec::with_capacity(samples);
+    for _ in 0..samples.max(1) {
+        nanos.push(time(iterations)? as f64 / iterations as f64);
+    }
+    drop(time);
+    tx.rollback()?;
+
+    let stats = BenchStats::new(iterations, nanos);
+    println!("{} {}", "bench".bold().green(), name.bold());
+    println!(
+        "    time: min {} median {} mean {} max {} (± {}, {} samples of {} iterations)",
+        format_nanos(stats.min),
+        format_nanos(stats.median).bold(),
+        format_nanos(stats.mean),
+        format_nanos(stats.max),
+        format_nanos(stats.stddev),
+        stats.samples,
+        stats.iterations,
+    );
+
+    let threshold = match std::env::var("OGX_BENCH_THRESHOLD") {
+        Ok(threshold) => threshold.parse().wrap_err("OGX_BENCH_THRESHOLD is not a number")?,
+        Err(_) => 5.0,
+    };
+    let baseline = std::env::var("OGX_BENCH_BASELINE").unwrap_or("base".into());
+    let mut regressed = false;
+    if let Some(base) = BenchStats::load(&baseline, name)? {
+        let change = (stats.median - base.median) / base.median * 100.0;
+        let verdict = if change > threshold {
+            regressed = true;
+            "regressed".bold().red().to_string()
+        } else if change < -threshold {
+            "improved".bold().green().to_string()
+        } else {
+            "no change".to_string()
+        };
+        println!(
+            "    change: {:+.2}% from {} in baseline `{}`, {}",
+            change,
+            format_nanos(base.median),
+            baseline,
+            verdict
+        );
+    }
+
+    if let Ok(save) = std::env::var("OGX_BENCH_SAVE_BASELINE") {
+        stats.save(&save, name)?;
+    }
+
+    if regressed {
+        return Err(eyre!(
+            "{} regressed by more than {}% from baseline `{}`",
+            name,
+            threshold,
+            baseline
+        ));
+    }
+    Ok(())
+}
+
+/// Statistics of the time one iteration of a benchmark takes, in nanoseconds
+#[derive(Debug, Clone, PartialEq)]
+struct BenchStats {
+    iterations: i64,
+    samples: usize,
+    min: f64,
+    median: f64,
+    mean: f64,
+    max: f64,
+    stddev: f64,
+}
+
+impl BenchStats {
+    fn new(iterations: i64, mut nanos: Vec<f64>) -> Self {
+        nanos.sort_by(|a, b| a.total_cmp(b));
+        let samples = nanos.len();
+        let mean = nanos.iter().sum::<f64>() / samples as f64;
+        let variance = nanos.iter().map(|n| (n - mean).powi(2)).sum::<f64>() / samples as f64;
+        let median = if samples % 2 == 0 {
+            (nanos[samples / 2 - 1] + nanos[samples / 2]) / 2.0
+        } else {
+            nanos[samples / 2]
+        };
+        BenchStats {
+            iterations,
+            samples,
+            min: nanos[0],
+            median,
+            mean,
+            max: nanos[samples - 1],
+            stddev: variance.sqrt(),
+        }
+    }
+
+    /// Baselines are kept in `target/ogx-bench/<baseline>/`, one JSON file per benchmark
+    fn path(baseline: &str, name: &str) -> eyre::Result<PathBuf> {
+        let mut path = get_target_dir()?;
+        path.push("ogx-bench");
+        path.push(baseline);
+        path.push(format!("{}.json", name.replace("::", ".")));
+        Ok(path)
+    }
+
+    fn load(baseline: &str, name: &str) -> eyre::Result<Option<Self>> {
+        let path = Self::path(baseline, name)?;
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e).wrap_err_with(|| format!("unable to read {}", path.display())),
+        };
+        let json: serde_json::Value = serde_json::from_str(&contents)
+            .wrap_err_with(|| format!("{} is not a valid baseline", path.display()))?;
+        let field = |field: &str| {
+            json[field]
+                .as_f64()
+                .ok_or_else(|| eyre!("{} has no `{}`", path.display(), field))
+        };
+        Ok(Some(BenchStats {
+            iterations: field("iterations")? as i64,
+            samples: field("samples")? as usize,
+            min: field("min_ns")?,
+            median: field("median_ns")?,
+            mean: field("mean_ns")?,
+            max: field("max_ns")?,
+            stddev: field("stddev_ns")?,
+        }))
+    }
+
+    fn save(&self, baseline: &str, name: &str) -> eyre::Result<()> {
+        let path = Self::path(baseline, name)?;
+        std::fs::create_dir_all(path.parent().unwrap())?;
+        let json = serde_json::json!({
+            "iterations": self.iterations,
+            "samples": self.samples,
+            "min_ns": self.min,
+            "median_ns": self.median,
+            "mean_ns": self.mean,
+            "max_ns": self.max,
+            "stddev_ns": self.stddev,
+        });
+        std::fs::write(&path, serde_json::to_string_pretty(&json)?)
+            .wrap_err_with(|| format!("unable to write {}", path.display()))
+    }
+}
+
+fn format_nanos(nanos: f64) -> String {
+    if nanos < 1_000.0 {
+        format!("{:.2} ns", nanos)
+    } else if nanos < 1_000_000.0 {
+        format!("{:.2} µs", nanos / 1_000.0)
+    } else if nanos < 1_000_000_000.0 {
+        format!("{:.2} ms", nanos / 1_000_000.0)
+    } else {
+        format!("{:.2} s", nanos / 1_000_000_000.0)
+    }
+}
+
 fn format_loglines(session_id: &str, loglines: &LogLines) -> String {
     let mut result = String::new();
 
@@ -307,9 +500,13 @@ fn install_extension() -> eyre::Result<()> {
         std::env::var("OGX_NO_DEFAULT_FEATURES").unwrap_or("false".to_string()) == "true";
     let all_features = std::env::var("OGX_ALL_FEATURES").unwrap_or("false".to_string()) == "true";
 
-    let pg_version = format!("pg{}", pg_sys::get_pg_major_version_string());
-    let ogx = Ogx::from_config()?;
-    let pg_config = ogx.get(&pg_version)?;
+    let pg_config = get_pg_config()?;
+
+    // an installation registered without a `pg_config` is named by its label instead
+    let pg_config_arg = match pg_config.installation() {
+        Some(_) => pg_config.label()?.into(),
+        None => pg_config.path().ok_or(eyre!("No pg_config found"))?,
+    };
 
     let mut command = Command::new("cargo");
     command
@@ -317,7 +514,7 @@ fn install_extension() -> eyre::Result<()> {
         .arg("install")
         .arg("--test")
         .arg("--pg-config")
-        .arg(pg_config.path().ok_or(eyre!("No pg_config found"))?)
+        .arg(pg_config_arg)
         .stdout(Stdio::inherit())
         .stderr(Stdio::piped())
         .env("CARGO_TARGET_DIR", get_target_dir()?);
@@ -615,7 +812,13 @@ fn get_extension_name() -> String {
 
 fn get_pgdata_path() -> eyre::Result<PathBuf> {
     let mut target_dir = get_target_dir()?;
-    target_dir.push(&format!("ogx-test-data-{}", pg_sys::get_pg_major_version_num()));
+    // several installations of a major version each need their own cluster
+    let major = pg_sys::get_pg_major_version_num().to_string();
+    let suffix = match std::env::var("OGX_INSTALLATION") {
+        Ok(name) if name != format!("og{}", major) => name,
+        _ => major,
+    };
+    target_dir.push(&format!("ogx-test-data-{}", suffix));
     Ok(target_dir)
 }
 
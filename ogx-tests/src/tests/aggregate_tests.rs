@@ -106,6 +106,39 @@ impl Aggregate for DemoUnique {
     }
 }
 
+#[derive(Copy, Clone, Default, Debug, OgType, Serialize, Deserialize)]
+pub struct DemoAvg {
+    sum: i64,
+    count: i64,
+}
+
+#[pg_aggregate]
+impl Aggregate for DemoAvg {
+    const INITIAL_CONDITION: Option<&'static str> = Some(r#"{"sum": 0, "count": 0}"#);
+
+    type Args = i32;
+    type State = DemoAvg;
+    type Finalize = f64;
+
+    fn state(
+        mut current: Self::State,
+        arg: Self::Args,
+        _fcinfo: pg_sys::FunctionCallInfo,
+    ) -> Self::State {
+        current.sum += arg as i64;
+        current.count += 1;
+        current
+    }
+
+    fn finalize(
+        current: Self::State,
+        _direct_args: Self::OrderedSetArgs,
+        _fcinfo: pg_sys::FunctionCallInfo,
+    ) -> Self::Finalize {
+        current.sum as f64 / current.count as f64
+    }
+}
+
 #[derive(Copy, Clone, Default, Debug, OgType, Serialize, Deserialize)]
 pub struct DemoPercentileDisc;
 
@@ -179,6 +212,14 @@ mod tests {
         assert_eq!(retval, 2);
     }
 
+    #[og_test]
+    fn aggregate_demo_avg() {
+        let retval =
+            Spi::get_one::<f64>("SELECT DemoAvg(value) FROM UNNEST(ARRAY [1, 2, 6]) as value;")
+                .expect("SQL select failed");
+        assert_eq!(retval, 3.0);
+    }
+
     #[og_test]
     fn aggregate_demo_percentile_disc() {
         let retval = Spi::get_one::<i32>(
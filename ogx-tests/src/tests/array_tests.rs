@@ -46,6 +46,14 @@ fn sum_array_i64_sliced(values: Array<i64>) -> i64 {
     values.as_slice().iter().sum()
 }
 
+#[og_extern]
+fn sum_array_f64_try_sliced(values: Array<f64>) -> f64 {
+    match values.try_as_slice() {
+        Some(slice) => slice.iter().sum(),
+        None => values.iter().map(|v| v.unwrap_or(0f64)).sum(),
+    }
+}
+
 #[og_extern]
 fn count_true(values: Array<bool>) -> i32 {
     values.iter().filter(|b| b.unwrap_or(false)).count() as i32
@@ -218,6 +226,22 @@ mod tests {
         assert_eq!(sum.unwrap(), 6);
     }
 
+    #[og_test]
+    fn test_sum_array_f64_try_sliced() {
+        let sum = Spi::get_one::<f64>(
+            "SELECT sum_array_f64_try_sliced(ARRAY[1.5,2.5,3.0]::double precision[])",
+        );
+        assert_eq!(sum, Some(7.0));
+    }
+
+    #[og_test]
+    fn test_sum_array_f64_try_sliced_with_null() {
+        let sum = Spi::get_one::<f64>(
+            "SELECT sum_array_f64_try_sliced(ARRAY[1.5,NULL,3.0]::double precision[])",
+        );
+        assert_eq!(sum, Some(4.5));
+    }
+
     #[og_test(error = "attempt to add with overflow")]
     fn test_sum_array_i32_overflow() {
         Spi::get_one::<i64>(
@@ -0,0 +1,36 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+
+#[cfg(any(test, feature = "og_test"))]
+#[ogx::og_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as ogx_tests;
+
+    use ogx::prelude::*;
+
+    #[og_bench]
+    fn bench_spi_get_one() -> Option<i32> {
+        Spi::get_one::<i32>("SELECT 1")
+    }
+
+    #[og_bench]
+    fn bench_string_building() -> String {
+        let mut text = String::with_capacity(64);
+        text.push_str("hello world");
+        text
+    }
+
+    #[og_test]
+    fn test_bench_timer_returns_elapsed_time() {
+        let elapsed = Spi::get_one::<i64>("SELECT tests.ogx_bench_bench_spi_get_one(10)")
+            .expect("SPI returned NULL");
+        assert!(elapsed > 0);
+    }
+}
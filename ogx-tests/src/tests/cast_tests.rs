@@ -0,0 +1,73 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+
+use ogx::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, OgType)]
+pub struct Temperature {
+    celsius: f64,
+}
+
+#[og_extern(immutable, parallel_safe)]
+#[og_cast(implicit)]
+fn float8_to_temperature(celsius: f64) -> Temperature {
+    Temperature { celsius }
+}
+
+#[og_extern(immutable, parallel_safe)]
+#[og_cast]
+fn temperature_to_float8(temperature: Temperature) -> f64 {
+    temperature.celsius
+}
+
+#[og_extern(immutable, parallel_safe)]
+#[og_cast(assignment)]
+fn temperature_to_int4(temperature: Temperature) -> i32 {
+    temperature.celsius.round() as i32
+}
+
+#[cfg(any(test, feature = "og_test"))]
+#[ogx::og_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as ogx_tests;
+
+    use ogx::prelude::*;
+
+    #[og_test]
+    fn test_explicit_cast() {
+        let result = Spi::get_one::<f64>(r#"SELECT '{"celsius": 21.5}'::Temperature::float8"#);
+        assert_eq!(Some(21.5), result);
+    }
+
+    #[og_test]
+    fn test_implicit_cast() {
+        let result = Spi::get_one::<f64>("SELECT temperature_to_float8(21.5::float8)");
+        assert_eq!(Some(21.5), result);
+    }
+
+    #[og_test]
+    fn test_assignment_cast() {
+        Spi::run("CREATE TEMP TABLE readings (celsius int4)");
+        Spi::run(r#"INSERT INTO readings VALUES ('{"celsius": 21.5}'::Temperature)"#);
+        let result = Spi::get_one::<i32>("SELECT celsius FROM readings");
+        assert_eq!(Some(22), result);
+    }
+
+    #[og_test]
+    fn test_cast_contexts() {
+        let result = Spi::get_one::<String>(
+            "SELECT string_agg(castcontext::text, ',' ORDER BY casttarget::regtype::text)
+               FROM pg_cast
+              WHERE castsource = 'temperature'::regtype OR casttarget = 'temperature'::regtype",
+        );
+        assert_eq!(Some(String::from("e,a,i")), result);
+    }
+}
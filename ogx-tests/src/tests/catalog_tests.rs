@@ -0,0 +1,97 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+
+#[cfg(any(test, feature = "og_test"))]
+#[ogx::og_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as ogx_tests;
+
+    use ogx::catalog::{
+        self, PgAttributeEntry, PgClassEntry, PgExtension, PgNamespaceEntry, PgProcEntry,
+        PgTypeEntry,
+    };
+    use ogx::prelude::*;
+    use ogx::{PgRelation, PgTupleDesc};
+
+    #[og_test]
+    fn test_type_entry() {
+        let entry = PgTypeEntry::search(pg_sys::INT4OID).expect("no pg_type row for int4");
+        assert_eq!(entry.name(), "int4");
+        assert_eq!(entry.typlen, 4);
+        assert_eq!(entry.oid(), pg_sys::INT4OID);
+    }
+
+    #[og_test]
+    fn test_missing_entry() {
+        assert!(PgTypeEntry::search(pg_sys::InvalidOid).is_none());
+    }
+
+    #[og_test]
+    fn test_namespace_entry() {
+        let int4 = PgTypeEntry::search(pg_sys::INT4OID).expect("no pg_type row for int4");
+        let entry =
+            PgNamespaceEntry::search(int4.typnamespace).expect("no pg_namespace row for int4");
+        assert_eq!(entry.name(), "pg_catalog");
+    }
+
+    #[og_test]
+    fn test_class_and_attribute_entries() {
+        Spi::run("CREATE TABLE catalog_pets (id int4, name text)");
+        let relid = catalog::regclass("catalog_pets").expect("no relation named catalog_pets");
+
+        let class = PgClassEntry::search(relid).expect("no pg_class row for catalog_pets");
+        assert_eq!(class.name(), "catalog_pets");
+        assert_eq!(class.relnatts, 2);
+
+        let attribute = PgAttributeEntry::search((relid, 2)).expect("no attribute 2");
+        assert_eq!(attribute.name(), "name");
+        assert_eq!(attribute.atttypid, pg_sys::TEXTOID);
+    }
+
+    #[og_test]
+    fn test_relation_namespace_and_composite_type() {
+        Spi::run("CREATE SCHEMA catalog_ns");
+        Spi::run("CREATE TABLE catalog_ns.owners (id int4, name text)");
+        let relid = catalog::regclass("catalog_ns.owners").expect("no relation named owners");
+        let relation = PgRelation::with_lock(relid, pg_sys::AccessShareLock as pg_sys::LOCKMODE);
+        assert_eq!(relation.namespace(), "catalog_ns");
+
+        let tupdesc = PgTupleDesc::for_composite_type("catalog_ns.owners").expect("no row type");
+        assert_eq!(tupdesc.len(), 2);
+        assert!(PgTupleDesc::for_composite_type("no_such_type").is_none());
+    }
+
+    #[og_test]
+    fn test_proc_entry() {
+        let oid = catalog::regprocedure("lower(text)").expect("no function lower(text)");
+        let entry = PgProcEntry::search(oid).expect("no pg_proc row for lower(text)");
+        assert_eq!(entry.name(), "lower");
+        assert_eq!(entry.prorettype, pg_sys::TEXTOID);
+    }
+
+    #[og_test]
+    fn test_name_resolution() {
+        assert_eq!(catalog::regtype("integer"), Some(pg_sys::INT4OID));
+        assert_eq!(catalog::regtype("pg_catalog.text"), Some(pg_sys::TEXTOID));
+        assert_eq!(catalog::regtype("no_such_type"), None);
+        assert_eq!(catalog::regclass("pg_catalog.pg_class"), Some(pg_sys::RelationRelationId));
+        assert_eq!(catalog::regclass("no_such_relation"), None);
+        assert!(catalog::regproc("pg_catalog.lower").is_some());
+        assert_eq!(catalog::regproc("no_such_function"), None);
+    }
+
+    #[og_test]
+    fn test_extension() {
+        let extension = PgExtension::lookup("plpgsql").expect("plpgsql is not installed");
+        assert_eq!(extension.name, "plpgsql");
+        assert_ne!(extension.oid, pg_sys::InvalidOid);
+        assert!(PgExtension::lookup("no_such_extension").is_none());
+    }
+}
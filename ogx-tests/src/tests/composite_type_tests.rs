@@ -0,0 +1,95 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+
+use ogx::prelude::*;
+
+#[derive(OgComposite, PartialEq, Debug)]
+pub struct Pet {
+    name: String,
+    scritches: Option<i32>,
+}
+
+#[og_extern]
+fn pet_more(pet: Pet) -> Pet {
+    Pet { name: pet.name, scritches: Some(pet.scritches.unwrap_or_default() + 1) }
+}
+
+#[og_extern]
+fn make_pet(name: &str) -> Pet {
+    Pet { name: name.to_string(), scritches: None }
+}
+
+#[ogx::og_schema]
+mod kennel {
+    use ogx::prelude::*;
+
+    #[derive(OgComposite, PartialEq, Debug)]
+    pub struct Kennel {
+        name: String,
+        capacity: i32,
+    }
+
+    #[og_extern]
+    fn make_kennel(name: &str) -> Kennel {
+        Kennel { name: name.to_string(), capacity: 2 }
+    }
+}
+
+#[cfg(any(test, feature = "og_test"))]
+#[ogx::og_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as ogx_tests;
+
+    use crate::tests::composite_type_tests::kennel::Kennel;
+    use crate::tests::composite_type_tests::Pet;
+    use ogx::prelude::*;
+    use ogx::IntoDatum;
+
+    #[og_test]
+    fn test_pet_composite_roundtrip() {
+        let result = Spi::get_one::<Pet>("SELECT pet_more(ROW('Nami', 41)::Pet);")
+            .expect("failed to get SPI result");
+        assert_eq!(Pet { name: "Nami".to_string(), scritches: Some(42) }, result);
+    }
+
+    #[og_test]
+    fn test_pet_composite_null_field() {
+        let scritches = Spi::get_one::<i32>("SELECT (make_pet('Brandy')).scritches;");
+        assert_eq!(None, scritches);
+
+        let result = Spi::get_one::<Pet>("SELECT pet_more(make_pet('Brandy'));")
+            .expect("failed to get SPI result");
+        assert_eq!(Pet { name: "Brandy".to_string(), scritches: Some(1) }, result);
+    }
+
+    #[og_test]
+    fn test_pet_composite_field_by_name() {
+        let name = Spi::get_one::<String>("SELECT (ROW('Nami', 1)::Pet).name;");
+        assert_eq!(Some("Nami".to_string()), name);
+    }
+
+    #[og_test]
+    fn test_composite_outside_search_path() {
+        Spi::run("SET LOCAL search_path TO pg_catalog");
+        assert_eq!(
+            Some(Pet::type_oid()),
+            Spi::get_one::<pg_sys::Oid>(
+                "SELECT t.oid FROM pg_type t JOIN pg_extension e ON t.typnamespace = e.extnamespace \
+                 WHERE e.extname = 'ogx_tests' AND t.typname = 'pet'"
+            )
+        );
+        assert_eq!(
+            Some(Kennel::type_oid()),
+            Spi::get_one::<pg_sys::Oid>("SELECT 'kennel.kennel'::regtype::oid")
+        );
+        let name = Spi::get_one::<String>("SELECT (kennel.make_kennel('Barkley')).name");
+        assert_eq!(Some("Barkley".to_string()), name);
+    }
+}
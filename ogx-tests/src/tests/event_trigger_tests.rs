@@ -0,0 +1,88 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+
+#[cfg(any(test, feature = "og_test"))]
+#[ogx::og_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as ogx_tests;
+
+    use ogx::prelude::*;
+    use ogx::{OgEventTrigger, OgEventTriggerEvent, OgTriggerError};
+
+    #[og_event_trigger]
+    fn record_ddl(trigger: &OgEventTrigger) -> Result<(), OgTriggerError> {
+        let is_create_table =
+            unsafe { ogx::is_a(trigger.parse_tree().as_ptr(), pg_sys::NodeTag_T_CreateStmt) };
+        Spi::run(&format!(
+            "INSERT INTO tests.ddl_audit VALUES ('{}', '{}', {})",
+            trigger.event()?.to_string(),
+            trigger.tag()?,
+            is_create_table
+        ));
+        Ok(())
+    }
+
+    #[og_event_trigger]
+    fn forbid_drop(trigger: &OgEventTrigger) -> Result<(), OgTriggerError> {
+        if trigger.event()? == OgEventTriggerEvent::SqlDrop {
+            ogx::error!("dropping `{}` is not allowed", trigger.tag()?);
+        }
+        Ok(())
+    }
+
+    fn create_audit_table() {
+        Spi::run("CREATE TABLE tests.ddl_audit (event text, tag text, create_table bool)");
+    }
+
+    #[og_test]
+    fn test_ddl_command_start_and_end() {
+        create_audit_table();
+        Spi::run(
+            "CREATE EVENT TRIGGER record_ddl_start ON ddl_command_start
+                EXECUTE PROCEDURE tests.record_ddl()",
+        );
+        Spi::run(
+            "CREATE EVENT TRIGGER record_ddl_end ON ddl_command_end
+                EXECUTE PROCEDURE tests.record_ddl()",
+        );
+
+        Spi::run("CREATE TABLE tests.event_trigger_pets (id int4)");
+
+        let recorded = Spi::get_one::<String>(
+            "SELECT string_agg(event || ':' || tag || ':' || create_table, ',' ORDER BY event DESC)
+               FROM tests.ddl_audit",
+        )
+        .expect("no ddl was recorded");
+        assert_eq!(
+            recorded,
+            "ddl_command_start:CREATE TABLE:true,ddl_command_end:CREATE TABLE:true"
+        );
+    }
+
+    #[og_test]
+    #[should_panic(expected = "dropping `DROP TABLE` is not allowed")]
+    fn test_sql_drop() {
+        Spi::run("CREATE TABLE tests.event_trigger_doomed (id int4)");
+        Spi::run(
+            "CREATE EVENT TRIGGER forbid_drop ON sql_drop
+                EXECUTE PROCEDURE tests.forbid_drop()",
+        );
+
+        Spi::run("DROP TABLE tests.event_trigger_doomed");
+    }
+
+    #[og_test]
+    fn test_returns_event_trigger() {
+        let rettype = Spi::get_one::<String>(
+            "SELECT prorettype::regtype::text FROM pg_proc WHERE proname = 'record_ddl'",
+        );
+        assert_eq!(rettype.as_deref(), Some("event_trigger"));
+    }
+}
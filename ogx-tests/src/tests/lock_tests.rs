@@ -0,0 +1,108 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+
+#[cfg(any(test, feature = "og_test"))]
+#[ogx::og_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as ogx_tests;
+
+    use ogx::prelude::*;
+    use ogx::{PgAdvisoryLockScope, PgLockGuard, PgLockMode};
+
+    fn count_locks(filter: &str) -> i64 {
+        Spi::get_one::<i64>(&format!(
+            "SELECT count(*) FROM pg_locks WHERE pid = pg_backend_pid() AND {}",
+            filter
+        ))
+        .expect("SPI returned NULL")
+    }
+
+    #[og_test]
+    fn test_lock_mode() {
+        assert!(PgLockMode::AccessShare < PgLockMode::AccessExclusive);
+        assert_eq!(PgLockMode::RowExclusive.as_pg(), pg_sys::RowExclusiveLock as pg_sys::LOCKMODE);
+        assert_eq!(
+            pg_sys::LOCKMODE::from(PgLockMode::AccessExclusive),
+            pg_sys::AccessExclusiveLock as pg_sys::LOCKMODE
+        );
+    }
+
+    #[og_test]
+    fn test_advisory_lock_released_on_drop() {
+        let filter =
+            "locktype = 'advisory' AND objid = 42 AND objsubid = 1 AND mode = 'ExclusiveLock'";
+
+        let guard = PgLockGuard::advisory_exclusive(42i64, PgAdvisoryLockScope::Session);
+        assert_eq!(count_locks(filter), 1);
+        drop(guard);
+        assert_eq!(count_locks(filter), 0);
+    }
+
+    #[og_test]
+    fn test_advisory_lock_shared_pair() {
+        let filter =
+            "locktype = 'advisory' AND classid = 7 AND objid = 8 AND objsubid = 2 AND mode = 'ShareLock'";
+
+        let guard = PgLockGuard::try_advisory_shared((7, 8), PgAdvisoryLockScope::Transaction)
+            .expect("advisory lock not available");
+        assert_eq!(count_locks(filter), 1);
+        drop(guard);
+        assert_eq!(count_locks(filter), 0);
+    }
+
+    #[og_test]
+    fn test_advisory_lock_held() {
+        let filter = "locktype = 'advisory' AND objid = 43 AND objsubid = 1";
+
+        PgLockGuard::advisory_exclusive(43i64, PgAdvisoryLockScope::Transaction).hold();
+        assert_eq!(count_locks(filter), 1);
+        assert_eq!(Spi::get_one::<bool>("SELECT pg_try_advisory_xact_lock(43)"), Some(true));
+    }
+
+    #[og_test]
+    fn test_relation_lock() {
+        Spi::run("CREATE TABLE tests.lock_pets (id int4)");
+        let relid = ogx::catalog::regclass("tests.lock_pets").expect("no relation lock_pets");
+        let filter = format!(
+            "locktype = 'relation' AND relation = {} AND mode = 'ShareRowExclusiveLock'",
+            relid
+        );
+
+        let guard = PgLockGuard::relation(relid, PgLockMode::ShareRowExclusive);
+        assert_eq!(count_locks(&filter), 1);
+        drop(guard);
+        assert_eq!(count_locks(&filter), 0);
+
+        let guard = PgLockGuard::try_relation(relid, PgLockMode::ShareRowExclusive)
+            .expect("relation lock not available");
+        assert_eq!(count_locks(&filter), 1);
+        drop(guard);
+        assert_eq!(count_locks(&filter), 0);
+    }
+
+    #[og_test]
+    fn test_try_relation_lock_missing() {
+        assert!(PgLockGuard::try_relation(pg_sys::InvalidOid, PgLockMode::AccessShare).is_none());
+    }
+
+    #[og_test]
+    fn test_database_object_lock() {
+        let filter = format!(
+            "locktype = 'object' AND classid = {} AND objid = 12345 AND mode = 'ExclusiveLock'",
+            pg_sys::TypeRelationId
+        );
+
+        let guard =
+            PgLockGuard::database_object(pg_sys::TypeRelationId, 12345, 0, PgLockMode::Exclusive);
+        assert_eq!(count_locks(&filter), 1);
+        drop(guard);
+        assert_eq!(count_locks(&filter), 0);
+    }
+}
@@ -11,12 +11,17 @@ mod aggregate_tests;
 mod anyarray_tests;
 mod array_tests;
 mod attributes_tests;
+mod bench_tests;
 mod bytea_tests;
+mod catalog_tests;
+mod cast_tests;
 mod cfg_tests;
+mod composite_type_tests;
 mod datetime_tests;
 mod default_arg_value_tests;
 mod derive_pgtype_lifetimes;
 mod enum_type_tests;
+mod event_trigger_tests;
 mod fcinfo_tests;
 mod guc_tests;
 mod heap_tuple;
@@ -25,12 +30,17 @@ mod inet_tests;
 mod internal_tests;
 mod json_tests;
 mod lifetime_tests;
+mod lock_tests;
 mod log_tests;
 mod memcxt_tests;
 mod name_tests;
+mod notify_tests;
 mod numeric_tests;
 mod og_extern_tests;
 mod pg_try_tests;
+mod reg_tests;
+mod relmodify_tests;
+mod relscan_tests;
 mod ogbox_tests;
 mod og_type_tests;
 mod schema_tests;
@@ -41,6 +51,7 @@ mod trigger_tests;
 mod uuid_tests;
 mod variadic_tests;
 mod xact_callback_tests;
+mod xact_tests;
 mod xid64_tests;
 mod zero_datum_edge_cases;
 
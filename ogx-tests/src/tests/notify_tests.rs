@@ -0,0 +1,140 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+
+use ogx::prelude::*;
+use ogx::{notify, FromDatum, IntoDatum};
+
+extension_sql!(
+    r#"CREATE TABLE notify_worker_listening (channels text[], worker_pid int4);"#,
+    name = "create_notify_worker_listening",
+);
+
+/// A background worker that listens, notifies, and records the channels it then listens on in
+/// `notify_worker_listening`.  Its argument is the oid of the database to connect to
+#[og_guard]
+#[no_mangle]
+pub extern "C" fn notify_test_worker(database: pg_sys::Datum) {
+    unsafe {
+        pg_sys::BackgroundWorkerUnblockSignals();
+        pg_sys::BackgroundWorkerInitializeConnectionByOid(
+            pg_sys::Oid::from_datum(database, false).unwrap(),
+            pg_sys::InvalidOid,
+        );
+    }
+
+    in_transaction(|| notify::listen("worker_channel"));
+    in_transaction(|| {
+        notify::notify("worker_channel", "from the worker");
+        let channels = notify::listening_channels();
+        Spi::execute(|mut client| {
+            client.update(
+                "INSERT INTO notify_worker_listening VALUES ($1, pg_backend_pid())",
+                None,
+                Some(vec![(PgBuiltInOids::TEXTARRAYOID.oid(), channels.into_datum())]),
+            );
+        });
+    });
+}
+
+fn in_transaction<R>(f: impl FnOnce() -> R) -> R {
+    unsafe {
+        pg_sys::StartTransactionCommand();
+        pg_sys::PushActiveSnapshot(pg_sys::GetTransactionSnapshot());
+    }
+    let result = f();
+    unsafe {
+        pg_sys::PopActiveSnapshot();
+        pg_sys::CommitTransactionCommand();
+    }
+    result
+}
+
+#[cfg(any(test, feature = "og_test"))]
+#[ogx::og_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as ogx_tests;
+
+    use ogx::notify;
+    use ogx::prelude::*;
+    use std::os::raw::c_char;
+
+    #[og_test]
+    fn test_notify() {
+        notify::notify("cache_invalidation", "it's a \"payload\"; DROP TABLE pets");
+        notify::notify("cache_invalidation", "");
+    }
+
+    #[og_test]
+    fn test_notify_max_payload() {
+        notify::notify("cache_invalidation", &"x".repeat(notify::MAX_PAYLOAD_LEN));
+    }
+
+    #[og_test]
+    #[should_panic(expected = "payload string too long")]
+    fn test_notify_payload_too_long() {
+        notify::notify("cache_invalidation", &"x".repeat(notify::MAX_PAYLOAD_LEN + 1));
+    }
+
+    #[og_test]
+    #[should_panic(expected = "channel name cannot be empty")]
+    fn test_notify_empty_channel() {
+        notify::notify("", "payload");
+    }
+
+    #[og_test]
+    fn test_listen_and_unlisten() {
+        notify::listen("Cache Invalidation; UNLISTEN *");
+        notify::unlisten("Cache Invalidation; UNLISTEN *");
+        notify::unlisten_all();
+        assert!(notify::listening_channels().is_empty());
+    }
+
+    #[og_test]
+    #[should_panic(expected = "channel name cannot be empty")]
+    fn test_listen_empty_channel() {
+        notify::listen("");
+    }
+
+    #[og_test]
+    fn test_bgworker_listen() {
+        fn set_name(field: &mut [c_char; 64], name: &str) {
+            for (dst, src) in field.iter_mut().zip(name.bytes()) {
+                *dst = src as c_char;
+            }
+        }
+
+        let mut worker = pg_sys::BackgroundWorker::default();
+        set_name(&mut worker.bgw_name, "notify_test_worker");
+        set_name(&mut worker.bgw_library_name, "ogx_tests");
+        set_name(&mut worker.bgw_function_name, "notify_test_worker");
+        worker.bgw_flags =
+            (pg_sys::BGWORKER_SHMEM_ACCESS | pg_sys::BGWORKER_BACKEND_DATABASE_CONNECTION) as i32;
+        worker.bgw_start_time = pg_sys::BgWorkerStartTime_BgWorkerStart_RecoveryFinished;
+        worker.bgw_restart_time = pg_sys::BGW_NEVER_RESTART;
+
+        let channels = unsafe {
+            worker.bgw_main_arg = pg_sys::MyDatabaseId.into();
+            worker.bgw_notify_pid = pg_sys::MyProcPid;
+
+            let mut handle = std::ptr::null_mut();
+            assert!(
+                pg_sys::RegisterDynamicBackgroundWorker(&mut worker, &mut handle),
+                "no background worker slots are free"
+            );
+            assert_eq!(
+                pg_sys::WaitForBackgroundWorkerShutdown(handle),
+                pg_sys::BgwHandleStatus_BGWH_STOPPED
+            );
+
+            Spi::get_one::<Vec<String>>("SELECT channels FROM notify_worker_listening")
+        };
+        assert_eq!(channels, Some(vec![String::from("worker_channel")]));
+    }
+}
@@ -8,7 +8,9 @@ Use of this source code is governed by the MIT license that can be found in the
 */
 use ogx::cstr_core::CStr;
 use ogx::prelude::*;
-use ogx::{InOutFuncs, PgVarlena, PgVarlenaInOutFuncs, StringInfo};
+use ogx::{
+    InOutFuncs, PgVarlena, PgVarlenaInOutFuncs, SendRecvFuncs, StringInfo, TypmodInOutFuncs,
+};
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 
@@ -71,6 +73,93 @@ fn fn_takes_option(input: Option<CustomTextFormatSerializedType>) -> String {
     })
 }
 
+#[derive(Serialize, Deserialize, OgType)]
+#[inoutfuncs]
+#[sendrecvfuncs]
+pub struct BinaryFormatType {
+    a: i32,
+    b: i64,
+}
+
+impl InOutFuncs for BinaryFormatType {
+    fn input(input: &CStr) -> Self {
+        let mut iter = input.to_str().unwrap().split(',');
+        let (a, b) = (iter.next(), iter.next());
+
+        BinaryFormatType {
+            a: i32::from_str(a.unwrap()).expect("a is not a valid i32"),
+            b: i64::from_str(b.unwrap()).expect("b is not a valid i64"),
+        }
+    }
+
+    fn output(&self, buffer: &mut StringInfo) {
+        buffer.push_str(&format!("{},{}", self.a, self.b))
+    }
+}
+
+impl SendRecvFuncs for BinaryFormatType {
+    fn send(&self) -> Vec<u8> {
+        let mut buffer = self.a.to_be_bytes().to_vec();
+        buffer.extend_from_slice(&self.b.to_be_bytes());
+        buffer
+    }
+
+    fn recv(buffer: &[u8]) -> Self {
+        let (a, b) = buffer.split_at(4);
+        BinaryFormatType {
+            a: i32::from_be_bytes(a.try_into().expect("a is not a valid i32")),
+            b: i64::from_be_bytes(b.try_into().expect("b is not a valid i64")),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, OgType)]
+#[inoutfuncs]
+#[typmod_inoutfuncs]
+pub struct BoundedText {
+    value: String,
+}
+
+impl InOutFuncs for BoundedText {
+    fn input(input: &CStr) -> Self {
+        BoundedText { value: input.to_str().unwrap().to_string() }
+    }
+
+    fn output(&self, buffer: &mut StringInfo) {
+        buffer.push_str(&self.value)
+    }
+}
+
+impl TypmodInOutFuncs for BoundedText {
+    fn typmod_in(modifiers: &[&str]) -> i32 {
+        match modifiers {
+            [len] => i32::from_str(len).expect("length must be a valid i32"),
+            _ => ogx::error!("BoundedText accepts exactly one modifier"),
+        }
+    }
+
+    fn typmod_coerce(self, typmod: i32, explicit: bool) -> Self {
+        let len = typmod as usize;
+        if typmod < 0 || self.value.chars().count() <= len {
+            self
+        } else if explicit {
+            BoundedText { value: self.value.chars().take(len).collect() }
+        } else {
+            ogx::error!("value too long for type BoundedText({})", typmod)
+        }
+    }
+}
+
+#[og_extern(immutable)]
+fn bounded_text_value(input: BoundedText) -> String {
+    input.value
+}
+
+#[og_extern(immutable)]
+fn bounded_text_typmod(_input: BoundedText, fcinfo: pg_sys::FunctionCallInfo) -> i32 {
+    unsafe { ogx::pg_getarg_typmod(fcinfo, 0) }
+}
+
 #[derive(Serialize, Deserialize, OgType)]
 pub struct JsonType {
     a: f32,
@@ -88,7 +177,7 @@ mod tests {
         CustomTextFormatSerializedType, JsonType, VarlenaType,
     };
     use ogx::prelude::*;
-    use ogx::PgVarlena;
+    use ogx::{Internal, PgVarlena, StringInfo};
 
     #[og_test]
     fn test_mytype() {
@@ -124,6 +213,70 @@ mod tests {
         assert_eq!(result.c, 3);
     }
 
+    #[og_test]
+    fn test_binaryformattype_send() {
+        let result =
+            Spi::get_one::<Vec<u8>>("SELECT binaryformattype_send('1,2'::BinaryFormatType)")
+                .expect("SPI returned NULL");
+        assert_eq!(result, vec![0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 2]);
+    }
+
+    #[og_test]
+    fn test_binaryformattype_recv_roundtrip() {
+        let sent = Spi::get_one::<Vec<u8>>(
+            "SELECT binaryformattype_send('-7,9000000000'::BinaryFormatType)",
+        )
+        .expect("SPI returned NULL");
+
+        // hand `_recv` the same `StringInfo` openGauss builds when it reads a binary value
+        let mut buffer = StringInfo::new();
+        buffer.push_bytes(&sent);
+        let buffer = buffer.into_pg();
+        let received = super::binaryformattype_recv(Internal::from(Some(buffer.into())));
+        assert_eq!(received.a, -7);
+        assert_eq!(received.b, 9000000000);
+        // the whole message was consumed
+        assert_eq!(unsafe { (*buffer).cursor }, unsafe { (*buffer).len });
+    }
+
+    #[og_test]
+    fn test_binaryformattype_has_sendrecv() {
+        let result = Spi::get_one::<bool>(
+            "SELECT typsend::oid <> 0 AND typreceive::oid <> 0 FROM pg_type WHERE typname = 'binaryformattype'",
+        );
+        assert_eq!(Some(true), result);
+    }
+
+    #[og_test]
+    fn test_boundedtext_typmod_out() {
+        let result = Spi::get_one::<String>("SELECT format_type('BoundedText'::regtype, 3)");
+        assert_eq!(Some(String::from("boundedtext(3)")), result);
+    }
+
+    #[og_test]
+    fn test_boundedtext_arg_typmod() {
+        let result =
+            Spi::get_one::<i32>("SELECT bounded_text_typmod('hello'::BoundedText::BoundedText(8))");
+        assert_eq!(Some(8), result);
+        let result = Spi::get_one::<i32>("SELECT bounded_text_typmod('hello'::BoundedText)");
+        assert_eq!(Some(-1), result);
+    }
+
+    #[og_test]
+    fn test_boundedtext_explicit_coercion() {
+        let result = Spi::get_one::<String>(
+            "SELECT bounded_text_value('hello'::BoundedText::BoundedText(3))",
+        );
+        assert_eq!(Some(String::from("hel")), result);
+    }
+
+    #[og_test]
+    #[should_panic(expected = "value too long for type BoundedText(3)")]
+    fn test_boundedtext_implicit_coercion() {
+        Spi::run("CREATE TEMP TABLE bounded (t BoundedText(3))");
+        Spi::run("INSERT INTO bounded VALUES ('hello'::BoundedText)");
+    }
+
     #[og_test]
     fn test_jsontype() {
         let result = Spi::get_one::<JsonType>(r#"SELECT '{"a": 1.0, "b": 2.0, "c": 3}'::JsonType"#)
@@ -0,0 +1,78 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+
+use ogx::prelude::*;
+use ogx::{RegClass, RegNamespace, RegOperator, RegProc, RegType};
+
+#[og_extern]
+fn relation_natts(relation: RegClass) -> i32 {
+    let relation = relation.open(pg_sys::AccessShareLock as pg_sys::LOCKMODE);
+    let natts = relation.tuple_desc().len();
+    natts as i32
+}
+
+#[og_extern]
+fn regtype_of_name(name: &str) -> Option<RegType> {
+    RegType::lookup(name)
+}
+
+#[cfg(any(test, feature = "og_test"))]
+#[ogx::og_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as ogx_tests;
+
+    use super::*;
+
+    #[og_test]
+    fn test_regclass_argument() {
+        Spi::run("CREATE TABLE reg_pets (id int4, name text, age int4)");
+        let natts =
+            Spi::get_one::<i32>("SELECT relation_natts('reg_pets')").expect("SPI returned NULL");
+        assert_eq!(natts, 3);
+    }
+
+    #[og_test]
+    fn test_regtype_return() {
+        let oid = Spi::get_one::<pg_sys::Oid>("SELECT regtype_of_name('integer')::oid")
+            .expect("SPI returned NULL");
+        assert_eq!(oid, pg_sys::INT4OID);
+        assert!(Spi::get_one::<RegType>("SELECT regtype_of_name('no_such_type')").is_none());
+    }
+
+    #[og_test]
+    fn test_from_datum() {
+        let regtype = Spi::get_one::<RegType>("SELECT 'text'::regtype").expect("SPI returned NULL");
+        assert_eq!(regtype.oid(), pg_sys::TEXTOID);
+        assert_eq!(regtype.entry().expect("no pg_type row").typlen, -1);
+    }
+
+    #[og_test]
+    fn test_display() {
+        Spi::run("CREATE TABLE \"Reg Pets\" (id int4)");
+        let relation = RegClass::lookup("\"Reg Pets\"").expect("no relation named Reg Pets");
+        assert_eq!(relation.to_string(), "\"Reg Pets\"");
+        assert_eq!(relation.entry().expect("no pg_class row").relnatts, 1);
+
+        assert_eq!(RegType::from_oid(pg_sys::INT4OID).to_string(), "integer");
+        assert_eq!(RegNamespace::lookup("pg_catalog").unwrap().to_string(), "pg_catalog");
+        assert_eq!(RegProc::lookup("pg_catalog.lower").unwrap().to_string(), "lower");
+        assert_eq!(
+            RegOperator::lookup("+(integer,integer)").unwrap().to_string(),
+            "+(integer,integer)"
+        );
+    }
+
+    #[og_test]
+    fn test_lookup_missing() {
+        assert!(RegClass::lookup("no_such_relation").is_none());
+        assert!(RegProc::lookup("no_such_function").is_none());
+        assert!(RegOperator::lookup("+(text,text)").is_none());
+    }
+}
@@ -0,0 +1,110 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+
+#[cfg(any(test, feature = "og_test"))]
+#[ogx::og_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as ogx_tests;
+
+    use ogx::heap_tuple::PgHeapTuple;
+    use ogx::prelude::*;
+    use ogx::{AllocatedByRust, IntoDatum, PgRelation};
+
+    fn create_pets() -> PgRelation {
+        Spi::run("CREATE TABLE pets (id int4 PRIMARY KEY, name text NOT NULL CHECK (name <> ''))");
+        Spi::run("INSERT INTO pets VALUES (1, 'Nami')");
+        let oid = Spi::get_one::<pg_sys::Oid>("SELECT 'pets'::regclass::oid").unwrap();
+        PgRelation::with_lock(oid, pg_sys::RowExclusiveLock as pg_sys::LOCKMODE)
+    }
+
+    fn pet<'a>(relation: &'a PgRelation, id: i32, name: &str) -> PgHeapTuple<'a, AllocatedByRust> {
+        PgHeapTuple::from_datums(relation.tuple_desc(), vec![id.into_datum(), name.into_datum()])
+            .unwrap()
+    }
+
+    #[og_test]
+    fn test_insert() {
+        let relation = create_pets();
+        relation.insert(pet(&relation, 2, "Brandy"));
+        let result = Spi::get_one::<String>("SELECT name FROM pets WHERE id = 2");
+        assert_eq!(Some(String::from("Brandy")), result);
+
+        // and it went into the primary key's index too
+        Spi::run("SET enable_seqscan TO off");
+        let result = Spi::get_one::<i64>("SELECT count(*) FROM pets WHERE id = 2");
+        assert_eq!(Some(1), result);
+    }
+
+    #[og_test]
+    fn test_writer_insert_many() {
+        let relation = create_pets();
+        let mut writer = relation.writer();
+        for (id, name) in [(2, "Brandy"), (3, "Kiki"), (4, "Jiji")] {
+            writer.insert(pet(&relation, id, name));
+        }
+        drop(writer);
+
+        let result = Spi::get_one::<i64>("SELECT count(*) FROM pets");
+        assert_eq!(Some(4), result);
+    }
+
+    #[og_test]
+    #[should_panic(expected = "it has triggers or foreign keys")]
+    fn test_insert_foreign_key() {
+        let _pets = create_pets();
+        Spi::run("CREATE TABLE owners (name text, pet_id int4 REFERENCES pets (id))");
+        let oid = Spi::get_one::<pg_sys::Oid>("SELECT 'owners'::regclass::oid").unwrap();
+        let owners = PgRelation::with_lock(oid, pg_sys::RowExclusiveLock as pg_sys::LOCKMODE);
+        let tuple = PgHeapTuple::from_datums(
+            owners.tuple_desc(),
+            vec!["Kiki".into_datum(), 42.into_datum()],
+        )
+        .unwrap();
+        owners.insert(tuple);
+    }
+
+    #[og_test]
+    #[should_panic(expected = "duplicate key value violates unique constraint")]
+    fn test_insert_unique_violation() {
+        let relation = create_pets();
+        relation.insert(pet(&relation, 1, "Brandy"));
+    }
+
+    #[og_test]
+    #[should_panic(expected = "violates check constraint")]
+    fn test_insert_check_violation() {
+        let relation = create_pets();
+        relation.insert(pet(&relation, 2, ""));
+    }
+
+    #[og_test]
+    #[should_panic(expected = "Ustore relations are not supported")]
+    fn test_insert_ustore() {
+        Spi::run("CREATE TABLE ustore_pets (id int4, name text) WITH (storage_type = ustore)");
+        let oid = Spi::get_one::<pg_sys::Oid>("SELECT 'ustore_pets'::regclass::oid").unwrap();
+        let relation = PgRelation::with_lock(oid, pg_sys::RowExclusiveLock as pg_sys::LOCKMODE);
+        relation.insert(pet(&relation, 1, "Nami"));
+    }
+
+    #[og_test]
+    fn test_update_and_delete() {
+        let relation = create_pets();
+        let ctid =
+            Spi::get_one::<pg_sys::ItemPointerData>("SELECT ctid FROM pets WHERE id = 1").unwrap();
+
+        let ctid = relation.update(ctid, pet(&relation, 1, "Sally"));
+        let result = Spi::get_one::<String>("SELECT name FROM pets WHERE id = 1");
+        assert_eq!(Some(String::from("Sally")), result);
+
+        relation.delete(ctid);
+        let result = Spi::get_one::<i64>("SELECT count(*) FROM pets");
+        assert_eq!(Some(0), result);
+    }
+}
@@ -0,0 +1,108 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+
+#[cfg(any(test, feature = "og_test"))]
+#[ogx::og_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as ogx_tests;
+
+    use ogx::prelude::*;
+    use ogx::{IndexStrategy, PgRelation, PgScanKey, PgSnapshot};
+
+    fn create_pets() -> PgRelation {
+        Spi::run("CREATE TABLE pets (id int4, name text)");
+        Spi::run("INSERT INTO pets VALUES (1, 'Nami'), (2, 'Brandy'), (3, 'Sally')");
+        PgRelation::open_with_name_and_share_lock("pets").expect("no relation named pets")
+    }
+
+    #[og_test]
+    fn test_scan() {
+        let relation = create_pets();
+        let mut names = relation
+            .scan(PgSnapshot::Transaction)
+            .map(|tuple| tuple.get_by_name::<String>("name").unwrap().unwrap())
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, vec!["Brandy", "Nami", "Sally"]);
+    }
+
+    #[og_test]
+    fn test_scan_with_keys() {
+        let relation = create_pets();
+        let ids = relation
+            .scan_with_keys(PgSnapshot::Transaction, &[PgScanKey::equals(2, "Sally")])
+            .map(|tuple| tuple.get_by_name::<i32>("id").unwrap().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![3]);
+    }
+
+    #[og_test]
+    fn test_fetch() {
+        let relation = create_pets();
+        let ctid = Spi::get_one::<pg_sys::ItemPointerData>("SELECT ctid FROM pets WHERE id = 2")
+            .expect("SPI returned NULL");
+        let tuple = relation.fetch(ctid, PgSnapshot::Transaction).expect("no tuple at ctid");
+        assert_eq!(tuple.get_by_name::<String>("name").unwrap(), Some(String::from("Brandy")));
+    }
+
+    #[og_test]
+    fn test_index_scan() {
+        let relation = create_pets();
+        Spi::run("CREATE INDEX pets_id_idx ON pets (id)");
+        let index = PgRelation::open_with_name_and_share_lock("pets_id_idx").unwrap();
+        let names = relation
+            .index_scan(&index, PgSnapshot::Transaction)
+            .key(1, IndexStrategy::GreaterEqual, 2)
+            .backward()
+            .heap_tuples()
+            .map(|tuple| tuple.get_by_name::<String>("name").unwrap().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["Sally", "Brandy"]);
+    }
+
+    #[og_test]
+    fn test_index_scan_tids() {
+        let relation = create_pets();
+        Spi::run("CREATE INDEX pets_name_idx ON pets (name)");
+        let index = PgRelation::open_with_name_and_share_lock("pets_name_idx").unwrap();
+        let tids = relation
+            .index_scan(&index, PgSnapshot::Transaction)
+            .key(1, IndexStrategy::Equal, "Nami")
+            .tids()
+            .collect::<Vec<_>>();
+        assert_eq!(tids.len(), 1);
+
+        let tuple = relation.fetch(tids[0], PgSnapshot::Transaction).expect("no tuple at tid");
+        assert_eq!(tuple.get_by_name::<i32>("id").unwrap(), Some(1));
+    }
+
+    #[og_test]
+    #[should_panic(expected = "it has no heap")]
+    fn test_scan_view() {
+        Spi::run("CREATE VIEW pet_view AS SELECT 1");
+        let relation = PgRelation::open_with_name_and_share_lock("pet_view").unwrap();
+        relation.scan(PgSnapshot::Transaction).for_each(drop);
+    }
+
+    #[og_test]
+    #[should_panic(expected = "Ustore relations are not supported")]
+    fn test_scan_ustore() {
+        Spi::run("CREATE TABLE ustore_pets (id int4) WITH (storage_type = ustore)");
+        let relation = PgRelation::open_with_name_and_share_lock("ustore_pets").unwrap();
+        assert!(relation.is_ustore());
+        relation.scan(PgSnapshot::Transaction).for_each(drop);
+    }
+
+    #[og_test]
+    fn test_astore_is_not_ustore() {
+        let relation = create_pets();
+        assert!(!relation.is_ustore());
+    }
+}
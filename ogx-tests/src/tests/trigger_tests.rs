@@ -596,4 +596,65 @@ mod tests {
             .expect("SQL select failed");
         assert_eq!(retval, "Fox");
     }
+
+    #[og_trigger]
+    fn audit_species_changes(trigger: &ogx::OgTrigger) -> Result<(), TriggerError> {
+        let old = trigger.old_transition_table();
+        let new = trigger.new_transition_table();
+
+        for (which, table) in [("old", old), ("new", new)] {
+            for tuple in table.into_iter().flatten() {
+                let species: String = tuple.get_by_name("species")?.unwrap();
+                Spi::run(&format!(
+                    "INSERT INTO tests.species_audit VALUES ('{}', '{}')",
+                    which, species
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn create_species_audit(operation: &str, referencing: &str) {
+        Spi::run("CREATE TABLE tests.has_transition_tables (species TEXT)");
+        Spi::run("INSERT INTO tests.has_transition_tables VALUES ('Fox'), ('Bear')");
+        Spi::run("CREATE TABLE tests.species_audit (which TEXT, species TEXT)");
+        Spi::run(&format!(
+            "CREATE TRIGGER audit_species
+                AFTER {} ON tests.has_transition_tables
+                REFERENCING {}
+                FOR EACH STATEMENT
+                EXECUTE PROCEDURE tests.audit_species_changes()",
+            operation, referencing
+        ));
+    }
+
+    fn species_audit() -> String {
+        Spi::get_one::<String>(
+            "SELECT string_agg(which || ':' || species, ',' ORDER BY which DESC, species)
+               FROM tests.species_audit",
+        )
+        .expect("nothing was audited")
+    }
+
+    #[og_test]
+    fn after_insert_new_transition_table() {
+        create_species_audit("INSERT", "NEW TABLE AS inserted");
+        Spi::run("INSERT INTO tests.has_transition_tables VALUES ('Cat'), ('Dog')");
+        assert_eq!(species_audit(), "new:Cat,new:Dog");
+    }
+
+    #[og_test]
+    fn after_update_old_and_new_transition_tables() {
+        create_species_audit("UPDATE", "OLD TABLE AS before NEW TABLE AS after");
+        Spi::run("UPDATE tests.has_transition_tables SET species = upper(species)");
+        assert_eq!(species_audit(), "old:Bear,old:Fox,new:BEAR,new:FOX");
+    }
+
+    #[og_test]
+    fn after_delete_old_transition_table() {
+        create_species_audit("DELETE", "OLD TABLE AS deleted");
+        Spi::run("DELETE FROM tests.has_transition_tables WHERE species = 'Fox'");
+        assert_eq!(species_audit(), "old:Fox");
+    }
 }
@@ -0,0 +1,83 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+
+#[cfg(any(test, feature = "og_test"))]
+#[ogx::og_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as ogx_tests;
+
+    use ogx::prelude::*;
+    use ogx::xact::{subtransaction, SubtransactionError};
+
+    fn count_rows() -> i64 {
+        Spi::get_one::<i64>("SELECT count(*) FROM tests.xact_pets").expect("SPI returned NULL")
+    }
+
+    #[og_test]
+    fn test_subtransaction_commits_ok() {
+        Spi::run("CREATE TABLE tests.xact_pets (id int4)");
+
+        let result = subtransaction(|| {
+            Spi::run("INSERT INTO tests.xact_pets VALUES (1)");
+            Ok::<_, ()>(42)
+        });
+        assert_eq!(result, Ok(42));
+        assert_eq!(count_rows(), 1);
+    }
+
+    #[og_test]
+    fn test_subtransaction_rolls_back_err() {
+        Spi::run("CREATE TABLE tests.xact_pets (id int4)");
+
+        let result = subtransaction(|| {
+            Spi::run("INSERT INTO tests.xact_pets VALUES (1)");
+            Err::<(), _>("changed my mind")
+        });
+        assert_eq!(result, Err(SubtransactionError::Returned("changed my mind")));
+        assert_eq!(count_rows(), 0);
+    }
+
+    #[og_test]
+    fn test_subtransaction_rolls_back_panic() {
+        Spi::run("CREATE TABLE tests.xact_pets (id int4)");
+
+        let result = subtransaction(|| {
+            Spi::run("INSERT INTO tests.xact_pets VALUES (1)");
+            ogx::error!("giving up on {}", 1);
+            #[allow(unreachable_code)]
+            Ok::<(), ()>(())
+        });
+        assert_eq!(result, Err(SubtransactionError::Raised("giving up on 1".to_string())));
+        assert_eq!(count_rows(), 0);
+    }
+
+    #[og_test]
+    fn test_subtransaction_batch() {
+        Spi::run("CREATE TABLE tests.xact_pets (id int4 PRIMARY KEY)");
+
+        let mut failed = Vec::new();
+        for id in [1, 2, 2, 3, 1] {
+            let result = subtransaction(|| {
+                Spi::run(&format!("INSERT INTO tests.xact_pets VALUES ({})", id));
+                Ok::<_, ()>(())
+            });
+            if let Err(SubtransactionError::Raised(message)) = result {
+                assert!(message.contains("duplicate key"), "unexpected error: {}", message);
+                failed.push(id);
+            }
+        }
+
+        assert_eq!(failed, vec![2, 1]);
+        assert_eq!(count_rows(), 3);
+        // the outer transaction is still usable
+        Spi::run("INSERT INTO tests.xact_pets VALUES (4)");
+        assert_eq!(count_rows(), 4);
+    }
+}
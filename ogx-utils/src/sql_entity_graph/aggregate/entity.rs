@@ -180,6 +180,48 @@ impl SqlGraphIdentifier for OgAggregateEntity {
     }
 }
 
+impl OgAggregateEntity {
+    /// Its argument types, as `DROP AGGREGATE` identifies the aggregate by: the direct arguments
+    /// of an ordered-set aggregate come first, followed by `ORDER BY` and the aggregated ones
+    pub fn sql_arguments(&self, context: &OgxSql) -> eyre::Result<String> {
+        let self_index = context.aggregates[self];
+        let sql_types = |args: &[AggregateTypeEntity]| -> eyre::Result<String> {
+            let mut sql_types = Vec::new();
+            for arg in args {
+                let sql_type = match arg.used_ty.metadata.argument_sql {
+                    Ok(ref mapping) => arg.used_ty.sql_type(mapping, context)?,
+                    Err(err) => context
+                        .source_only_to_sql_type(arg.used_ty.ty_source)
+                        .ok_or(err)
+                        .wrap_err("While mapping argument")?,
+                };
+                let schema_prefix = context
+                    .graph
+                    .neighbors_undirected(self_index)
+                    .find(|neighbor| match &context.graph[*neighbor] {
+                        SqlGraphEntity::Type(ty) => ty.id_matches(&arg.used_ty.ty_id),
+                        SqlGraphEntity::Enum(en) => en.id_matches(&arg.used_ty.ty_id),
+                        SqlGraphEntity::BuiltinType(defined) => defined == arg.used_ty.full_path,
+                        _ => false,
+                    })
+                    .map(|index| context.schema_prefix_for(&index))
+                    .unwrap_or_default();
+                let variadic = if arg.used_ty.variadic { "VARIADIC " } else { "" };
+                sql_types.push(format!("{}{}{}", variadic, schema_prefix, sql_type));
+            }
+            Ok(sql_types.join(", "))
+        };
+
+        let args = sql_types(&self.args)?;
+        Ok(match &self.direct_args {
+            Some(direct_args) if self.ordered_set => {
+                format!("{} ORDER BY {}", sql_types(direct_args)?, args)
+            }
+            _ => args,
+        })
+    }
+}
+
 impl ToSql for OgAggregateEntity {
     #[tracing::instrument(level = "debug", err, skip(self, context), fields(identifier = %self.rust_identifier()))]
     fn to_sql(&self, context: &OgxSql) -> eyre::Result<String> {
@@ -349,7 +349,7 @@ impl OgAggregate {
                     #[allow(non_snake_case, clippy::too_many_arguments)]
                     #og_extern_attr
                     fn #fn_name(this: #type_state_without_self, fcinfo: ogx::pg_sys::FunctionCallInfo) -> #type_finalize {
-                        <#target_path as ::Aggregate>::in_memory_context(
+                        <#target_path as ogx::Aggregate>::in_memory_context(
                             fcinfo,
                             move |_context| <#target_path as ogx::Aggregate>::finalize(this, (), fcinfo)
                         )
@@ -986,6 +986,36 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn agg_finalize_without_direct_args() -> Result<()> {
+        let tokens: ItemImpl = parse_quote! {
+            #[pg_aggregate]
+            impl Aggregate for DemoAgg {
+                type State = PgVarlena<Self>;
+                type Args = i32;
+                type Finalize = i64;
+                const NAME: &'static str = "DEMO";
+
+                fn state(current: Self::State, v: Self::Args) -> Self::State {
+                    todo!()
+                }
+
+                fn finalize(current: Self::State) -> Self::Finalize {
+                    todo!()
+                }
+            }
+        };
+        let agg = OgAggregate::new(tokens)?;
+        assert_eq!(agg.og_externs.len(), 2);
+        let extern_fn = &agg.og_externs[1];
+        assert_eq!(extern_fn.sig.ident.to_string(), "demo_agg_finalize");
+        // Every call in its body has to go through `ogx::Aggregate`, not a crate named `Aggregate`
+        let body = quote::quote! { #extern_fn }.to_string();
+        assert!(!body.contains("as :: Aggregate"), "{}", body);
+        assert_eq!(body.matches("as ogx :: Aggregate").count(), 2, "{}", body);
+        Ok(())
+    }
+
     #[test]
     fn agg_missing_required() -> Result<()> {
         // This is not valid as it is missing required types/consts.
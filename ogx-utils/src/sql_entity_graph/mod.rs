@@ -24,12 +24,14 @@ pub(crate) mod og_trigger;
 pub(crate) mod ogx_attribute;
 pub(crate) mod ogx_sql;
 pub(crate) mod positioning_ref;
+pub(crate) mod og_composite;
 pub(crate) mod og_enum;
 pub(crate) mod og_hash;
 pub(crate) mod og_ord;
 pub(crate) mod og_type;
 pub(crate) mod schema;
 pub(crate) mod to_sql;
+pub(crate) mod upgrade;
 pub(crate) mod used_type;
 
 pub use aggregate::entity::{AggregateTypeEntity, OgAggregateEntity};
@@ -41,15 +43,17 @@ pub use extension_sql::entity::{ExtensionSqlEntity, SqlDeclaredEntity};
 pub use extension_sql::{ExtensionSql, ExtensionSqlFile, SqlDeclared};
 pub use mapping::{RustSourceOnlySqlMapping, RustSqlMapping};
 pub use og_extern::entity::{
-    OgExternArgumentEntity, OgExternEntity, OgExternReturnEntity, OgExternReturnEntityIteratedItem,
-    OgOperatorEntity,
+    OgCastEntity, OgExternArgumentEntity, OgExternEntity, OgExternReturnEntity,
+    OgExternReturnEntityIteratedItem, OgOperatorEntity,
 };
-pub use og_extern::{NameMacro, OgExtern, OgExternArgument, OgOperator};
+pub use og_extern::{NameMacro, OgCast, OgExtern, OgExternArgument, OgOperator};
 pub use og_trigger::attribute::OgTriggerAttribute;
 pub use og_trigger::entity::OgTriggerEntity;
 pub use og_trigger::OgTrigger;
 pub use ogx_sql::{OgxSql, RustToSqlMapping};
 pub use positioning_ref::PositioningRef;
+pub use og_composite::entity::{OgCompositeEntity, OgCompositeFieldEntity};
+pub use og_composite::OgComposite;
 pub use og_enum::entity::OgEnumEntity;
 pub use og_enum::OgEnum;
 pub use og_hash::entity::OgHashEntity;
@@ -62,6 +66,7 @@ pub use schema::entity::SchemaEntity;
 pub use schema::Schema;
 pub use to_sql::entity::ToSqlConfigEntity;
 pub use to_sql::{ToSql, ToSqlConfig};
+pub use upgrade::OgxSqlUpgrade;
 pub use used_type::{UsedType, UsedTypeEntity};
 
 pub use crate::ExternArgs;
@@ -96,6 +101,7 @@ pub enum SqlGraphEntity {
     Type(OgTypeEntity),
     BuiltinType(String),
     Enum(OgEnumEntity),
+    Composite(OgCompositeEntity),
     Ord(OgOrdEntity),
     Hash(OgHashEntity),
     Aggregate(OgAggregateEntity),
@@ -129,6 +135,7 @@ impl SqlGraphIdentifier for SqlGraphEntity {
             SqlGraphEntity::Type(item) => item.dot_identifier(),
             SqlGraphEntity::BuiltinType(item) => format!("preexisting type {}", item),
             SqlGraphEntity::Enum(item) => item.dot_identifier(),
+            SqlGraphEntity::Composite(item) => item.dot_identifier(),
             SqlGraphEntity::Ord(item) => item.dot_identifier(),
             SqlGraphEntity::Hash(item) => item.dot_identifier(),
             SqlGraphEntity::Aggregate(item) => item.dot_identifier(),
@@ -145,6 +152,7 @@ impl SqlGraphIdentifier for SqlGraphEntity {
             SqlGraphEntity::Type(item) => item.rust_identifier(),
             SqlGraphEntity::BuiltinType(item) => item.to_string(),
             SqlGraphEntity::Enum(item) => item.rust_identifier(),
+            SqlGraphEntity::Composite(item) => item.rust_identifier(),
             SqlGraphEntity::Ord(item) => item.rust_identifier(),
             SqlGraphEntity::Hash(item) => item.rust_identifier(),
             SqlGraphEntity::Aggregate(item) => item.rust_identifier(),
@@ -161,6 +169,7 @@ impl SqlGraphIdentifier for SqlGraphEntity {
             SqlGraphEntity::Type(item) => item.file(),
             SqlGraphEntity::BuiltinType(_item) => None,
             SqlGraphEntity::Enum(item) => item.file(),
+            SqlGraphEntity::Composite(item) => item.file(),
             SqlGraphEntity::Ord(item) => item.file(),
             SqlGraphEntity::Hash(item) => item.file(),
             SqlGraphEntity::Aggregate(item) => item.file(),
@@ -177,6 +186,7 @@ impl SqlGraphIdentifier for SqlGraphEntity {
             SqlGraphEntity::Type(item) => item.line(),
             SqlGraphEntity::BuiltinType(_item) => None,
             SqlGraphEntity::Enum(item) => item.line(),
+            SqlGraphEntity::Composite(item) => item.line(),
             SqlGraphEntity::Ord(item) => item.line(),
             SqlGraphEntity::Hash(item) => item.line(),
             SqlGraphEntity::Aggregate(item) => item.line(),
@@ -205,7 +215,7 @@ impl ToSql for SqlGraphEntity {
                 if context.graph.neighbors_undirected(context.externs.get(item).unwrap().clone()).any(|neighbor| {
                     let neighbor_item = &context.graph[neighbor];
                     match neighbor_item {
-                        SqlGraphEntity::Type(OgTypeEntity { in_fn, in_fn_module_path, out_fn, out_fn_module_path, .. }) => {
+                        SqlGraphEntity::Type(ty @ OgTypeEntity { in_fn, in_fn_module_path, out_fn, out_fn_module_path, .. }) => {
                             let is_in_fn = item.full_path.starts_with(in_fn_module_path) && item.full_path.ends_with(in_fn);
                             if is_in_fn {
                                 tracing::trace!(r#type = %neighbor_item.dot_identifier(), "Skipping, is an in_fn.");
@@ -214,7 +224,19 @@ impl ToSql for SqlGraphEntity {
                             if is_out_fn {
                                 tracing::trace!(r#type = %neighbor_item.dot_identifier(), "Skipping, is an out_fn.");
                             }
-                            is_in_fn || is_out_fn
+                            let is_send_or_receive_fn = ty.send_receive_fn_paths().map_or(false, |(send_fn_path, receive_fn_path)| {
+                                item.full_path == send_fn_path || item.full_path == receive_fn_path
+                            });
+                            if is_send_or_receive_fn {
+                                tracing::trace!(r#type = %neighbor_item.dot_identifier(), "Skipping, is a send_fn or receive_fn.");
+                            }
+                            let is_typmod_fn = ty.typmod_fn_paths().map_or(false, |(typmod_in_fn_path, typmod_out_fn_path, typmod_coerce_fn_path)| {
+                                item.full_path == typmod_in_fn_path || item.full_path == typmod_out_fn_path || item.full_path == typmod_coerce_fn_path
+                            });
+                            if is_typmod_fn {
+                                tracing::trace!(r#type = %neighbor_item.dot_identifier(), "Skipping, is a typmod function.");
+                            }
+                            is_in_fn || is_out_fn || is_send_or_receive_fn || is_typmod_fn
                         },
                         _ => false,
                     }
@@ -231,6 +253,9 @@ impl ToSql for SqlGraphEntity {
             SqlGraphEntity::Enum(item) => {
                 item.to_sql_config.to_sql(self, context).unwrap_or_else(|| item.to_sql(context))
             }
+            SqlGraphEntity::Composite(item) => {
+                item.to_sql_config.to_sql(self, context).unwrap_or_else(|| item.to_sql(context))
+            }
             SqlGraphEntity::Ord(item) => {
                 item.to_sql_config.to_sql(self, context).unwrap_or_else(|| item.to_sql(context))
             }
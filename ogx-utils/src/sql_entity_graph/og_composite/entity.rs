@@ -0,0 +1,182 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+/*!
+
+`#[derive(OgComposite)]` related entities for Rust to SQL translation
+
+> Like all of the [`sql_entity_graph`][crate::sql_entity_graph] APIs, this is considered **internal**
+to the `ogx` framework and very subject to change between versions. While you may use this, please do it with caution.
+
+*/
+use crate::sql_entity_graph::mapping::RustSqlMapping;
+use crate::sql_entity_graph::metadata::SqlMapping;
+use crate::sql_entity_graph::ogx_sql::OgxSql;
+use crate::sql_entity_graph::to_sql::entity::ToSqlConfigEntity;
+use crate::sql_entity_graph::to_sql::ToSql;
+use crate::sql_entity_graph::{SqlGraphEntity, SqlGraphIdentifier, UsedTypeEntity};
+
+use eyre::{eyre, WrapErr};
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+
+/// The output of a [`OgComposite`](crate::sql_entity_graph::og_composite::OgComposite) from `quote::ToTokens::to_tokens`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OgCompositeEntity {
+    pub name: &'static str,
+    pub file: &'static str,
+    pub line: u32,
+    pub full_path: &'static str,
+    pub module_path: &'static str,
+    pub mappings: std::collections::HashSet<RustSqlMapping>,
+    pub fields: Vec<OgCompositeFieldEntity>,
+    pub to_sql_config: ToSqlConfigEntity,
+}
+
+/// A single attribute of a [`OgCompositeEntity`].
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct OgCompositeFieldEntity {
+    pub name: &'static str,
+    pub used_ty: UsedTypeEntity,
+}
+
+impl Hash for OgCompositeEntity {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.full_path.hash(state);
+    }
+}
+
+impl Ord for OgCompositeEntity {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.file.cmp(other.file).then_with(|| self.line.cmp(&other.line))
+    }
+}
+
+impl PartialOrd for OgCompositeEntity {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl OgCompositeEntity {
+    pub fn id_matches(&self, candidate: &core::any::TypeId) -> bool {
+        self.mappings.iter().any(|tester| *candidate == tester.id)
+    }
+
+    /// The fields, each with its SQL type including any schema prefix
+    pub fn sql_fields(
+        &self,
+        context: &OgxSql,
+    ) -> eyre::Result<Vec<(&OgCompositeFieldEntity, String)>> {
+        let self_index = context.composites[self];
+        let mut fields = Vec::new();
+        for field in &self.fields {
+            let graph_index = context
+                .graph
+                .neighbors_undirected(self_index)
+                .find(|neighbor| match &context.graph[*neighbor] {
+                    SqlGraphEntity::Type(ty) => ty.id_matches(&field.used_ty.ty_id),
+                    SqlGraphEntity::Enum(en) => en.id_matches(&field.used_ty.ty_id),
+                    SqlGraphEntity::Composite(comp) => comp.id_matches(&field.used_ty.ty_id),
+                    SqlGraphEntity::BuiltinType(defined) => defined == field.used_ty.full_path,
+                    _ => false,
+                })
+                .ok_or_else(|| eyre!("Could not find field type in graph. Got: {:?}", field))?;
+            let sql_type = match field.used_ty.metadata.argument_sql {
+                Ok(SqlMapping::As(ref argument_sql)) => argument_sql.clone(),
+                Ok(SqlMapping::Composite { array_brackets }) => field
+                    .used_ty
+                    .composite_type
+                    .map(|v| if array_brackets { format!("{v}[]") } else { format!("{v}") })
+                    .ok_or_else(|| {
+                        eyre!("Macro expansion time suggested a composite_type!() in field")
+                    })?,
+                Ok(SqlMapping::Source { array_brackets }) => context
+                    .source_only_to_sql_type(field.used_ty.ty_source)
+                    .map(|v| if array_brackets { format!("{v}[]") } else { format!("{v}") })
+                    .ok_or_else(|| {
+                        eyre!("Macro expansion time suggested a source only mapping in field")
+                    })?,
+                Ok(SqlMapping::Skip) => {
+                    return Err(eyre!(
+                        "Composite type `{}` field `{}` cannot be skipped",
+                        self.name,
+                        field.name
+                    ))
+                }
+                Err(err) => match context.source_only_to_sql_type(field.used_ty.ty_source) {
+                    Some(source_only_mapping) => source_only_mapping,
+                    None => return Err(err).wrap_err("While mapping composite type field"),
+                },
+            };
+            fields
+                .push((field, format!("{}{}", context.schema_prefix_for(&graph_index), sql_type)));
+        }
+        Ok(fields)
+    }
+}
+
+impl From<OgCompositeEntity> for SqlGraphEntity {
+    fn from(val: OgCompositeEntity) -> Self {
+        SqlGraphEntity::Composite(val)
+    }
+}
+
+impl SqlGraphIdentifier for OgCompositeEntity {
+    fn dot_identifier(&self) -> String {
+        format!("composite {}", self.full_path)
+    }
+    fn rust_identifier(&self) -> String {
+        self.full_path.to_string()
+    }
+
+    fn file(&self) -> Option<&'static str> {
+        Some(self.file)
+    }
+
+    fn line(&self) -> Option<u32> {
+        Some(self.line)
+    }
+}
+
+impl ToSql for OgCompositeEntity {
+    #[tracing::instrument(level = "debug", err, skip(self, context), fields(identifier = %self.rust_identifier()))]
+    fn to_sql(&self, context: &OgxSql) -> eyre::Result<String> {
+        let self_index = context.composites[self];
+        let fields = self
+            .sql_fields(context)?
+            .into_iter()
+            .map(|(field, sql_type)| {
+                format!(
+                    "\t\"{name}\" {sql_type} /* {type_name} */",
+                    name = field.name,
+                    sql_type = sql_type,
+                    type_name = field.used_ty.metadata.type_name,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let sql = format!(
+            "\n\
+                    -- {file}:{line}\n\
+                    -- {full_path}\n\
+                    CREATE TYPE {schema}{name} AS (\n\
+                        {fields}\
+                    );\
+                ",
+            schema = context.schema_prefix_for(&self_index),
+            full_path = self.full_path,
+            file = self.file,
+            line = self.line,
+            name = self.name,
+            fields = fields.join(",\n") + "\n",
+        );
+        tracing::trace!(%sql);
+        Ok(sql)
+    }
+}
@@ -0,0 +1,75 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+/*!
+
+`#[og_cast]` related macro expansion for Rust to SQL translation
+
+> Like all of the [`sql_entity_graph`][crate::sql_entity_graph] APIs, this is considered **internal**
+to the `ogx` framework and very subject to change between versions. While you may use this, please do it with caution.
+
+*/
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, ToTokens, TokenStreamExt};
+use syn::parse::{Parse, ParseStream};
+
+/// A parsed `#[og_cast]` cast.
+///
+/// It is created during [`OgExtern`](crate::sql_entity_graph::OgExtern) parsing.
+///
+/// ```rust
+/// use ogx_utils::sql_entity_graph::OgCast;
+///
+/// # fn main() -> eyre::Result<()> {
+/// let parsed: OgCast = syn::parse_str("(implicit)")?;
+/// assert_eq!(parsed, OgCast::Implicit);
+/// let parsed: OgCast = syn::parse_str("")?;
+/// assert_eq!(parsed, OgCast::Default);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OgCast {
+    Default,
+    Assignment,
+    Implicit,
+}
+
+impl Parse for OgCast {
+    fn parse(input: ParseStream) -> Result<Self, syn::Error> {
+        if input.is_empty() {
+            return Ok(OgCast::Default);
+        }
+
+        let inner;
+        syn::parenthesized!(inner in input);
+        let ident: syn::Ident = inner.parse()?;
+        match ident.to_string().as_str() {
+            "explicit" => Ok(OgCast::Default),
+            "assignment" => Ok(OgCast::Assignment),
+            "implicit" => Ok(OgCast::Implicit),
+            _ => Err(syn::Error::new(
+                ident.span(),
+                "expected one of `explicit`, `assignment` or `implicit`",
+            )),
+        }
+    }
+}
+
+impl ToTokens for OgCast {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        let quoted = match self {
+            OgCast::Default => quote! { ::ogx::utils::sql_entity_graph::OgCastEntity::Default },
+            OgCast::Assignment => {
+                quote! { ::ogx::utils::sql_entity_graph::OgCastEntity::Assignment }
+            }
+            OgCast::Implicit => quote! { ::ogx::utils::sql_entity_graph::OgCastEntity::Implicit },
+        };
+        tokens.append_all(quoted);
+    }
+}
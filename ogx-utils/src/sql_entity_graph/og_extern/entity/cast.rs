@@ -0,0 +1,39 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+/*!
+
+`#[og_extern]` related cast entities for Rust to SQL translation
+
+> Like all of the [`sql_entity_graph`][crate::sql_entity_graph] APIs, this is considered **internal**
+to the `ogx` framework and very subject to change between versions. While you may use this, please do it with caution.
+
+*/
+use serde::{Deserialize, Serialize};
+
+/// The output of a [`OgCast`](crate::sql_entity_graph::OgCast) from `quote::ToTokens::to_tokens`.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum OgCastEntity {
+    /// Only applied by an explicit `CAST(x AS t)` or `x::t`.
+    Default,
+    /// Also applied implicitly when assigning to a column.
+    Assignment,
+    /// Applied implicitly in any context.
+    Implicit,
+}
+
+impl OgCastEntity {
+    /// The trailing `AS ...` clause of a `CREATE CAST` for this kind of cast.
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            OgCastEntity::Default => "",
+            OgCastEntity::Assignment => "\n\tAS ASSIGNMENT",
+            OgCastEntity::Implicit => "\n\tAS IMPLICIT",
+        }
+    }
+}
@@ -15,10 +15,12 @@ to the `ogx` framework and very subject to change between versions. While you ma
 
 */
 mod argument;
+mod cast;
 mod operator;
 mod returning;
 
 pub use argument::OgExternArgumentEntity;
+pub use cast::OgCastEntity;
 pub use operator::OgOperatorEntity;
 pub use returning::{OgExternReturnEntity, OgExternReturnEntityIteratedItem};
 
@@ -26,10 +28,11 @@ use crate::sql_entity_graph::metadata::{Returns, SqlMapping};
 use crate::sql_entity_graph::ogx_sql::OgxSql;
 use crate::sql_entity_graph::to_sql::entity::ToSqlConfigEntity;
 use crate::sql_entity_graph::to_sql::ToSql;
-use crate::sql_entity_graph::{SqlGraphEntity, SqlGraphIdentifier};
+use crate::sql_entity_graph::{SqlGraphEntity, SqlGraphIdentifier, UsedTypeEntity};
 use crate::ExternArgs;
 
 use eyre::{eyre, WrapErr};
+use petgraph::stable_graph::NodeIndex;
 use std::cmp::Ordering;
 
 /// The output of a [`OgExtern`](crate::sql_entity_graph::og_extern::OgExtern) from `quote::ToTokens::to_tokens`.
@@ -48,6 +51,7 @@ pub struct OgExternEntity {
     pub extern_attrs: Vec<ExternArgs>,
     pub search_path: Option<Vec<&'static str>>,
     pub operator: Option<OgOperatorEntity>,
+    pub cast: Option<OgCastEntity>,
     pub to_sql_config: ToSqlConfigEntity,
 }
 
@@ -160,6 +164,7 @@ impl ToSql for OgExternEntity {
                         .find(|neighbor| match &context.graph[*neighbor] {
                             SqlGraphEntity::Type(ty) => ty.id_matches(&arg.used_ty.ty_id),
                             SqlGraphEntity::Enum(en) => en.id_matches(&arg.used_ty.ty_id),
+                            SqlGraphEntity::Composite(comp) => comp.id_matches(&arg.used_ty.ty_id),
                             SqlGraphEntity::BuiltinType(defined) => {
                                 defined == arg.used_ty.full_path
                             }
@@ -283,6 +288,7 @@ impl ToSql for OgExternEntity {
                         .find(|neighbor| match &context.graph[*neighbor] {
                             SqlGraphEntity::Type(neighbor_ty) => neighbor_ty.id_matches(&ty.ty_id),
                             SqlGraphEntity::Enum(neighbor_en) => neighbor_en.id_matches(&ty.ty_id),
+                            SqlGraphEntity::Composite(neighbor_comp) => neighbor_comp.id_matches(&ty.ty_id),
                             SqlGraphEntity::BuiltinType(defined) => &*defined == ty.full_path,
                             _ => false,
                         })
@@ -324,6 +330,7 @@ impl ToSql for OgExternEntity {
                         .find(|neighbor| match &context.graph[*neighbor] {
                             SqlGraphEntity::Type(neighbor_ty) => neighbor_ty.id_matches(&ty.ty_id),
                             SqlGraphEntity::Enum(neighbor_en) => neighbor_en.id_matches(&ty.ty_id),
+                            SqlGraphEntity::Composite(neighbor_comp) => neighbor_comp.id_matches(&ty.ty_id),
                             SqlGraphEntity::BuiltinType(defined) => defined == ty.full_path,
                             _ => false,
                         })
@@ -403,6 +410,9 @@ impl ToSql for OgExternEntity {
                                     SqlGraphEntity::Enum(neightbor_en) => {
                                         neightbor_en.id_matches(&ty.ty_id)
                                     }
+                                    SqlGraphEntity::Composite(neightbor_comp) => {
+                                        neightbor_comp.id_matches(&ty.ty_id)
+                                    }
                                     SqlGraphEntity::BuiltinType(defined) => defined == ty.ty_source,
                                     _ => false,
                                 });
@@ -487,161 +497,409 @@ impl ToSql for OgExternEntity {
         tracing::trace!(sql = %ext_sql);
 
         let rendered = if let Some(op) = &self.operator {
-            let mut optionals = vec![];
-            if let Some(it) = op.commutator {
-                optionals.push(format!("\tCOMMUTATOR = {}", it));
-            };
-            if let Some(it) = op.negator {
-                optionals.push(format!("\tNEGATOR = {}", it));
-            };
-            if let Some(it) = op.restrict {
-                optionals.push(format!("\tRESTRICT = {}", it));
-            };
-            if let Some(it) = op.join {
-                optionals.push(format!("\tJOIN = {}", it));
-            };
-            if op.hashes {
-                optionals.push(String::from("\tHASHES"));
-            };
-            if op.merges {
-                optionals.push(String::from("\tMERGES"));
+            let operator_sql = self.operator_sql(context, self_index, op)?;
+            tracing::trace!(sql = %operator_sql);
+            ext_sql + &operator_sql
+        } else {
+            ext_sql
+        };
+
+        let rendered = if let Some(cast) = &self.cast {
+            let cast_sql = self.cast_sql(context, self_index, cast)?;
+            tracing::trace!(sql = %cast_sql);
+            rendered + &cast_sql
+        } else {
+            rendered
+        };
+        Ok(rendered)
+    }
+}
+
+impl OgExternEntity {
+    /// The arguments SQL sees, each with its SQL type as `DROP FUNCTION` identifies the function
+    /// by, including any schema prefix and `VARIADIC`
+    pub fn sql_arguments(
+        &self,
+        context: &OgxSql,
+    ) -> eyre::Result<Vec<(&OgExternArgumentEntity, String)>> {
+        let mut arguments = Vec::new();
+        for (arg, metadata_argument) in self.fn_args.iter().zip(&self.metadata.arguments) {
+            let sql_type = match metadata_argument.argument_sql {
+                Ok(SqlMapping::Skip) => continue,
+                Ok(ref mapping) => arg.used_ty.sql_type(mapping, context)?,
+                Err(err) => context
+                    .source_only_to_sql_type(arg.used_ty.ty_source)
+                    .ok_or(err)
+                    .wrap_err("While mapping argument")?,
             };
+            arguments.push((
+                arg,
+                format!(
+                    "{variadic}{schema_prefix}{sql_type}",
+                    variadic = if metadata_argument.variadic { "VARIADIC " } else { "" },
+                    schema_prefix = self.type_schema_prefix(context, &arg.used_ty),
+                ),
+            ));
+        }
+        Ok(arguments)
+    }
 
-            let left_arg =
-                self.metadata.arguments.get(0).ok_or_else(|| {
-                    eyre!("Did not find `left_arg` for operator `{}`.", self.name)
-                })?;
-            let left_fn_arg = self
-                .fn_args
-                .get(0)
-                .ok_or_else(|| eyre!("Did not find `left_arg` for operator `{}`.", self.name))?;
-            let left_arg_graph_index = context
-                .graph
-                .neighbors_undirected(self_index)
-                .find(|neighbor| match &context.graph[*neighbor] {
-                    SqlGraphEntity::Type(ty) => ty.id_matches(&left_fn_arg.used_ty.ty_id),
-                    SqlGraphEntity::Enum(en) => en.id_matches(&left_fn_arg.used_ty.ty_id),
-                    SqlGraphEntity::BuiltinType(defined) => defined == &left_arg.type_name,
-                    _ => false,
-                })
-                .ok_or_else(|| {
-                    eyre!("Could not find left arg type in graph. Got: {:?}", left_arg)
-                })?;
-            let left_arg_sql = match left_arg.argument_sql {
-                Ok(SqlMapping::As(ref sql)) => sql.clone(),
-                Ok(SqlMapping::Composite { array_brackets }) => {
-                    if array_brackets {
-                        let composite_type = self.fn_args[0].used_ty.composite_type
-                            .ok_or(eyre!("Found a composite type but macro expansion time did not reveal a name, use `ogx::composite_type!()`"))?;
-                        format!("{composite_type}[]")
-                    } else {
-                        self.fn_args[0].used_ty.composite_type
-                            .ok_or(eyre!("Found a composite type but macro expansion time did not reveal a name, use `ogx::composite_type!()`"))?.to_string()
+    /// What the function returns, as written after `RETURNS`
+    pub fn sql_return_type(&self, context: &OgxSql) -> eyre::Result<String> {
+        let return_sql = || {
+            self.metadata
+                .retval
+                .as_ref()
+                .map(|retval| retval.return_sql.clone())
+                .ok_or_else(|| eyre!("Macro expansion time and SQL resolution time had differing opinions about the return value existing"))
+        };
+        let returns = match &self.fn_return {
+            OgExternReturnEntity::None => String::from("void"),
+            OgExternReturnEntity::Trigger => String::from("trigger"),
+            OgExternReturnEntity::Type { ty } => {
+                let sql_type = match return_sql()? {
+                    Ok(Returns::One(mapping)) => ty.sql_type(&mapping, context)?,
+                    Ok(other) => return Err(eyre!("Got non-plain mapped/composite return variant SQL in what macro-expansion thought was a type, got: {other:?}")),
+                    Err(err) => context
+                        .source_only_to_sql_type(ty.ty_source)
+                        .ok_or(err)
+                        .wrap_err("Error mapping return SQL")?,
+                };
+                format!("{}{}", self.type_schema_prefix(context, ty), sql_type)
+            }
+            OgExternReturnEntity::SetOf { ty, optional: _ } => match return_sql()? {
+                Ok(Returns::SetOf(mapping)) => format!(
+                    "SETOF {}{}",
+                    self.type_schema_prefix(context, ty),
+                    ty.sql_type(&mapping, context)?
+                ),
+                Ok(_other) => return Err(eyre!("Got non-setof mapped/composite return variant SQL in what macro-expansion thought was a setof")),
+                Err(err) => return Err(err).wrap_err("Error mapping return SQL"),
+            },
+            OgExternReturnEntity::Iterated { tys, optional: _ } => match return_sql()? {
+                Ok(Returns::Table(variants)) => {
+                    let mut columns = Vec::new();
+                    for (item, mapping) in tys.iter().zip(&variants) {
+                        columns.push(format!(
+                            "{} {}{}",
+                            item.name.unwrap_or_default(),
+                            self.type_schema_prefix(context, &item.ty),
+                            item.ty.sql_type(mapping, context)?
+                        ));
                     }
+                    format!("TABLE ({})", columns.join(", "))
+                }
+                Ok(_other) => return Err(eyre!("Got non-table return variant SQL in what macro-expansion thought was a table")),
+                Err(err) => return Err(err).wrap_err("Error mapping return SQL"),
+            },
+        };
+        Ok(returns)
+    }
+
+    /// The schema prefix of the type or builtin type `used_ty` resolved to in the graph
+    fn type_schema_prefix(&self, context: &OgxSql, used_ty: &UsedTypeEntity) -> String {
+        context
+            .graph
+            .neighbors_undirected(context.externs[self])
+            .find(|neighbor| match &context.graph[*neighbor] {
+                SqlGraphEntity::Type(ty) => ty.id_matches(&used_ty.ty_id),
+                SqlGraphEntity::Enum(en) => en.id_matches(&used_ty.ty_id),
+                SqlGraphEntity::Composite(comp) => comp.id_matches(&used_ty.ty_id),
+                SqlGraphEntity::BuiltinType(defined) => {
+                    defined == used_ty.full_path || defined == used_ty.ty_source
                 }
-                Ok(SqlMapping::Source { array_brackets }) => {
-                    if array_brackets {
-                        let composite_type = context
-                            .source_only_to_sql_type(self.fn_args[0].used_ty.ty_source)
-                            .ok_or(eyre!(
-                                "Found a source only mapping but no source mapping exists for this"
-                            ))?;
-                        format!("{composite_type}[]")
-                    } else {
-                        context.source_only_to_sql_type(self.fn_args[0].used_ty.ty_source)
+                _ => false,
+            })
+            .map(|index| context.schema_prefix_for(&index))
+            .unwrap_or_default()
+    }
+
+    /// The `CREATE OPERATOR` for an `#[opname]` function, from its two arguments.
+    pub(crate) fn operator_sql(
+        &self,
+        context: &OgxSql,
+        self_index: NodeIndex,
+        op: &OgOperatorEntity,
+    ) -> eyre::Result<String> {
+        let mut optionals = vec![];
+        if let Some(it) = op.commutator {
+            optionals.push(format!("\tCOMMUTATOR = {}", it));
+        };
+        if let Some(it) = op.negator {
+            optionals.push(format!("\tNEGATOR = {}", it));
+        };
+        if let Some(it) = op.restrict {
+            optionals.push(format!("\tRESTRICT = {}", it));
+        };
+        if let Some(it) = op.join {
+            optionals.push(format!("\tJOIN = {}", it));
+        };
+        if op.hashes {
+            optionals.push(String::from("\tHASHES"));
+        };
+        if op.merges {
+            optionals.push(String::from("\tMERGES"));
+        };
+
+        let left_arg = self
+            .metadata
+            .arguments
+            .get(0)
+            .ok_or_else(|| eyre!("Did not find `left_arg` for operator `{}`.", self.name))?;
+        let left_fn_arg = self
+            .fn_args
+            .get(0)
+            .ok_or_else(|| eyre!("Did not find `left_arg` for operator `{}`.", self.name))?;
+        let left_arg_graph_index = context
+            .graph
+            .neighbors_undirected(self_index)
+            .find(|neighbor| match &context.graph[*neighbor] {
+                SqlGraphEntity::Type(ty) => ty.id_matches(&left_fn_arg.used_ty.ty_id),
+                SqlGraphEntity::Enum(en) => en.id_matches(&left_fn_arg.used_ty.ty_id),
+                SqlGraphEntity::Composite(comp) => comp.id_matches(&left_fn_arg.used_ty.ty_id),
+                SqlGraphEntity::BuiltinType(defined) => defined == &left_arg.type_name,
+                _ => false,
+            })
+            .ok_or_else(|| eyre!("Could not find left arg type in graph. Got: {:?}", left_arg))?;
+        let left_arg_sql = match left_arg.argument_sql {
+            Ok(SqlMapping::As(ref sql)) => sql.clone(),
+            Ok(SqlMapping::Composite { array_brackets }) => {
+                if array_brackets {
+                    let composite_type = self.fn_args[0].used_ty.composite_type
+                        .ok_or(eyre!("Found a composite type but macro expansion time did not reveal a name, use `ogx::composite_type!()`"))?;
+                    format!("{composite_type}[]")
+                } else {
+                    self.fn_args[0].used_ty.composite_type
                         .ok_or(eyre!("Found a composite type but macro expansion time did not reveal a name, use `ogx::composite_type!()`"))?.to_string()
-                    }
                 }
-                Ok(SqlMapping::Skip) => {
-                    return Err(eyre!(
-                        "Found an skipped SQL type in an operator, this is not valid"
-                    ))
+            }
+            Ok(SqlMapping::Source { array_brackets }) => {
+                if array_brackets {
+                    let composite_type = context
+                        .source_only_to_sql_type(self.fn_args[0].used_ty.ty_source)
+                        .ok_or(eyre!(
+                            "Found a source only mapping but no source mapping exists for this"
+                        ))?;
+                    format!("{composite_type}[]")
+                } else {
+                    context.source_only_to_sql_type(self.fn_args[0].used_ty.ty_source)
+                    .ok_or(eyre!("Found a composite type but macro expansion time did not reveal a name, use `ogx::composite_type!()`"))?.to_string()
                 }
-                Err(err) => return Err(err.into()),
-            };
+            }
+            Ok(SqlMapping::Skip) => {
+                return Err(eyre!("Found an skipped SQL type in an operator, this is not valid"))
+            }
+            Err(err) => return Err(err.into()),
+        };
 
-            let right_arg =
-                self.metadata.arguments.get(1).ok_or_else(|| {
-                    eyre!("Did not find `left_arg` for operator `{}`.", self.name)
-                })?;
-            let right_fn_arg = self
-                .fn_args
-                .get(1)
-                .ok_or_else(|| eyre!("Did not find `left_arg` for operator `{}`.", self.name))?;
-            let right_arg_graph_index = context
-                .graph
-                .neighbors_undirected(self_index)
-                .find(|neighbor| match &context.graph[*neighbor] {
-                    SqlGraphEntity::Type(ty) => ty.id_matches(&right_fn_arg.used_ty.ty_id),
-                    SqlGraphEntity::Enum(en) => en.id_matches(&right_fn_arg.used_ty.ty_id),
-                    SqlGraphEntity::BuiltinType(defined) => defined == &right_arg.type_name,
-                    _ => false,
-                })
-                .ok_or_else(|| {
-                    eyre!("Could not find right arg type in graph. Got: {:?}", right_arg)
-                })?;
-            let right_arg_sql = match right_arg.argument_sql {
-                Ok(SqlMapping::As(ref sql)) => sql.clone(),
-                Ok(SqlMapping::Composite { array_brackets }) => {
-                    if array_brackets {
-                        let composite_type = self.fn_args[1].used_ty.composite_type
-                            .ok_or(eyre!("Found a composite type but macro expansion time did not reveal a name, use `ogx::composite_type!()`"))?;
-                        format!("{composite_type}[]")
-                    } else {
-                        self.fn_args[0].used_ty.composite_type
-                            .ok_or(eyre!("Found a composite type but macro expansion time did not reveal a name, use `ogx::composite_type!()`"))?.to_string()
-                    }
-                }
-                Ok(SqlMapping::Source { array_brackets }) => {
-                    if array_brackets {
-                        let composite_type = context
-                            .source_only_to_sql_type(self.fn_args[1].used_ty.ty_source)
-                            .ok_or(eyre!(
-                                "Found a source only mapping but no source mapping exists for this"
-                            ))?;
-                        format!("{composite_type}[]")
-                    } else {
-                        context.source_only_to_sql_type(self.fn_args[1].used_ty.ty_source)
+        let right_arg = self
+            .metadata
+            .arguments
+            .get(1)
+            .ok_or_else(|| eyre!("Did not find `left_arg` for operator `{}`.", self.name))?;
+        let right_fn_arg = self
+            .fn_args
+            .get(1)
+            .ok_or_else(|| eyre!("Did not find `left_arg` for operator `{}`.", self.name))?;
+        let right_arg_graph_index = context
+            .graph
+            .neighbors_undirected(self_index)
+            .find(|neighbor| match &context.graph[*neighbor] {
+                SqlGraphEntity::Type(ty) => ty.id_matches(&right_fn_arg.used_ty.ty_id),
+                SqlGraphEntity::Enum(en) => en.id_matches(&right_fn_arg.used_ty.ty_id),
+                SqlGraphEntity::Composite(comp) => comp.id_matches(&right_fn_arg.used_ty.ty_id),
+                SqlGraphEntity::BuiltinType(defined) => defined == &right_arg.type_name,
+                _ => false,
+            })
+            .ok_or_else(|| eyre!("Could not find right arg type in graph. Got: {:?}", right_arg))?;
+        let right_arg_sql = match right_arg.argument_sql {
+            Ok(SqlMapping::As(ref sql)) => sql.clone(),
+            Ok(SqlMapping::Composite { array_brackets }) => {
+                if array_brackets {
+                    let composite_type = self.fn_args[1].used_ty.composite_type
+                        .ok_or(eyre!("Found a composite type but macro expansion time did not reveal a name, use `ogx::composite_type!()`"))?;
+                    format!("{composite_type}[]")
+                } else {
+                    self.fn_args[0].used_ty.composite_type
                         .ok_or(eyre!("Found a composite type but macro expansion time did not reveal a name, use `ogx::composite_type!()`"))?.to_string()
-                    }
                 }
-                Ok(SqlMapping::Skip) => {
-                    return Err(eyre!(
-                        "Found an skipped SQL type in an operator, this is not valid"
-                    ))
+            }
+            Ok(SqlMapping::Source { array_brackets }) => {
+                if array_brackets {
+                    let composite_type = context
+                        .source_only_to_sql_type(self.fn_args[1].used_ty.ty_source)
+                        .ok_or(eyre!(
+                            "Found a source only mapping but no source mapping exists for this"
+                        ))?;
+                    format!("{composite_type}[]")
+                } else {
+                    context.source_only_to_sql_type(self.fn_args[1].used_ty.ty_source)
+                    .ok_or(eyre!("Found a composite type but macro expansion time did not reveal a name, use `ogx::composite_type!()`"))?.to_string()
                 }
-                Err(err) => return Err(err.into()),
-            };
+            }
+            Ok(SqlMapping::Skip) => {
+                return Err(eyre!("Found an skipped SQL type in an operator, this is not valid"))
+            }
+            Err(err) => return Err(err.into()),
+        };
 
-            let operator_sql = format!("\n\n\
-                                                    -- {file}:{line}\n\
-                                                    -- {module_path}::{name}\n\
-                                                    CREATE OPERATOR {opname} (\n\
-                                                        \tPROCEDURE=\"{name}\",\n\
-                                                        \tLEFTARG={schema_prefix_left}{left_arg}, /* {left_name} */\n\
-                                                        \tRIGHTARG={schema_prefix_right}{right_arg}{maybe_comma} /* {right_name} */\n\
-                                                        {optionals}\
-                                                    );\
-                                                    ",
-                                                    opname = op.opname.unwrap(),
-                                                    file = self.file,
-                                                    line = self.line,
-                                                    name = self.name,
-                                                    module_path = self.module_path,
-                                                    left_name = left_arg.type_name,
-                                                    right_name = right_arg.type_name,
-                                                    schema_prefix_left = context.schema_prefix_for(&left_arg_graph_index),
-                                                    left_arg = left_arg_sql,
-                                                    schema_prefix_right = context.schema_prefix_for(&right_arg_graph_index),
-                                                    right_arg = right_arg_sql,
-                                                    maybe_comma = if optionals.len() >= 1 { "," } else { "" },
-                                                    optionals = if !optionals.is_empty() { optionals.join(",\n") + "\n" } else { "".to_string() },
-                                            );
-            tracing::trace!(sql = %operator_sql);
-            ext_sql + &operator_sql
-        } else {
-            ext_sql
+        let operator_sql = format!("\n\n\
+                                                -- {file}:{line}\n\
+                                                -- {module_path}::{name}\n\
+                                                CREATE OPERATOR {opname} (\n\
+                                                    \tPROCEDURE=\"{name}\",\n\
+                                                    \tLEFTARG={schema_prefix_left}{left_arg}, /* {left_name} */\n\
+                                                    \tRIGHTARG={schema_prefix_right}{right_arg}{maybe_comma} /* {right_name} */\n\
+                                                    {optionals}\
+                                                );\
+                                                ",
+                                                opname = op.opname.unwrap(),
+                                                file = self.file,
+                                                line = self.line,
+                                                name = self.name,
+                                                module_path = self.module_path,
+                                                left_name = left_arg.type_name,
+                                                right_name = right_arg.type_name,
+                                                schema_prefix_left = context.schema_prefix_for(&left_arg_graph_index),
+                                                left_arg = left_arg_sql,
+                                                schema_prefix_right = context.schema_prefix_for(&right_arg_graph_index),
+                                                right_arg = right_arg_sql,
+                                                maybe_comma = if optionals.len() >= 1 { "," } else { "" },
+                                                optionals = if !optionals.is_empty() { optionals.join(",\n") + "\n" } else { "".to_string() },
+                                        );
+        Ok(operator_sql)
+    }
+
+    /// The `CREATE CAST` for a `#[og_cast]` function, from its first argument to its return type.
+    fn cast_sql(
+        &self,
+        context: &OgxSql,
+        self_index: NodeIndex,
+        cast: &OgCastEntity,
+    ) -> eyre::Result<String> {
+        let source_arg = self
+            .metadata
+            .arguments
+            .get(0)
+            .ok_or_else(|| eyre!("Did not find source argument for cast `{}`.", self.name))?;
+        let source_fn_arg = self
+            .fn_args
+            .get(0)
+            .ok_or_else(|| eyre!("Did not find source argument for cast `{}`.", self.name))?;
+        let source_graph_index = context
+            .graph
+            .neighbors_undirected(self_index)
+            .find(|neighbor| match &context.graph[*neighbor] {
+                SqlGraphEntity::Type(ty) => ty.id_matches(&source_fn_arg.used_ty.ty_id),
+                SqlGraphEntity::Enum(en) => en.id_matches(&source_fn_arg.used_ty.ty_id),
+                SqlGraphEntity::Composite(comp) => comp.id_matches(&source_fn_arg.used_ty.ty_id),
+                SqlGraphEntity::BuiltinType(defined) => defined == &source_arg.type_name,
+                _ => false,
+            })
+            .ok_or_else(|| {
+                eyre!("Could not find cast source type in graph. Got: {:?}", source_arg)
+            })?;
+        let source_sql = match source_arg.argument_sql {
+            Ok(SqlMapping::As(ref sql)) => sql.clone(),
+            Ok(SqlMapping::Composite { array_brackets }) => {
+                let composite_type = source_fn_arg.used_ty.composite_type
+                    .ok_or(eyre!("Found a composite type but macro expansion time did not reveal a name, use `ogx::composite_type!()`"))?;
+                if array_brackets {
+                    format!("{composite_type}[]")
+                } else {
+                    composite_type.to_string()
+                }
+            }
+            Ok(SqlMapping::Source { array_brackets }) => {
+                let source_type =
+                    context.source_only_to_sql_type(source_fn_arg.used_ty.ty_source).ok_or(
+                        eyre!("Found a source only mapping but no source mapping exists for this"),
+                    )?;
+                if array_brackets {
+                    format!("{source_type}[]")
+                } else {
+                    source_type
+                }
+            }
+            Ok(SqlMapping::Skip) => {
+                return Err(eyre!("Found an skipped SQL type in a cast, this is not valid"))
+            }
+            Err(err) => return Err(err.into()),
         };
-        Ok(rendered)
+
+        let target_ty = match &self.fn_return {
+            OgExternReturnEntity::Type { ty } => ty,
+            _ => return Err(eyre!("Cast `{}` must return a single value.", self.name)),
+        };
+        let target_graph_index = context
+            .graph
+            .neighbors_undirected(self_index)
+            .find(|neighbor| match &context.graph[*neighbor] {
+                SqlGraphEntity::Type(ty) => ty.id_matches(&target_ty.ty_id),
+                SqlGraphEntity::Enum(en) => en.id_matches(&target_ty.ty_id),
+                SqlGraphEntity::Composite(comp) => comp.id_matches(&target_ty.ty_id),
+                SqlGraphEntity::BuiltinType(defined) => defined == target_ty.full_path,
+                _ => false,
+            })
+            .ok_or_else(|| eyre!("Could not find cast target type in graph."))?;
+        let metadata_retval = self.metadata.retval.clone().ok_or_else(|| eyre!("Macro expansion time and SQL resolution time had differing opinions about the return value existing"))?;
+        let target_sql = match metadata_retval.return_sql {
+            Ok(Returns::One(SqlMapping::As(ref sql))) => sql.clone(),
+            Ok(Returns::One(SqlMapping::Composite { array_brackets })) => {
+                let composite_type = target_ty.composite_type
+                    .ok_or(eyre!("Found a composite type but macro expansion time did not reveal a name, use `ogx::composite_type!()`"))?;
+                if array_brackets {
+                    format!("{composite_type}[]")
+                } else {
+                    composite_type.to_string()
+                }
+            }
+            Ok(Returns::One(SqlMapping::Source { array_brackets })) => {
+                let source_type = context.source_only_to_sql_type(target_ty.ty_source).ok_or(
+                    eyre!("Found a source only mapping but no source mapping exists for this"),
+                )?;
+                if array_brackets {
+                    format!("{source_type}[]")
+                } else {
+                    source_type
+                }
+            }
+            Ok(other) => {
+                return Err(eyre!(
+                    "Got non-plain mapped/composite return variant SQL in a cast, got: {other:?}"
+                ))
+            }
+            Err(err) => return Err(err).wrap_err("Error mapping cast target SQL"),
+        };
+
+        let arguments = [
+            format!("{}{}", context.schema_prefix_for(&source_graph_index), source_sql),
+            String::from("integer"),
+            String::from("boolean"),
+        ];
+        Ok(format!(
+            "\n\n\
+                -- {file}:{line}\n\
+                -- {module_path}::{name}\n\
+                CREATE CAST ({schema_prefix_source}{source} AS {schema_prefix_target}{target}) /* {source_name} -> {target_name} */\n\
+                \tWITH FUNCTION {fn_schema}\"{name}\"({arguments}){as_sql};\
+            ",
+            file = self.file,
+            line = self.line,
+            module_path = self.module_path,
+            name = self.name,
+            schema_prefix_source = context.schema_prefix_for(&source_graph_index),
+            source = source_sql,
+            schema_prefix_target = context.schema_prefix_for(&target_graph_index),
+            target = target_sql,
+            source_name = source_arg.type_name,
+            target_name = target_ty.full_path,
+            fn_schema = self
+                .schema
+                .map(|schema| format!("{}.", schema))
+                .unwrap_or_else(|| context.schema_prefix_for(&self_index)),
+            arguments = arguments[..self.fn_args.len().min(arguments.len())].join(", "),
+            as_sql = cast.as_sql(),
+        ))
     }
 }
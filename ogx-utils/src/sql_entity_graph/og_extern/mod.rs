@@ -17,12 +17,14 @@ to the `ogx` framework and very subject to change between versions. While you ma
 */
 mod argument;
 mod attribute;
+mod cast;
 pub mod entity;
 mod operator;
 mod returning;
 mod search_path;
 
 pub use argument::OgExternArgument;
+pub use cast::OgCast;
 pub use operator::OgOperator;
 pub use returning::NameMacro;
 
@@ -205,6 +207,23 @@ impl OgExtern {
         skel
     }
 
+    fn cast(&self) -> Option<OgCast> {
+        self.func
+            .attrs
+            .iter()
+            .find(|attr| {
+                attr.path
+                    .segments
+                    .last()
+                    .map(|segment| segment.ident == "og_cast")
+                    .unwrap_or_default()
+            })
+            .map(|attr| {
+                syn::parse2::<OgCast>(attr.tokens.clone())
+                    .expect(&format!("Unable to parse {:?}", &attr.tokens))
+            })
+    }
+
     fn search_path(&self) -> Option<SearchPathList> {
         self.func
             .attrs
@@ -278,6 +297,38 @@ impl OgExtern {
         };
 
         let operator = self.operator().into_iter();
+        let cast = self.cast();
+        if cast.is_some() {
+            // `CREATE CAST` wants `(source [, integer [, boolean]]) -> target`
+            let arg_count = self.func.sig.inputs.len();
+            if !matches!(returns, Returning::Type(_)) || arg_count < 1 || arg_count > 3 {
+                let msg = "`#[og_cast]` functions must take the value to cast, optionally followed by an `i32` typmod and a `bool` explicit flag, and return a single value";
+                return quote_spanned! { self.func.sig.span() =>
+                    std::compile_error!(#msg);
+                };
+            }
+            for (input, expected) in self.func.sig.inputs.iter().skip(1).zip(["i32", "bool"]) {
+                let matches_expected = match input {
+                    syn::FnArg::Typed(pat_ty) => match &*pat_ty.ty {
+                        syn::Type::Path(path) => {
+                            path.qself.is_none() && path.path.is_ident(expected)
+                        }
+                        _ => false,
+                    },
+                    syn::FnArg::Receiver(_) => false,
+                };
+                if !matches_expected {
+                    let msg = format!(
+                        "`#[og_cast]` functions take `{}` after the value to cast",
+                        expected
+                    );
+                    return quote_spanned! { input.span() =>
+                        std::compile_error!(#msg);
+                    };
+                }
+            }
+        }
+        let cast = cast.into_iter();
         let to_sql_config = match self.overridden() {
             None => self.to_sql_config.clone(),
             Some(content) => {
@@ -312,6 +363,7 @@ impl OgExtern {
                     extern_attrs: vec![#extern_attrs],
                     search_path: None #( .unwrap_or(Some(vec![#search_path])) )*,
                     operator: None #( .unwrap_or(Some(#operator)) )*,
+                    cast: None #( .unwrap_or(Some(#cast)) )*,
                     to_sql_config: #to_sql_config,
                 };
                 ::ogx::utils::sql_entity_graph::SqlGraphEntity::Function(submission)
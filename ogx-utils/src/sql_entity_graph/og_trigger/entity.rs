@@ -1,6 +1,6 @@
 /*!
 
-`#[og_trigger]` related entities for Rust to SQL translation
+`#[og_trigger]` and `#[og_event_trigger]` related entities for Rust to SQL translation
 
 > Like all of the [`sql_entity_graph`][crate::sql_entity_graph] APIs, this is considered **internal**
 to the `ogx` framework and very subject to change between versions. While you may use this, please do it with caution.
@@ -21,6 +21,8 @@ pub struct OgTriggerEntity {
     pub line: u32,
     pub module_path: &'static str,
     pub full_path: &'static str,
+    /// Whether this is a DDL event trigger rather than a DML trigger
+    pub event: bool,
 }
 
 impl OgTriggerEntity {
@@ -62,7 +64,7 @@ impl ToSql for OgTriggerEntity {
             -- {file}:{line}\n\
             -- {full_path}\n\
             CREATE FUNCTION {schema}\"{function_name}\"()\n\
-                \tRETURNS TRIGGER\n\
+                \tRETURNS {returns}\n\
                 \tLANGUAGE c\n\
                 \tAS 'MODULE_PATHNAME', '{wrapper_function_name}';\
         ",
@@ -71,6 +73,7 @@ impl ToSql for OgTriggerEntity {
             line = self.line,
             full_path = self.full_path,
             function_name = self.function_name,
+            returns = if self.event { "EVENT_TRIGGER" } else { "TRIGGER" },
             wrapper_function_name = self.wrapper_function_name(),
         );
         Ok(sql)
@@ -79,7 +82,11 @@ impl ToSql for OgTriggerEntity {
 
 impl SqlGraphIdentifier for OgTriggerEntity {
     fn dot_identifier(&self) -> String {
-        format!("trigger fn {}", self.full_path)
+        if self.event {
+            format!("event trigger fn {}", self.full_path)
+        } else {
+            format!("trigger fn {}", self.full_path)
+        }
     }
     fn rust_identifier(&self) -> String {
         self.full_path.to_string()
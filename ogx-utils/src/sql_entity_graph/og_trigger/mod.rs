@@ -1,6 +1,6 @@
 /*!
 
-`#[og_trigger]` related macro expansion for Rust to SQL translation
+`#[og_trigger]` and `#[og_event_trigger]` related macro expansion for Rust to SQL translation
 
 > Like all of the [`sql_entity_graph`][crate::sql_entity_graph] APIs, this is considered **internal**
 to the `ogx` framework and very subject to change between versions. While you may use this, please do it with caution.
@@ -19,12 +19,30 @@ use syn::{ItemFn, Token};
 pub struct OgTrigger {
     func: syn::ItemFn,
     to_sql_config: ToSqlConfig,
+    event: bool,
 }
 
 impl OgTrigger {
+    /// A DML trigger function, which returns `trigger`
     pub fn new(
         func: ItemFn,
         attributes: syn::punctuated::Punctuated<OgTriggerAttribute, Token![,]>,
+    ) -> Result<Self, syn::Error> {
+        Self::new_inner(func, attributes, false)
+    }
+
+    /// A DDL event trigger function, which returns `event_trigger`
+    pub fn new_event_trigger(
+        func: ItemFn,
+        attributes: syn::punctuated::Punctuated<OgTriggerAttribute, Token![,]>,
+    ) -> Result<Self, syn::Error> {
+        Self::new_inner(func, attributes, true)
+    }
+
+    fn new_inner(
+        func: ItemFn,
+        attributes: syn::punctuated::Punctuated<OgTriggerAttribute, Token![,]>,
+        event: bool,
     ) -> Result<Self, syn::Error> {
         if attributes.len() > 1 {
             return Err(syn::Error::new(
@@ -51,7 +69,7 @@ impl OgTrigger {
             crate::ident_is_acceptable_to_opengauss(&func.sig.ident)?;
         }
 
-        Ok(Self { func, to_sql_config })
+        Ok(Self { func, to_sql_config, event })
     }
 
     pub fn entity_tokens(&self) -> Result<ItemFn, syn::Error> {
@@ -62,6 +80,7 @@ impl OgTrigger {
         let func_sig_ident = &self.func.sig.ident;
         let function_name = func_sig_ident.to_string();
         let to_sql_config = &self.to_sql_config;
+        let event = self.event;
 
         let tokens = quote! {
             #[no_mangle]
@@ -78,6 +97,7 @@ impl OgTrigger {
                     full_path: concat!(module_path!(), "::", stringify!(#func_sig_ident)),
                     module_path: module_path!(),
                     to_sql_config: #to_sql_config,
+                    event: #event,
                 };
                 ::ogx::utils::sql_entity_graph::SqlGraphEntity::Trigger(submission)
             }
@@ -91,19 +111,32 @@ impl OgTrigger {
             &format!("{}_wrapper", self.func.sig.ident.to_string()),
             self.func.sig.ident.span(),
         );
+        if self.event {
+            let tokens = quote! {
+                #[no_mangle]
+                #[ogx::og_guard]
+                extern "C" fn #extern_func_ident(fcinfo: ::ogx::pg_sys::FunctionCallInfo) -> ::ogx::pg_sys::Datum {
+                    let maybe_og_event_trigger = unsafe { ::ogx::trigger_support::OgEventTrigger::from_fcinfo(fcinfo) };
+                    let og_event_trigger = maybe_og_event_trigger.expect("OgEventTrigger::from_fcinfo failed");
+                    let trigger_fn_result: Result<(), _> = #function_ident(&og_event_trigger);
+
+                    trigger_fn_result.expect("Event trigger function panic");
+                    ::ogx::pg_return_null(fcinfo)
+                }
+            };
+            return syn::parse2(tokens);
+        }
+
         let tokens = quote! {
             #[no_mangle]
             #[ogx::og_guard]
             extern "C" fn #extern_func_ident(fcinfo: ::ogx::pg_sys::FunctionCallInfo) -> ::ogx::pg_sys::Datum {
                 let maybe_og_trigger = unsafe { ::ogx::trigger_support::OgTrigger::from_fcinfo(fcinfo) };
                 let og_trigger = maybe_og_trigger.expect("OgTrigger::from_fcinfo failed");
-                let trigger_fn_result: Result<
-                    ::ogx::heap_tuple::PgHeapTuple<'_, _>,
-                    _,
-                > = #function_ident(&og_trigger);
+                let trigger_fn_result: Result<_, _> = #function_ident(&og_trigger);
 
                 let trigger_retval = trigger_fn_result.expect("Trigger function panic");
-                match trigger_retval.into_trigger_datum() {
+                match ::ogx::trigger_support::OgTriggerReturn::into_trigger_datum(trigger_retval) {
                     None => ::ogx::pg_return_null(fcinfo),
                     Some(datum) => datum,
                 }
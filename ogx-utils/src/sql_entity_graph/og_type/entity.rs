@@ -18,9 +18,10 @@ use crate::sql_entity_graph::mapping::RustSqlMapping;
 use crate::sql_entity_graph::ogx_sql::OgxSql;
 use crate::sql_entity_graph::to_sql::entity::ToSqlConfigEntity;
 use crate::sql_entity_graph::to_sql::ToSql;
-use crate::sql_entity_graph::{SqlGraphEntity, SqlGraphIdentifier};
+use crate::sql_entity_graph::{OgExternEntity, SqlGraphEntity, SqlGraphIdentifier};
 
 use eyre::eyre;
+use petgraph::graph::NodeIndex;
 use std::cmp::Ordering;
 use std::hash::{Hash, Hasher};
 
@@ -37,6 +38,13 @@ pub struct OgTypeEntity {
     pub in_fn_module_path: String,
     pub out_fn: &'static str,
     pub out_fn_module_path: String,
+    /// Set via `#[sendrecvfuncs]`, always local to `module_path`.
+    pub send_fn: Option<&'static str>,
+    pub receive_fn: Option<&'static str>,
+    /// Set via `#[typmod_inoutfuncs]`, always local to `module_path`.
+    pub typmod_in_fn: Option<&'static str>,
+    pub typmod_out_fn: Option<&'static str>,
+    pub typmod_coerce_fn: Option<&'static str>,
     pub to_sql_config: ToSqlConfigEntity,
 }
 
@@ -62,6 +70,29 @@ impl OgTypeEntity {
     pub fn id_matches(&self, candidate: &core::any::TypeId) -> bool {
         self.mappings.iter().any(|tester| *candidate == tester.id)
     }
+
+    /// The full paths of the `send_fn`/`receive_fn`, if the type has them.
+    pub fn send_receive_fn_paths(&self) -> Option<(String, String)> {
+        match (self.send_fn, self.receive_fn) {
+            (Some(send_fn), Some(receive_fn)) => Some((
+                format!("{}::{}", self.module_path, send_fn),
+                format!("{}::{}", self.module_path, receive_fn),
+            )),
+            _ => None,
+        }
+    }
+
+    /// The full paths of the `typmod_in_fn`/`typmod_out_fn`/`typmod_coerce_fn`, if the type has them.
+    pub fn typmod_fn_paths(&self) -> Option<(String, String, String)> {
+        match (self.typmod_in_fn, self.typmod_out_fn, self.typmod_coerce_fn) {
+            (Some(typmod_in_fn), Some(typmod_out_fn), Some(typmod_coerce_fn)) => Some((
+                format!("{}::{}", self.module_path, typmod_in_fn),
+                format!("{}::{}", self.module_path, typmod_out_fn),
+                format!("{}::{}", self.module_path, typmod_coerce_fn),
+            )),
+            _ => None,
+        }
+    }
 }
 
 impl From<OgTypeEntity> for SqlGraphEntity {
@@ -163,6 +194,58 @@ impl ToSql for OgTypeEntity {
         let out_fn_sql = out_fn.to_sql(context)?;
         tracing::trace!(%out_fn_sql);
 
+        // Support functions are emitted between the shell type and the materialized type, and
+        // referenced from the latter as `ATTRIBUTE = function`.
+        let mut support_fns_sql = String::new();
+        let mut support_fns_attrs = String::new();
+        let mut support_fn_attr = |attr: &str, path: &str, kind: &str| -> eyre::Result<()> {
+            let (graph_index, func) = find_neighbor_fn(context, self_index, path, kind)?;
+            support_fns_sql += "\n";
+            support_fns_sql += &func.to_sql(context)?;
+            support_fns_attrs += &format!(
+                "\t{attr} = {schema_prefix}{name}, /* {path} */\n",
+                attr = attr,
+                schema_prefix = context.schema_prefix_for(&graph_index),
+                name = func.name,
+                path = path,
+            );
+            Ok(())
+        };
+        if let Some((send_fn_path, receive_fn_path)) = item.send_receive_fn_paths() {
+            support_fn_attr("RECEIVE", &receive_fn_path, "receive_fn")?;
+            support_fn_attr("SEND", &send_fn_path, "send_fn")?;
+        }
+        let typmod_fn_paths = item.typmod_fn_paths();
+        if let Some((typmod_in_fn_path, typmod_out_fn_path, _)) = &typmod_fn_paths {
+            support_fn_attr("TYPMOD_IN", typmod_in_fn_path, "typmod_in_fn")?;
+            support_fn_attr("TYPMOD_OUT", typmod_out_fn_path, "typmod_out_fn")?;
+        }
+
+        // The length coercion cast needs the materialized type, so it comes last.
+        let mut typmod_cast_sql = String::new();
+        if let Some((_, _, typmod_coerce_fn_path)) = &typmod_fn_paths {
+            let (graph_index, func) =
+                find_neighbor_fn(context, self_index, typmod_coerce_fn_path, "typmod_coerce_fn")?;
+            let type_name = format!("{}{}", context.schema_prefix_for(&self_index), item.name);
+            typmod_cast_sql = format!(
+                "\n{coerce_fn_sql}\n\
+                    -- {file}:{line}\n\
+                    -- {full_path}\n\
+                    CREATE CAST ({type_name} AS {type_name})\n\
+                    \tWITH FUNCTION {schema_prefix}{coerce_fn}({type_name}, integer, boolean)\n\
+                    \tAS IMPLICIT;\
+                ",
+                coerce_fn_sql = func.to_sql(context)?,
+                file = item.file,
+                line = item.line,
+                full_path = item.full_path,
+                type_name = type_name,
+                schema_prefix = context.schema_prefix_for(&graph_index),
+                coerce_fn = func.name,
+            );
+            tracing::trace!(sql = %typmod_cast_sql);
+        }
+
         let shell_type = format!(
             "\n\
                                 -- {file}:{line}\n\
@@ -184,6 +267,7 @@ impl ToSql for OgTypeEntity {
                                     \tINTERNALLENGTH = variable,\n\
                                     \tINPUT = {schema_prefix_in_fn}{in_fn}, /* {in_fn_path} */\n\
                                     \tOUTPUT = {schema_prefix_out_fn}{out_fn}, /* {out_fn_path} */\n\
+                                    {support_fns_attrs}\
                                     \tSTORAGE = extended\n\
                                 );\
                             ",
@@ -198,9 +282,37 @@ impl ToSql for OgTypeEntity {
                                         schema_prefix_out_fn = context.schema_prefix_for(&out_fn_graph_index),
                                         out_fn = item.out_fn,
                                         out_fn_path = out_fn_path,
+                                        support_fns_attrs = support_fns_attrs,
         );
         tracing::trace!(sql = %materialized_type);
 
-        Ok(shell_type + "\n" + &in_fn_sql + "\n" + &out_fn_sql + "\n" + &materialized_type)
+        Ok(shell_type
+            + "\n"
+            + &in_fn_sql
+            + "\n"
+            + &out_fn_sql
+            + &support_fns_sql
+            + "\n"
+            + &materialized_type
+            + &typmod_cast_sql)
     }
 }
+
+/// Find the function at `path` among the neighbors of the type at `self_index`.
+fn find_neighbor_fn<'a>(
+    context: &'a OgxSql,
+    self_index: NodeIndex,
+    path: &str,
+    kind: &str,
+) -> eyre::Result<(NodeIndex, &'a OgExternEntity)> {
+    let found = context
+        .graph
+        .neighbors_undirected(self_index)
+        .find_map(|neighbor| match &context.graph[neighbor] {
+            SqlGraphEntity::Function(func) if func.full_path == path => Some((neighbor, func)),
+            _ => None,
+        })
+        .ok_or_else(|| eyre!("Could not find {} graph entity `{}`.", kind, path))?;
+    tracing::trace!(?path, "Found matching `{}`", kind);
+    Ok(found)
+}
@@ -51,6 +51,10 @@ pub struct OgType {
     generics: Generics,
     in_fn: Ident,
     out_fn: Ident,
+    /// Set via `#[sendrecvfuncs]`, as `(send_fn, receive_fn)`.
+    send_receive_fns: Option<(Ident, Ident)>,
+    /// Set via `#[typmod_inoutfuncs]`, as `(typmod_in_fn, typmod_out_fn, typmod_coerce_fn)`.
+    typmod_fns: Option<(Ident, Ident, Ident)>,
     to_sql_config: ToSqlConfig,
 }
 
@@ -60,12 +64,32 @@ impl OgType {
         generics: Generics,
         in_fn: Ident,
         out_fn: Ident,
+        send_receive_fns: Option<(Ident, Ident)>,
+        typmod_fns: Option<(Ident, Ident, Ident)>,
         to_sql_config: ToSqlConfig,
     ) -> Result<Self, syn::Error> {
         if !to_sql_config.overrides_default() {
             crate::ident_is_acceptable_to_opengauss(&name)?;
         }
-        Ok(Self { generics, name, in_fn, out_fn, to_sql_config })
+        Ok(Self { generics, name, in_fn, out_fn, send_receive_fns, typmod_fns, to_sql_config })
+    }
+
+    fn send_receive_fns(name: &Ident, attrs: &[syn::Attribute]) -> Option<(Ident, Ident)> {
+        if !attrs.iter().any(|attr| attr.path.is_ident("sendrecvfuncs")) {
+            return None;
+        }
+        let funcname_send = Ident::new(&format!("{}_send", name).to_lowercase(), name.span());
+        let funcname_recv = Ident::new(&format!("{}_recv", name).to_lowercase(), name.span());
+        Some((funcname_send, funcname_recv))
+    }
+
+    fn typmod_fns(name: &Ident, attrs: &[syn::Attribute]) -> Option<(Ident, Ident, Ident)> {
+        if !attrs.iter().any(|attr| attr.path.is_ident("typmod_inoutfuncs")) {
+            return None;
+        }
+        let funcname =
+            |suffix: &str| Ident::new(&format!("{}_{}", name, suffix).to_lowercase(), name.span());
+        Some((funcname("typmod_in"), funcname("typmod_out"), funcname("typmod_coerce")))
     }
 
     pub fn from_derive_input(derive_input: DeriveInput) -> Result<Self, syn::Error> {
@@ -85,11 +109,16 @@ impl OgType {
             &format!("{}_out", derive_input.ident).to_lowercase(),
             derive_input.ident.span(),
         );
+        let send_receive_fns =
+            Self::send_receive_fns(&derive_input.ident, derive_input.attrs.as_slice());
+        let typmod_fns = Self::typmod_fns(&derive_input.ident, derive_input.attrs.as_slice());
         Self::new(
             derive_input.ident,
             derive_input.generics,
             funcname_in,
             funcname_out,
+            send_receive_fns,
+            typmod_fns,
             to_sql_config,
         )
     }
@@ -104,7 +133,17 @@ impl Parse for OgType {
             Ident::new(&format!("{}_in", parsed.ident).to_lowercase(), parsed.ident.span());
         let funcname_out =
             Ident::new(&format!("{}_out", parsed.ident).to_lowercase(), parsed.ident.span());
-        Self::new(parsed.ident, parsed.generics, funcname_in, funcname_out, to_sql_config)
+        let send_receive_fns = Self::send_receive_fns(&parsed.ident, parsed.attrs.as_slice());
+        let typmod_fns = Self::typmod_fns(&parsed.ident, parsed.attrs.as_slice());
+        Self::new(
+            parsed.ident,
+            parsed.generics,
+            funcname_in,
+            funcname_out,
+            send_receive_fns,
+            typmod_fns,
+            to_sql_config,
+        )
     }
 }
 
@@ -145,6 +184,20 @@ impl ToTokens for OgType {
 
         let in_fn = &self.in_fn;
         let out_fn = &self.out_fn;
+        let (send_fn, receive_fn) = match &self.send_receive_fns {
+            Some((send_fn, receive_fn)) => {
+                (quote! { Some(stringify!(#send_fn)) }, quote! { Some(stringify!(#receive_fn)) })
+            }
+            None => (quote! { None }, quote! { None }),
+        };
+        let (typmod_in_fn, typmod_out_fn, typmod_coerce_fn) = match &self.typmod_fns {
+            Some((typmod_in_fn, typmod_out_fn, typmod_coerce_fn)) => (
+                quote! { Some(stringify!(#typmod_in_fn)) },
+                quote! { Some(stringify!(#typmod_out_fn)) },
+                quote! { Some(stringify!(#typmod_coerce_fn)) },
+            ),
+            None => (quote! { None }, quote! { None }, quote! { None }),
+        };
 
         let sql_graph_entity_fn_name =
             syn::Ident::new(&format!("__ogx_internals_type_{}", self.name), Span::call_site());
@@ -210,6 +263,11 @@ impl ToTokens for OgType {
                         let _ = path_items.pop(); // Drop the one we don't want.
                         path_items.join("::")
                     },
+                    send_fn: #send_fn,
+                    receive_fn: #receive_fn,
+                    typmod_in_fn: #typmod_in_fn,
+                    typmod_out_fn: #typmod_out_fn,
+                    typmod_coerce_fn: #typmod_coerce_fn,
                     to_sql_config: #to_sql_config,
                 };
                 ::ogx::utils::sql_entity_graph::SqlGraphEntity::Type(submission)
@@ -19,6 +19,7 @@ use eyre::eyre;
 use petgraph::dot::Dot;
 use petgraph::graph::NodeIndex;
 use petgraph::stable_graph::StableGraph;
+use petgraph::visit::{EdgeRef, NodeIndexable};
 use std::any::TypeId;
 use std::collections::HashMap;
 use std::fmt::Debug;
@@ -30,6 +31,7 @@ use crate::sql_entity_graph::control_file::ControlFile;
 use crate::sql_entity_graph::extension_sql::entity::{ExtensionSqlEntity, SqlDeclaredEntity};
 use crate::sql_entity_graph::extension_sql::SqlDeclared;
 use crate::sql_entity_graph::mapping::RustSourceOnlySqlMapping;
+use crate::sql_entity_graph::og_composite::entity::OgCompositeEntity;
 use crate::sql_entity_graph::og_extern::entity::OgExternEntity;
 use crate::sql_entity_graph::og_trigger::entity::OgTriggerEntity;
 use crate::sql_entity_graph::positioning_ref::PositioningRef;
@@ -39,6 +41,7 @@ use crate::sql_entity_graph::og_ord::entity::OgOrdEntity;
 use crate::sql_entity_graph::og_type::entity::OgTypeEntity;
 use crate::sql_entity_graph::schema::entity::SchemaEntity;
 use crate::sql_entity_graph::to_sql::ToSql;
+use crate::sql_entity_graph::upgrade::OgxSqlUpgrade;
 use crate::sql_entity_graph::{SqlGraphEntity, SqlGraphIdentifier};
 
 use super::{OgExternReturnEntity, OgExternReturnEntityIteratedItem};
@@ -80,6 +83,7 @@ pub struct OgxSql {
     pub types: HashMap<OgTypeEntity, NodeIndex>,
     pub builtin_types: HashMap<String, NodeIndex>,
     pub enums: HashMap<OgEnumEntity, NodeIndex>,
+    pub composites: HashMap<OgCompositeEntity, NodeIndex>,
     pub ords: HashMap<OgOrdEntity, NodeIndex>,
     pub hashes: HashMap<OgHashEntity, NodeIndex>,
     pub aggregates: HashMap<OgAggregateEntity, NodeIndex>,
@@ -109,6 +113,7 @@ impl OgxSql {
         let mut externs: Vec<OgExternEntity> = Vec::default();
         let mut types: Vec<OgTypeEntity> = Vec::default();
         let mut enums: Vec<OgEnumEntity> = Vec::default();
+        let mut composites: Vec<OgCompositeEntity> = Vec::default();
         let mut ords: Vec<OgOrdEntity> = Vec::default();
         let mut hashes: Vec<OgHashEntity> = Vec::default();
         let mut aggregates: Vec<OgAggregateEntity> = Vec::default();
@@ -134,6 +139,9 @@ impl OgxSql {
                 SqlGraphEntity::Enum(input_enum) => {
                     enums.push(input_enum);
                 }
+                SqlGraphEntity::Composite(input_composite) => {
+                    composites.push(input_composite);
+                }
                 SqlGraphEntity::Ord(input_ord) => {
                     ords.push(input_ord);
                 }
@@ -165,6 +173,8 @@ impl OgxSql {
         let mapped_schemas = initialize_schemas(&mut graph, bootstrap, finalize, schemas)?;
         let mapped_enums = initialize_enums(&mut graph, root, bootstrap, finalize, enums)?;
         let mapped_types = initialize_types(&mut graph, root, bootstrap, finalize, types)?;
+        let mapped_composites =
+            initialize_composites(&mut graph, root, bootstrap, finalize, composites)?;
         let (mapped_externs, mut mapped_builtin_types) = initialize_externs(
             &mut graph,
             root,
@@ -173,6 +183,7 @@ impl OgxSql {
             externs,
             &mapped_types,
             &mapped_enums,
+            &mapped_composites,
         )?;
         let mapped_ords = initialize_ords(&mut graph, root, bootstrap, finalize, ords)?;
         let mapped_hashes = initialize_hashes(&mut graph, root, bootstrap, finalize, hashes)?;
@@ -196,11 +207,20 @@ impl OgxSql {
             &mapped_schemas,
             &mapped_types,
             &mapped_enums,
+            &mapped_composites,
             &mapped_externs,
             &mapped_triggers,
         )?;
         connect_enums(&mut graph, &mapped_enums, &mapped_schemas);
         connect_types(&mut graph, &mapped_types, &mapped_schemas);
+        connect_composites(
+            &mut graph,
+            &mapped_composites,
+            &mapped_schemas,
+            &mapped_types,
+            &mapped_enums,
+            &mut mapped_builtin_types,
+        );
         connect_externs(
             &mut graph,
             &mapped_externs,
@@ -208,6 +228,7 @@ impl OgxSql {
             &mapped_schemas,
             &mapped_types,
             &mapped_enums,
+            &mapped_composites,
             &mapped_builtin_types,
             &mapped_extension_sqls,
             &mapped_triggers,
@@ -248,6 +269,7 @@ impl OgxSql {
             types: mapped_types,
             builtin_types: mapped_builtin_types,
             enums: mapped_enums,
+            composites: mapped_composites,
             ords: mapped_ords,
             hashes: mapped_hashes,
             aggregates: mapped_aggregates,
@@ -369,6 +391,10 @@ impl OgxSql {
                         "label = \"{}\", penwidth = 0, style = \"filled\", fillcolor = \"#C9A7C8\", weight = 5, shape = \"oval\"",
                         node.dot_identifier()
                     ),
+                    SqlGraphEntity::Composite(_item) => format!(
+                        "label = \"{}\", penwidth = 0, style = \"filled\", fillcolor = \"#C9A7C8\", weight = 5, shape = \"oval\"",
+                        node.dot_identifier()
+                    ),
                     SqlGraphEntity::Ord(_item) => format!(
                         "label = \"{}\", penwidth = 0, style = \"filled\", fillcolor = \"#FFCFD3\", weight = 5, shape = \"diamond\"",
                         node.dot_identifier()
@@ -407,6 +433,173 @@ impl OgxSql {
         Ok(())
     }
 
+    /// Write the entity graph as JSON, for documentation generators and other tools
+    ///
+    /// Entities are listed in the order their SQL is generated, each with the `id`s of the
+    /// entities it depends on.
+    #[instrument(level = "error", err, skip(self))]
+    pub fn to_json(&self, file: impl AsRef<Path> + Debug) -> eyre::Result<()> {
+        use std::fs::{create_dir_all, File};
+        use std::path::Path;
+        let generated = self.json_value()?;
+        let path = Path::new(file.as_ref());
+
+        let parent = path.parent();
+        if let Some(parent) = parent {
+            create_dir_all(parent)?;
+        }
+        let out = File::create(path)?;
+        serde_json::to_writer_pretty(out, &generated)?;
+        Ok(())
+    }
+
+    /// The entity graph [`OgxSql::to_json`] writes
+    pub fn json_value(&self) -> eyre::Result<serde_json::Value> {
+        let mut entities = Vec::new();
+        // operators aren't nodes of the graph, so they're numbered after all of them
+        let mut next_operator_id = self.graph.node_bound();
+        for index in petgraph::algo::toposort(&self.graph, None).map_err(|e| {
+            eyre!("Failed to toposort SQL entities, node with cycle: {:?}", self.graph[e.node_id()])
+        })? {
+            let node = &self.graph[index];
+            let sql = node.to_sql(self)?;
+            let (kind, name, signature) = match node {
+                SqlGraphEntity::ExtensionRoot(_) => {
+                    ("extension", self.extension_name.as_str(), None)
+                }
+                SqlGraphEntity::Schema(item) => ("schema", item.name, None),
+                SqlGraphEntity::CustomSql(item) => ("custom_sql", item.name, None),
+                SqlGraphEntity::Function(item) => {
+                    let arguments = item
+                        .sql_arguments(self)?
+                        .into_iter()
+                        .map(|(arg, sql_type)| format!("{} {}", arg.pattern, sql_type))
+                        .collect::<Vec<_>>();
+                    let signature = format!(
+                        "\"{}\"({}) RETURNS {}",
+                        item.name,
+                        arguments.join(", "),
+                        item.sql_return_type(self)?
+                    );
+                    ("function", item.name, Some(signature))
+                }
+                SqlGraphEntity::Type(item) => ("type", item.name, Some(item.name.to_string())),
+                SqlGraphEntity::BuiltinType(item) => ("builtin_type", item.as_str(), None),
+                SqlGraphEntity::Enum(item) => {
+                    let labels = item
+                        .variants
+                        .iter()
+                        .map(|label| format!("'{}'", label))
+                        .collect::<Vec<_>>();
+                    let signature = format!("{} AS ENUM ({})", item.name, labels.join(", "));
+                    ("enum", item.name, Some(signature))
+                }
+                SqlGraphEntity::Composite(item) => {
+                    let fields = item
+                        .sql_fields(self)?
+                        .into_iter()
+                        .map(|(field, sql_type)| format!("\"{}\" {}", field.name, sql_type))
+                        .collect::<Vec<_>>();
+                    let signature = format!("{} AS ({})", item.name, fields.join(", "));
+                    ("composite", item.name, Some(signature))
+                }
+                SqlGraphEntity::Ord(item) => ("ord", item.name, None),
+                SqlGraphEntity::Hash(item) => ("hash", item.name, None),
+                SqlGraphEntity::Aggregate(item) => {
+                    let signature = format!("{}({})", item.name, item.sql_arguments(self)?);
+                    ("aggregate", item.name, Some(signature))
+                }
+                SqlGraphEntity::Trigger(item) => {
+                    let returns = if item.event { "EVENT_TRIGGER" } else { "TRIGGER" };
+                    let signature = format!("\"{}\"() RETURNS {}", item.function_name, returns);
+                    ("trigger", item.function_name, Some(signature))
+                }
+            };
+            let schema = match node {
+                SqlGraphEntity::Function(item) if item.schema.is_some() => {
+                    item.schema.map(String::from)
+                }
+                SqlGraphEntity::ExtensionRoot(_) | SqlGraphEntity::BuiltinType(_) => None,
+                _ => self.schema_alias_of(&index),
+            };
+            let depends_on = self
+                .graph
+                .edges_directed(index, petgraph::Direction::Incoming)
+                .map(|edge| {
+                    serde_json::json!({
+                        "id": edge.source().index(),
+                        "relationship": match edge.weight() {
+                            SqlGraphRelationship::RequiredBy => "required_by",
+                            SqlGraphRelationship::RequiredByArg => "required_by_arg",
+                            SqlGraphRelationship::RequiredByReturn => "required_by_return",
+                        },
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            let mut entity = serde_json::json!({
+                "id": index.index(),
+                "kind": kind,
+                "name": name,
+                "schema": schema,
+                "rust_path": node.rust_identifier(),
+                "file": node.file(),
+                "line": node.line(),
+                "signature": signature,
+                "sql": sql,
+                "depends_on": depends_on,
+            });
+            if let SqlGraphEntity::Function(item) = node {
+                entity["cast"] = serde_json::to_value(&item.cast)?;
+            }
+            entities.push(entity);
+
+            // an operator is created along with its function, and listed right after it
+            if let SqlGraphEntity::Function(item) = node {
+                if let Some(op) = &item.operator {
+                    let opname = op
+                        .opname
+                        .ok_or_else(|| eyre!("Operator `{}` has no `#[opname]`", item.name))?;
+                    let arguments = item
+                        .sql_arguments(self)?
+                        .into_iter()
+                        .map(|(_, sql_type)| sql_type)
+                        .collect::<Vec<_>>();
+                    entities.push(serde_json::json!({
+                        "id": next_operator_id,
+                        "kind": "operator",
+                        "name": opname,
+                        "schema": schema,
+                        "rust_path": node.rust_identifier(),
+                        "file": node.file(),
+                        "line": node.line(),
+                        "signature": format!(
+                            "{}({}) RETURNS {}",
+                            opname,
+                            arguments.join(", "),
+                            item.sql_return_type(self)?
+                        ),
+                        "sql": item.operator_sql(self, index, op)?,
+                        "depends_on": [{ "id": index.index(), "relationship": "required_by" }],
+                        "commutator": op.commutator,
+                        "negator": op.negator,
+                        "restrict": op.restrict,
+                        "join": op.join,
+                        "hashes": op.hashes,
+                        "merges": op.merges,
+                    }));
+                    next_operator_id += 1;
+                }
+            }
+        }
+
+        Ok(serde_json::json!({
+            "extension": self.extension_name,
+            "version": self.control.default_version,
+            "entities": entities,
+        }))
+    }
+
     pub fn schema_alias_of(&self, item_index: &NodeIndex) -> Option<String> {
         self.graph
             .neighbors_undirected(*item_index)
@@ -448,6 +641,12 @@ impl OgxSql {
         Ok(full_sql)
     }
 
+    /// The upgrade script from the extension as it was in `from` to this version of it
+    #[instrument(level = "error", skip_all)]
+    pub fn upgrade_from(&self, from: &OgxSql) -> eyre::Result<OgxSqlUpgrade> {
+        OgxSqlUpgrade::new(from, self)
+    }
+
     pub fn has_sql_declared_entity(&self, identifier: &SqlDeclared) -> Option<&SqlDeclaredEntity> {
         self.extension_sqls.iter().find_map(|(item, _index)| {
             let retval = item.creates.iter().find_map(|create_entity| {
@@ -553,6 +752,7 @@ pub fn find_positioning_ref_target<'a>(
     positioning_ref: &'a PositioningRef,
     types: &'a HashMap<OgTypeEntity, NodeIndex>,
     enums: &'a HashMap<OgEnumEntity, NodeIndex>,
+    composites: &'a HashMap<OgCompositeEntity, NodeIndex>,
     externs: &'a HashMap<OgExternEntity, NodeIndex>,
     schemas: &'a HashMap<SchemaEntity, NodeIndex>,
     extension_sqls: &'a HashMap<ExtensionSqlEntity, NodeIndex>,
@@ -576,6 +776,11 @@ pub fn find_positioning_ref_target<'a>(
                     return Some(&other_index);
                 }
             }
+            for (other, other_index) in composites {
+                if last_segment == &other.name && other.module_path.ends_with(&module_path) {
+                    return Some(&other_index);
+                }
+            }
             for (other, other_index) in externs {
                 if *last_segment == other.unaliased_name
                     && other.module_path.ends_with(&module_path)
@@ -614,6 +819,7 @@ fn connect_extension_sqls(
     schemas: &HashMap<SchemaEntity, NodeIndex>,
     types: &HashMap<OgTypeEntity, NodeIndex>,
     enums: &HashMap<OgEnumEntity, NodeIndex>,
+    composites: &HashMap<OgCompositeEntity, NodeIndex>,
     externs: &HashMap<OgExternEntity, NodeIndex>,
     triggers: &HashMap<OgTriggerEntity, NodeIndex>,
 ) -> eyre::Result<()> {
@@ -632,6 +838,7 @@ fn connect_extension_sqls(
                 requires,
                 types,
                 enums,
+                composites,
                 externs,
                 schemas,
                 extension_sqls,
@@ -764,6 +971,78 @@ fn connect_types(
     }
 }
 
+#[tracing::instrument(level = "error", skip_all)]
+fn initialize_composites(
+    graph: &mut StableGraph<SqlGraphEntity, SqlGraphRelationship>,
+    root: NodeIndex,
+    bootstrap: Option<NodeIndex>,
+    finalize: Option<NodeIndex>,
+    composites: Vec<OgCompositeEntity>,
+) -> eyre::Result<HashMap<OgCompositeEntity, NodeIndex>> {
+    let mut mapped_composites = HashMap::default();
+    for item in composites {
+        let entity: SqlGraphEntity = item.clone().into();
+        let index = graph.add_node(entity);
+        mapped_composites.insert(item, index);
+        build_base_edges(graph, index, root, bootstrap, finalize);
+    }
+    Ok(mapped_composites)
+}
+
+#[tracing::instrument(level = "error", skip_all)]
+fn connect_composites(
+    graph: &mut StableGraph<SqlGraphEntity, SqlGraphRelationship>,
+    composites: &HashMap<OgCompositeEntity, NodeIndex>,
+    schemas: &HashMap<SchemaEntity, NodeIndex>,
+    types: &HashMap<OgTypeEntity, NodeIndex>,
+    enums: &HashMap<OgEnumEntity, NodeIndex>,
+    builtin_types: &mut HashMap<String, NodeIndex>,
+) {
+    for (item, &index) in composites {
+        make_schema_connection(
+            graph,
+            "Composite",
+            index,
+            &item.rust_identifier(),
+            item.module_path,
+            schemas,
+        );
+
+        for field in &item.fields {
+            let mut found = make_type_or_enum_connection(
+                graph,
+                "Composite",
+                index,
+                &item.rust_identifier(),
+                &field.used_ty.ty_id,
+                types,
+                enums,
+            );
+            if !found {
+                for (other_item, &other_index) in composites {
+                    if other_item.id_matches(&field.used_ty.ty_id) {
+                        tracing::debug!(from = %item.rust_identifier(), to = %other_item.rust_identifier(), "Adding Composite after Composite (due to field) edge");
+                        graph.add_edge(other_index, index, SqlGraphRelationship::RequiredBy);
+                        found = true;
+                        break;
+                    }
+                }
+            }
+            if !found {
+                let builtin_index = *builtin_types
+                    .entry(field.used_ty.full_path.to_string())
+                    .or_insert_with(|| {
+                        graph.add_node(SqlGraphEntity::BuiltinType(
+                            field.used_ty.full_path.to_string(),
+                        ))
+                    });
+                tracing::debug!(from = %item.rust_identifier(), to = field.used_ty.full_path, "Adding Composite after BuiltIn Type (due to field) edge");
+                graph.add_edge(builtin_index, index, SqlGraphRelationship::RequiredBy);
+            }
+        }
+    }
+}
+
 #[tracing::instrument(level = "error", skip_all)]
 fn initialize_externs(
     graph: &mut StableGraph<SqlGraphEntity, SqlGraphRelationship>,
@@ -773,6 +1052,7 @@ fn initialize_externs(
     externs: Vec<OgExternEntity>,
     mapped_types: &HashMap<OgTypeEntity, NodeIndex>,
     mapped_enums: &HashMap<OgEnumEntity, NodeIndex>,
+    mapped_composites: &HashMap<OgCompositeEntity, NodeIndex>,
 ) -> eyre::Result<(HashMap<OgExternEntity, NodeIndex>, HashMap<String, NodeIndex>)> {
     let mut mapped_externs = HashMap::default();
     let mut mapped_builtin_types = HashMap::default();
@@ -796,6 +1076,12 @@ fn initialize_externs(
                     break;
                 }
             }
+            for (ty_item, &_ty_index) in mapped_composites {
+                if ty_item.id_matches(&arg.used_ty.ty_id) {
+                    found = true;
+                    break;
+                }
+            }
             if !found {
                 mapped_builtin_types.entry(arg.used_ty.full_path.to_string()).or_insert_with(
                     || {
@@ -823,6 +1109,12 @@ fn initialize_externs(
                         break;
                     }
                 }
+                for (ty_item, &_ty_index) in mapped_composites {
+                    if ty_item.id_matches(&ty.ty_id) {
+                        found = true;
+                        break;
+                    }
+                }
                 if !found {
                     mapped_builtin_types.entry(ty.full_path.to_string()).or_insert_with(|| {
                         graph.add_node(SqlGraphEntity::BuiltinType(ty.full_path.to_string()))
@@ -846,6 +1138,12 @@ fn initialize_externs(
                             break;
                         }
                     }
+                    for (ty_item, &_ty_index) in mapped_composites {
+                        if ty_item.id_matches(&return_ty_entity.ty_id) {
+                            found = true;
+                            break;
+                        }
+                    }
                     if !found {
                         mapped_builtin_types
                             .entry(return_ty_entity.ty_source.to_string())
@@ -870,6 +1168,7 @@ fn connect_externs(
     schemas: &HashMap<SchemaEntity, NodeIndex>,
     types: &HashMap<OgTypeEntity, NodeIndex>,
     enums: &HashMap<OgEnumEntity, NodeIndex>,
+    composites: &HashMap<OgCompositeEntity, NodeIndex>,
     builtin_types: &HashMap<String, NodeIndex>,
     extension_sqls: &HashMap<ExtensionSqlEntity, NodeIndex>,
     triggers: &HashMap<OgTriggerEntity, NodeIndex>,
@@ -884,6 +1183,7 @@ fn connect_externs(
                             requires,
                             types,
                             enums,
+                            composites,
                             externs,
                             schemas,
                             extension_sqls,
@@ -933,6 +1233,16 @@ fn connect_externs(
             }
         }
 
+        // The typmod_in/typmod_out functions don't mention their type, but are emitted with it.
+        for (ty_item, &ty_index) in types {
+            if let Some((typmod_in_fn_path, typmod_out_fn_path, _)) = ty_item.typmod_fn_paths() {
+                if item.full_path == typmod_in_fn_path || item.full_path == typmod_out_fn_path {
+                    tracing::debug!(from = %item.rust_identifier(), to = %ty_item.rust_identifier(), "Adding Extern after Type (due to typmod function) edge");
+                    graph.add_edge(ty_index, index, SqlGraphRelationship::RequiredBy);
+                }
+            }
+        }
+
         for arg in &item.fn_args {
             let mut found = false;
 
@@ -954,6 +1264,16 @@ fn connect_externs(
                     }
                 }
             }
+            if !found {
+                for (composite_item, &composite_index) in composites {
+                    if composite_item.id_matches(&arg.used_ty.ty_id) {
+                        tracing::debug!(from = %item.rust_identifier(), to = %composite_item.rust_identifier(), "Adding Extern after Composite (due to argument) edge");
+                        graph.add_edge(composite_index, index, SqlGraphRelationship::RequiredByArg);
+                        found = true;
+                        break;
+                    }
+                }
+            }
             if !found {
                 let builtin_index = builtin_types
                     .get(arg.used_ty.full_path)
@@ -999,6 +1319,16 @@ fn connect_externs(
                         }
                     }
                 }
+                if !found {
+                    for (ty_item, &ty_index) in composites {
+                        if ty_item.id_matches(&ty.ty_id) {
+                            tracing::debug!(from = %item.rust_identifier(), to = %ty_item.rust_identifier(), "Adding Extern after Composite (due to return) edge");
+                            graph.add_edge(ty_index, index, SqlGraphRelationship::RequiredByReturn);
+                            found = true;
+                            break;
+                        }
+                    }
+                }
                 if !found {
                     let builtin_index = builtin_types
                         .get(&ty.full_path.to_string())
@@ -1047,6 +1377,20 @@ fn connect_externs(
                             }
                         }
                     }
+                    if !found {
+                        for (ty_item, &ty_index) in composites {
+                            if ty_item.id_matches(&type_entity.ty_id) {
+                                tracing::debug!(from = %item.rust_identifier(), to = %ty_item.rust_identifier(), "Adding Extern after Composite (due to return) edge");
+                                graph.add_edge(
+                                    ty_index,
+                                    index,
+                                    SqlGraphRelationship::RequiredByReturn,
+                                );
+                                found = true;
+                                break;
+                            }
+                        }
+                    }
                     if !found {
                         let builtin_index =
                             builtin_types.get(&type_entity.ty_source.to_string()).expect(&format!(
@@ -1586,3 +1930,90 @@ fn make_type_or_enum_connection(
 
     found
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::sql_entity_graph::upgrade::tests::{bounded_text, build, color, function, used_ty};
+    use crate::sql_entity_graph::{
+        OgOperatorEntity, OgTriggerEntity, SqlGraphEntity, ToSqlConfigEntity,
+    };
+    use std::collections::HashSet;
+
+    #[test]
+    fn json_signatures() {
+        let int_args = || vec![("a", used_ty::<i32>()), ("b", used_ty::<i32>())];
+        let mut eq = function("int_eq", int_args(), used_ty::<bool>(), true);
+        if let SqlGraphEntity::Function(item) = &mut eq {
+            item.operator = Some(OgOperatorEntity {
+                opname: Some("==="),
+                commutator: Some("==="),
+                negator: None,
+                restrict: None,
+                join: None,
+                hashes: false,
+                merges: false,
+            });
+        }
+        let add = function("add", int_args(), used_ty::<i32>(), false);
+        let trigger = |function_name, event| {
+            SqlGraphEntity::Trigger(OgTriggerEntity {
+                function_name,
+                to_sql_config: ToSqlConfigEntity { enabled: true, callback: None, content: None },
+                file: "src/lib.rs",
+                line: 1,
+                module_path: "ext",
+                full_path: function_name,
+                event,
+            })
+        };
+        let json = build(vec![
+            add,
+            eq,
+            color(vec!["Red", "Blue"]),
+            trigger("audit", false),
+            trigger("on_ddl", true),
+        ])
+        .json_value()
+        .unwrap();
+
+        let entities = json["entities"].as_array().unwrap();
+        let ids =
+            entities.iter().map(|entity| entity["id"].as_u64().unwrap()).collect::<HashSet<_>>();
+        assert_eq!(ids.len(), entities.len());
+        let find = |kind: &str, name: &str| {
+            entities.iter().find(|entity| entity["kind"] == kind && entity["name"] == name).unwrap()
+        };
+
+        assert_eq!(find("function", "add")["signature"], "\"add\"(a INT, b INT) RETURNS INT");
+        assert_eq!(find("enum", "Color")["signature"], "Color AS ENUM ('Red', 'Blue')");
+        assert_eq!(find("trigger", "audit")["signature"], "\"audit\"() RETURNS TRIGGER");
+        assert_eq!(find("trigger", "on_ddl")["signature"], "\"on_ddl\"() RETURNS EVENT_TRIGGER");
+
+        let function = find("function", "int_eq");
+        assert_eq!(function["signature"], "\"int_eq\"(a INT, b INT) RETURNS bool");
+        assert!(function.get("operator").is_none());
+        let operator = find("operator", "===");
+        assert_eq!(operator["signature"], "===(INT, INT) RETURNS bool");
+        assert_eq!(operator["commutator"], "===");
+        assert_eq!(operator["depends_on"][0]["id"], function["id"]);
+        assert!(operator["sql"].as_str().unwrap().contains("CREATE OPERATOR ==="));
+    }
+
+    #[test]
+    fn typmod_type_sql() {
+        let sql = build(bounded_text(true)).to_sql().unwrap();
+        assert!(sql.contains("CREATE  FUNCTION \"boundedtext_typmod_in\"("));
+        assert!(sql.contains("CREATE  FUNCTION \"boundedtext_typmod_out\"("));
+        assert!(
+            sql.contains("\tTYPMOD_IN = boundedtext_typmod_in, /* ext::boundedtext_typmod_in */\n")
+        );
+        assert!(sql.contains(
+            "\tTYPMOD_OUT = boundedtext_typmod_out, /* ext::boundedtext_typmod_out */\n"
+        ));
+        assert!(sql.contains(
+            "CREATE CAST (BoundedText AS BoundedText)\n\tWITH FUNCTION boundedtext_coerce(BoundedText, integer, boolean)"
+        ));
+        // they are only emitted along with the type
+        assert_eq!(sql.matches("FUNCTION \"boundedtext_typmod_in\"(").count(), 1);
+    }
+}
@@ -71,6 +71,15 @@ impl ToTokens for Schema {
         // End of hack
 
         let mut updated_content = content_items.clone();
+        // the composites' `IntoDatum` needs their schema to find them whatever the `search_path`
+        let schema_name = syn::LitStr::new(&ident.to_string(), ident.span());
+        for item in updated_content.iter_mut() {
+            if let syn::Item::Struct(item_struct) = item {
+                if derives_composite(&item_struct.attrs) {
+                    item_struct.attrs.push(syn::parse_quote! { #[ogx_schema = #schema_name] });
+                }
+            }
+        }
         let sql_graph_entity_fn_name = syn::Ident::new(
             &format!("__ogx_internals_schema_{}_{}", ident, postfix),
             Span::call_site(),
@@ -102,3 +111,16 @@ impl ToTokens for Schema {
         tokens.append_all(inv);
     }
 }
+
+/// Whether `attrs` has a `#[derive(OgComposite)]`
+fn derives_composite(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().filter(|attr| attr.path.is_ident("derive")).any(|attr| match attr.parse_meta() {
+        Ok(syn::Meta::List(list)) => list.nested.iter().any(|nested| match nested {
+            syn::NestedMeta::Meta(syn::Meta::Path(path)) => {
+                matches!(path.segments.last(), Some(segment) if segment.ident == "OgComposite")
+            }
+            _ => false,
+        }),
+        _ => false,
+    })
+}
@@ -0,0 +1,934 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+/*!
+
+Extension upgrade script generation, by diffing the entity graphs of two versions of an extension
+
+> Like all of the [`sql_entity_graph`][crate::sql_entity_graph] APIs, this is considered **internal**
+to the `ogx` framework and very subject to change between versions. While you may use this, please do it with caution.
+
+*/
+use crate::sql_entity_graph::{OgExternEntity, OgxSql, SqlGraphEntity, ToSql};
+
+use petgraph::stable_graph::NodeIndex;
+use petgraph::Direction;
+use std::collections::{HashMap, HashSet};
+
+/// An upgrade script from one version of an extension's SQL to another, as produced by
+/// [`OgxSql::upgrade_from`](crate::sql_entity_graph::OgxSql::upgrade_from).
+///
+/// Entities are matched between the versions by their SQL identity, for example a function's
+/// schema, name and argument types, so a renamed entity is seen as one being dropped and another
+/// created.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OgxSqlUpgrade {
+    /// The statements of the upgrade script: the `DROP`s, in reverse dependency order, followed by
+    /// the `CREATE`s in dependency order
+    pub statements: Vec<String>,
+    /// The changes which are destructive, or which could not be generated and are left commented
+    /// out in the script or must be run by hand.  These need a manual review before the script is
+    /// used
+    pub needs_review: Vec<String>,
+}
+
+impl OgxSqlUpgrade {
+    /// Diff the entity graphs of two versions of an extension
+    pub fn new(from: &OgxSql, to: &OgxSql) -> eyre::Result<Self> {
+        let from = Version::new(from)?;
+        let to = Version::new(to)?;
+
+        let mut changes = HashMap::new();
+        for key in &to.order {
+            if let Some(old) = from.entities.get(key) {
+                let change =
+                    Change::between(key, old, from.context, &to.entities[key], to.context)?;
+                changes.insert(key.clone(), change);
+            }
+        }
+
+        // Dropping an entity fails while anything still depends on it, so whatever is kept and
+        // depends on a dropped entity is dropped and recreated along with it.  If one of those
+        // can't be, because it holds data or is custom SQL, the entity is left to be migrated by
+        // hand instead
+        let mut blocked_by = HashMap::new();
+        let mut recreated_with = HashMap::new();
+        for key in &from.order {
+            let removed = !to.entities.contains_key(key);
+            if !removed && !matches!(changes[key], Change::Recreate) {
+                continue;
+            }
+            let dependents = from
+                .dependents(key)
+                .into_iter()
+                .filter(|dependent| to.entities.contains_key(dependent))
+                .collect::<Vec<_>>();
+            match dependents.iter().find(|dependent| !from.entities[*dependent].is_droppable()) {
+                Some(dependent) => {
+                    blocked_by.insert(key.clone(), dependent.clone());
+                    if !removed {
+                        changes.insert(
+                            key.clone(),
+                            Change::Manual(format!(
+                                "changed and must be dropped and recreated, but {} depends on it",
+                                dependent
+                            )),
+                        );
+                    }
+                }
+                None => {
+                    for dependent in dependents {
+                        recreated_with.entry(dependent).or_insert_with(|| key.clone());
+                    }
+                }
+            }
+        }
+        // only those which didn't change themselves are recreated for the sake of another
+        recreated_with.retain(|dependent, _| match changes.get_mut(dependent) {
+            Some(change @ (Change::None | Change::Replace)) => {
+                *change = Change::Recreate;
+                true
+            }
+            _ => false,
+        });
+
+        let mut upgrade = OgxSqlUpgrade::default();
+        for key in from.order.iter().rev() {
+            let old = &from.entities[key];
+            let removed = !to.entities.contains_key(key);
+            if !removed && !matches!(changes[key], Change::Recreate) {
+                continue;
+            }
+
+            let drops = old.drops(key, from.context)?;
+            if let Some(dependent) = blocked_by.get(key) {
+                upgrade.needs_review.push(format!(
+                    "{} was removed, but {} depends on it, so it must be dropped by hand",
+                    key, dependent
+                ));
+                upgrade.statements.extend(drops.iter().map(|drop| commented(key, drop)));
+                continue;
+            }
+            if let SqlGraphEntity::Type(_) = old.item {
+                upgrade.needs_review.push(format!(
+                    "{} was removed, but dropping it also drops every column of that type, and the data in them, so it must be dropped by hand",
+                    key
+                ));
+                upgrade.statements.extend(drops.iter().map(|drop| commented(key, drop)));
+                continue;
+            }
+            if drops.is_empty() {
+                upgrade
+                    .needs_review
+                    .push(format!("{} was removed, and must be undone by hand:\n{}", key, old.sql));
+                continue;
+            }
+
+            upgrade.needs_review.push(match recreated_with.get(key) {
+                _ if removed => format!("{} was removed", key),
+                Some(dependency) => {
+                    format!(
+                        "{} must be dropped and recreated, as it depends on {}",
+                        key, dependency
+                    )
+                }
+                _ => format!("{} changed and must be dropped and recreated", key),
+            });
+            upgrade.statements.extend(drops);
+        }
+
+        for key in &to.order {
+            let new = &to.entities[key];
+            match changes.get(key) {
+                None | Some(Change::Recreate) => upgrade.statements.push(new.sql.clone()),
+                Some(Change::None) => (),
+                Some(Change::Replace) => {
+                    upgrade.statements.push(or_replace(&new.function_sql(to.context)?))
+                }
+                Some(Change::AddLabels(alters)) => upgrade.needs_review.push(format!(
+                    "{} gained labels, which can't be added in the transaction the upgrade runs in, so add them by hand first:\n{}",
+                    key,
+                    alters.join("\n")
+                )),
+                Some(Change::Manual(reason)) => {
+                    upgrade.needs_review.push(format!("{} {}", key, reason));
+                    upgrade.statements.push(commented(key, &new.sql));
+                }
+            }
+        }
+
+        Ok(upgrade)
+    }
+
+    /// `true` if nothing changed between the two versions
+    pub fn is_empty(&self) -> bool {
+        self.statements.is_empty() && self.needs_review.is_empty()
+    }
+
+    /// The upgrade script, headed by a comment listing the changes that need a review
+    pub fn to_sql(&self) -> String {
+        let mut sql = String::from("/*\nThis file is auto generated by ogx.\n");
+        if !self.needs_review.is_empty() {
+            sql.push_str("\nThese changes are destructive, or couldn't be generated, and must be reviewed:\n");
+            for item in &self.needs_review {
+                sql.push_str(&format!("  * {}\n", item.replace("*/", "* /")));
+            }
+        }
+        sql.push_str("*/\n");
+        for statement in &self.statements {
+            sql.push('\n');
+            sql.push_str(statement);
+            sql.push('\n');
+        }
+        sql
+    }
+}
+
+/// The kinds of SQL object an entity creates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Kind {
+    Schema,
+    CustomSql,
+    Function,
+    Type,
+    Aggregate,
+    OperatorFamily,
+}
+
+/// What identifies an entity between versions, built from its metadata: its SQL identity, as its
+/// `DROP` statement names it
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct EntityKey {
+    kind: Kind,
+    identity: String,
+}
+
+impl EntityKey {
+    fn of(context: &OgxSql, index: NodeIndex) -> eyre::Result<Option<Self>> {
+        let schema_prefix = context.schema_prefix_for(&index);
+        let (kind, identity) = match &context.graph[index] {
+            SqlGraphEntity::ExtensionRoot(_) | SqlGraphEntity::BuiltinType(_) => return Ok(None),
+            SqlGraphEntity::Schema(item) => (Kind::Schema, item.name.to_string()),
+            SqlGraphEntity::CustomSql(item) => (Kind::CustomSql, item.name.to_string()),
+            SqlGraphEntity::Function(item) => {
+                let schema_prefix =
+                    item.schema.map(|schema| format!("{}.", schema)).unwrap_or(schema_prefix);
+                let argument_types = item
+                    .sql_arguments(context)?
+                    .into_iter()
+                    .map(|(_, sql_type)| sql_type)
+                    .collect::<Vec<_>>();
+                (
+                    Kind::Function,
+                    format!("{}\"{}\"({})", schema_prefix, item.name, argument_types.join(", ")),
+                )
+            }
+            SqlGraphEntity::Trigger(item) => {
+                (Kind::Function, format!("{}\"{}\"()", schema_prefix, item.function_name))
+            }
+            SqlGraphEntity::Type(item) => (Kind::Type, format!("{}{}", schema_prefix, item.name)),
+            SqlGraphEntity::Enum(item) => (Kind::Type, format!("{}{}", schema_prefix, item.name)),
+            SqlGraphEntity::Composite(item) => {
+                (Kind::Type, format!("{}{}", schema_prefix, item.name))
+            }
+            SqlGraphEntity::Ord(item) => {
+                (Kind::OperatorFamily, format!("{}_btree_ops USING btree", item.name))
+            }
+            SqlGraphEntity::Hash(item) => {
+                (Kind::OperatorFamily, format!("{}_hash_ops USING hash", item.name))
+            }
+            SqlGraphEntity::Aggregate(item) => (
+                Kind::Aggregate,
+                format!("{}{}({})", schema_prefix, item.name, item.sql_arguments(context)?),
+            ),
+        };
+        Ok(Some(EntityKey { kind, identity }))
+    }
+}
+
+impl std::fmt::Display for EntityKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self.kind {
+            Kind::Schema => "schema",
+            Kind::CustomSql => "custom SQL",
+            Kind::Function => "function",
+            Kind::Type => "type",
+            Kind::Aggregate => "aggregate",
+            Kind::OperatorFamily => "operator family",
+        };
+        write!(f, "{} {}", kind, self.identity)
+    }
+}
+
+/// An entity of one version of the extension
+struct Entity<'a> {
+    index: NodeIndex,
+    item: &'a SqlGraphEntity,
+    sql: String,
+    /// Its SQL without comments or insignificant whitespace, so an entity which only moved in its
+    /// source file compares the same
+    normalized: String,
+}
+
+impl Entity<'_> {
+    /// Whether the entity can be dropped and recreated without losing anything.  Types can hold
+    /// data, and custom SQL can't be undone automatically
+    fn is_droppable(&self) -> bool {
+        matches!(
+            self.item,
+            SqlGraphEntity::Function(_)
+                | SqlGraphEntity::Trigger(_)
+                | SqlGraphEntity::Aggregate(_)
+                | SqlGraphEntity::Ord(_)
+                | SqlGraphEntity::Hash(_)
+        )
+    }
+
+    /// The statements dropping everything the entity created, or none if it can't be dropped
+    fn drops(&self, key: &EntityKey, context: &OgxSql) -> eyre::Result<Vec<String>> {
+        let mut drops = Vec::new();
+        match self.item {
+            SqlGraphEntity::CustomSql(_) => return Ok(drops),
+            SqlGraphEntity::Function(item) => {
+                let argument_types = item
+                    .sql_arguments(context)?
+                    .into_iter()
+                    .map(|(_, sql_type)| sql_type)
+                    .collect::<Vec<_>>();
+                if item.cast.is_some() {
+                    let source = argument_types.first().cloned().unwrap_or_default();
+                    let target = item.sql_return_type(context)?;
+                    drops.push(format!("DROP CAST ({} AS {});", source, target));
+                }
+                if let Some(opname) = item.operator.as_ref().and_then(|op| op.opname) {
+                    drops.push(format!(
+                        "DROP OPERATOR {} ({});",
+                        opname,
+                        argument_types.join(", ")
+                    ));
+                }
+                drops.push(format!("DROP FUNCTION {};", key.identity));
+            }
+            // its input and output functions go with it, as do the columns of that type
+            SqlGraphEntity::Type(_) => drops.push(format!("DROP TYPE {} CASCADE;", key.identity)),
+            SqlGraphEntity::Enum(_) | SqlGraphEntity::Composite(_) => {
+                drops.push(format!("DROP TYPE {};", key.identity))
+            }
+            SqlGraphEntity::Trigger(_) => drops.push(format!("DROP FUNCTION {};", key.identity)),
+            SqlGraphEntity::Aggregate(_) => drops.push(format!("DROP AGGREGATE {};", key.identity)),
+            SqlGraphEntity::Ord(_) | SqlGraphEntity::Hash(_) => {
+                drops.push(format!("DROP OPERATOR FAMILY {};", key.identity))
+            }
+            SqlGraphEntity::Schema(_) => drops.push(format!("DROP SCHEMA {};", key.identity)),
+            SqlGraphEntity::ExtensionRoot(_) | SqlGraphEntity::BuiltinType(_) => (),
+        }
+        Ok(drops)
+    }
+
+    /// A function's `CREATE FUNCTION` alone, without its operator or cast
+    fn function_sql(&self, context: &OgxSql) -> eyre::Result<String> {
+        match self.item {
+            SqlGraphEntity::Function(item) => {
+                let mut function = item.clone();
+                function.operator = None;
+                function.cast = None;
+                Ok(function.to_sql(context)?.trim().to_string())
+            }
+            _ => Ok(self.sql.clone()),
+        }
+    }
+}
+
+/// The entities of one version of the extension, by their key
+struct Version<'a> {
+    context: &'a OgxSql,
+    /// The keys, in dependency order
+    order: Vec<EntityKey>,
+    entities: HashMap<EntityKey, Entity<'a>>,
+    keys: HashMap<NodeIndex, EntityKey>,
+}
+
+impl<'a> Version<'a> {
+    fn new(context: &'a OgxSql) -> eyre::Result<Self> {
+        let mut version =
+            Version { context, order: Vec::new(), entities: HashMap::new(), keys: HashMap::new() };
+        for index in petgraph::algo::toposort(&context.graph, None).map_err(|e| {
+            eyre::eyre!(
+                "Failed to toposort SQL entities, node with cycle: {:?}",
+                context.graph[e.node_id()]
+            )
+        })? {
+            let key = match EntityKey::of(context, index)? {
+                Some(key) => key,
+                None => continue,
+            };
+            let item = &context.graph[index];
+            let sql = item.to_sql(context)?;
+            let normalized = normalize(&sql);
+            // such as the functions of a type, which the type's SQL creates
+            if normalized.is_empty() || version.entities.contains_key(&key) {
+                continue;
+            }
+            version.order.push(key.clone());
+            version.keys.insert(index, key.clone());
+            version
+                .entities
+                .insert(key, Entity { index, item, sql: sql.trim().to_string(), normalized });
+        }
+        Ok(version)
+    }
+
+    /// Everything which depends on the entity, directly or not, in no particular order.  The
+    /// `finalize` custom SQL depends on every entity only to run last, so it doesn't count
+    fn dependents(&self, key: &EntityKey) -> Vec<EntityKey> {
+        let mut dependents = Vec::new();
+        let mut seen = HashSet::new();
+        let mut pending = vec![self.entities[key].index];
+        while let Some(index) = pending.pop() {
+            for dependent in self.context.graph.neighbors_directed(index, Direction::Outgoing) {
+                if Some(dependent) == self.context.graph_finalize || !seen.insert(dependent) {
+                    continue;
+                }
+                dependents.extend(self.keys.get(&dependent).cloned());
+                pending.push(dependent);
+            }
+        }
+        dependents
+    }
+}
+
+enum Change {
+    None,
+    /// `CREATE OR REPLACE` the function
+    Replace,
+    /// `DROP` the old entity, then `CREATE` the new one
+    Recreate,
+    /// Labels were added to the enum, by these `ALTER TYPE ... ADD VALUE`s
+    AddLabels(Vec<String>),
+    /// Leave the new definition commented out for a person to deal with, for this reason
+    Manual(String),
+}
+
+impl Change {
+    fn between(
+        key: &EntityKey,
+        old: &Entity,
+        old_context: &OgxSql,
+        new: &Entity,
+        new_context: &OgxSql,
+    ) -> eyre::Result<Self> {
+        if old.normalized == new.normalized {
+            return Ok(Change::None);
+        }
+        let change = match (old.item, new.item) {
+            (SqlGraphEntity::Schema(_), SqlGraphEntity::Schema(_)) => Change::None,
+            (SqlGraphEntity::Function(old_item), SqlGraphEntity::Function(new_item)) => {
+                // the operator or cast using the function isn't replaced along with it, and SQL
+                // given in place of the generated SQL may not be a `CREATE FUNCTION`
+                let overridden = |item: &OgExternEntity| {
+                    item.to_sql_config.callback.is_some() || item.to_sql_config.content.is_some()
+                };
+                if FunctionSignature::of(old_item, old_context)?
+                    == FunctionSignature::of(new_item, new_context)?
+                    && old_item.operator == new_item.operator
+                    && old_item.cast == new_item.cast
+                    && !overridden(old_item)
+                    && !overridden(new_item)
+                {
+                    Change::Replace
+                } else {
+                    Change::Recreate
+                }
+            }
+            (SqlGraphEntity::Trigger(old_item), SqlGraphEntity::Trigger(new_item))
+                if old_item.event == new_item.event =>
+            {
+                Change::Replace
+            }
+            (SqlGraphEntity::Enum(old_item), SqlGraphEntity::Enum(new_item)) => {
+                match add_enum_values(&key.identity, &old_item.variants, &new_item.variants) {
+                    Some(alters) => Change::AddLabels(alters),
+                    None => Change::Manual(String::from(
+                        "changed in a way that can't be upgraded automatically",
+                    )),
+                }
+            }
+            _ if old.is_droppable() && new.is_droppable() => Change::Recreate,
+            _ => Change::Manual(String::from(
+                "changed in a way that can't be upgraded automatically",
+            )),
+        };
+        Ok(change)
+    }
+}
+
+/// What `CREATE OR REPLACE FUNCTION` can't change: the names, types and defaults of the
+/// arguments, and the return type
+#[derive(PartialEq, Eq)]
+struct FunctionSignature {
+    arguments: Vec<(&'static str, String, Option<&'static str>)>,
+    returns: String,
+}
+
+impl FunctionSignature {
+    fn of(item: &OgExternEntity, context: &OgxSql) -> eyre::Result<Self> {
+        let arguments = item
+            .sql_arguments(context)?
+            .into_iter()
+            .map(|(arg, sql_type)| (arg.pattern, sql_type, arg.used_ty.default))
+            .collect();
+        Ok(FunctionSignature { arguments, returns: item.sql_return_type(context)? })
+    }
+}
+
+/// `sql` commented out, for a person to review
+fn commented(key: &EntityKey, sql: &str) -> String {
+    let commented = sql.lines().map(|line| format!("-- {}", line)).collect::<Vec<_>>();
+    format!("-- REVIEW: {} must be migrated by hand\n{}", key, commented.join("\n"))
+}
+
+/// A statement without comments, its terminating `;`, or any insignificant whitespace
+fn normalize(statement: &str) -> String {
+    let mut normalized = String::new();
+    let mut pending_space = false;
+    let mut chars = statement.chars().peekable();
+    while let Some(c) = chars.next() {
+        let token = match c {
+            '\'' | '"' => {
+                let mut quoted = c.to_string();
+                for next in chars.by_ref() {
+                    quoted.push(next);
+                    if next == c {
+                        break;
+                    }
+                }
+                quoted
+            }
+            '-' if chars.peek() == Some(&'-') => {
+                chars.by_ref().find(|next| *next == '\n');
+                pending_space = true;
+                continue;
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                for next in chars.by_ref() {
+                    if previous == '*' && next == '/' {
+                        break;
+                    }
+                    previous = next;
+                }
+                pending_space = true;
+                continue;
+            }
+            c if c.is_whitespace() => {
+                pending_space = true;
+                continue;
+            }
+            c => c.to_string(),
+        };
+        if pending_space && !normalized.is_empty() {
+            normalized.push(' ');
+        }
+        pending_space = false;
+        normalized.push_str(&token);
+    }
+    normalized.trim_end_matches(';').trim_end().to_string()
+}
+
+/// The `ALTER TYPE ... ADD VALUE`s turning `old` labels into `new`, if only labels were added
+fn add_enum_values(name: &str, old: &[&str], new: &[&str]) -> Option<Vec<String>> {
+    let kept = new.iter().filter(|label| old.contains(label)).collect::<Vec<_>>();
+    if !kept.into_iter().eq(old.iter()) {
+        return None;
+    }
+
+    let mut alters = Vec::new();
+    let mut previous = None;
+    for label in new {
+        if !old.contains(label) {
+            alters.push(match previous {
+                Some(previous) => {
+                    format!("ALTER TYPE {} ADD VALUE '{}' AFTER '{}';", name, label, previous)
+                }
+                None => {
+                    format!("ALTER TYPE {} ADD VALUE '{}' BEFORE '{}';", name, label, old.first()?)
+                }
+            });
+        }
+        previous = Some(label);
+    }
+    Some(alters).filter(|alters| !alters.is_empty())
+}
+
+/// Turn a function's `CREATE FUNCTION` into a `CREATE OR REPLACE FUNCTION`
+fn or_replace(raw: &str) -> String {
+    match (raw.find("CREATE"), raw.find("FUNCTION")) {
+        (Some(create), Some(function)) if create < function => {
+            format!("{}CREATE OR REPLACE {}", &raw[..create], &raw[function..])
+        }
+        _ => raw.to_string(),
+    }
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::OgxSqlUpgrade;
+    use crate::sql_entity_graph::metadata::{
+        ArgumentError, FunctionMetadataEntity, Returns, ReturnsError, SqlMapping, SqlTranslatable,
+    };
+    use crate::sql_entity_graph::{
+        ControlFile, ExtensionSqlEntity, OgEnumEntity, OgExternArgumentEntity, OgExternEntity,
+        OgExternReturnEntity, OgOrdEntity, OgTypeEntity, OgxSql, PositioningRef, RustSqlMapping,
+        RustToSqlMapping, SqlGraphEntity, ToSqlConfigEntity, UsedTypeEntity,
+    };
+    use std::any::{type_name, TypeId};
+    use std::ffi::CStr;
+
+    pub(in crate::sql_entity_graph) fn used_ty<T: SqlTranslatable + 'static>() -> UsedTypeEntity {
+        UsedTypeEntity {
+            ty_source: type_name::<T>(),
+            ty_id: TypeId::of::<T>(),
+            full_path: type_name::<T>(),
+            module_path: String::new(),
+            composite_type: None,
+            variadic: false,
+            default: None,
+            optional: false,
+            metadata: T::entity(),
+        }
+    }
+
+    fn to_sql_config() -> ToSqlConfigEntity {
+        ToSqlConfigEntity { enabled: true, callback: None, content: None }
+    }
+
+    pub(in crate::sql_entity_graph) fn function(
+        name: &'static str,
+        args: Vec<(&'static str, UsedTypeEntity)>,
+        returns: UsedTypeEntity,
+        immutable: bool,
+    ) -> SqlGraphEntity {
+        SqlGraphEntity::Function(OgExternEntity {
+            name,
+            unaliased_name: name,
+            module_path: "ext",
+            full_path: name,
+            metadata: FunctionMetadataEntity {
+                arguments: args.iter().map(|(_, ty)| ty.metadata.clone()).collect(),
+                retval: Some(returns.metadata.clone()),
+                path: name,
+            },
+            fn_args: args
+                .into_iter()
+                .map(|(pattern, used_ty)| OgExternArgumentEntity { pattern, used_ty })
+                .collect(),
+            fn_return: OgExternReturnEntity::Type { ty: returns },
+            schema: None,
+            file: "src/lib.rs",
+            line: 1,
+            extern_attrs: if immutable { vec![crate::ExternArgs::Immutable] } else { vec![] },
+            search_path: None,
+            operator: None,
+            cast: None,
+            to_sql_config: to_sql_config(),
+        })
+    }
+
+    pub(in crate::sql_entity_graph) fn color(variants: Vec<&'static str>) -> SqlGraphEntity {
+        SqlGraphEntity::Enum(OgEnumEntity {
+            name: "Color",
+            file: "src/lib.rs",
+            line: 1,
+            full_path: "ext::Color",
+            module_path: "ext",
+            mappings: [RustSqlMapping {
+                rust: String::from("Color"),
+                sql: String::from("Color"),
+                id: TypeId::of::<OgxSqlUpgrade>(),
+            }]
+            .into_iter()
+            .collect(),
+            variants,
+            to_sql_config: to_sql_config(),
+        })
+    }
+
+    pub(in crate::sql_entity_graph) struct BoundedText;
+
+    unsafe impl SqlTranslatable for BoundedText {
+        fn argument_sql() -> Result<SqlMapping, ArgumentError> {
+            Ok(SqlMapping::As(String::from("BoundedText")))
+        }
+        fn return_sql() -> Result<Returns, ReturnsError> {
+            Ok(Returns::One(SqlMapping::As(String::from("BoundedText"))))
+        }
+    }
+
+    /// A `#[derive(OgType)]` and its input and output functions, and its `#[typmod_inoutfuncs]` if
+    /// `typmod`
+    pub(in crate::sql_entity_graph) fn bounded_text(typmod: bool) -> Vec<SqlGraphEntity> {
+        let in_out_fn = |name, full_path, args, returns| {
+            let mut entity = function(name, args, returns, true);
+            if let SqlGraphEntity::Function(item) = &mut entity {
+                item.full_path = full_path;
+            }
+            entity
+        };
+        let mut entities = vec![
+            SqlGraphEntity::Type(OgTypeEntity {
+                name: "BoundedText",
+                file: "src/lib.rs",
+                line: 1,
+                full_path: "ext::BoundedText",
+                module_path: "ext",
+                mappings: [RustSqlMapping {
+                    rust: String::from("BoundedText"),
+                    sql: String::from("BoundedText"),
+                    id: TypeId::of::<BoundedText>(),
+                }]
+                .into_iter()
+                .collect(),
+                in_fn: "boundedtext_in",
+                in_fn_module_path: String::new(),
+                out_fn: "boundedtext_out",
+                out_fn_module_path: String::new(),
+                send_fn: None,
+                receive_fn: None,
+                typmod_in_fn: typmod.then(|| "boundedtext_typmod_in"),
+                typmod_out_fn: typmod.then(|| "boundedtext_typmod_out"),
+                typmod_coerce_fn: typmod.then(|| "boundedtext_coerce"),
+                to_sql_config: to_sql_config(),
+            }),
+            in_out_fn(
+                "boundedtext_in",
+                "ext::boundedtext_in",
+                vec![("input", used_ty::<&'static CStr>())],
+                used_ty::<BoundedText>(),
+            ),
+            in_out_fn(
+                "boundedtext_out",
+                "ext::boundedtext_out",
+                vec![("input", used_ty::<BoundedText>())],
+                used_ty::<&'static CStr>(),
+            ),
+        ];
+        if typmod {
+            entities.extend([
+                in_out_fn(
+                    "boundedtext_typmod_in",
+                    "ext::boundedtext_typmod_in",
+                    vec![("input", used_ty::<Vec<&'static CStr>>())],
+                    used_ty::<i32>(),
+                ),
+                in_out_fn(
+                    "boundedtext_typmod_out",
+                    "ext::boundedtext_typmod_out",
+                    vec![("typmod", used_ty::<i32>())],
+                    used_ty::<&'static CStr>(),
+                ),
+                in_out_fn(
+                    "boundedtext_coerce",
+                    "ext::boundedtext_coerce",
+                    vec![
+                        ("value", used_ty::<BoundedText>()),
+                        ("typmod", used_ty::<i32>()),
+                        ("explicit", used_ty::<bool>()),
+                    ],
+                    used_ty::<BoundedText>(),
+                ),
+            ]);
+        }
+        entities
+    }
+
+    fn custom_sql(name: &'static str, requires: &str) -> SqlGraphEntity {
+        SqlGraphEntity::CustomSql(ExtensionSqlEntity {
+            module_path: "ext",
+            full_path: "ext::custom",
+            sql: "COMMENT ON FUNCTION \"add\"(integer, integer) IS 'adds';",
+            file: "src/lib.rs",
+            line: 1,
+            name,
+            bootstrap: false,
+            finalize: false,
+            requires: vec![PositioningRef::FullPath(requires.to_string())],
+            creates: vec![],
+        })
+    }
+
+    pub(in crate::sql_entity_graph) fn build(entities: Vec<SqlGraphEntity>) -> OgxSql {
+        let control = ControlFile {
+            comment: String::from("ext"),
+            default_version: String::from("1.0"),
+            module_pathname: None,
+            relocatable: false,
+            superuser: true,
+            schema: None,
+        };
+        OgxSql::build(
+            RustToSqlMapping { rust_source_to_sql: Default::default() },
+            std::iter::once(SqlGraphEntity::ExtensionRoot(control)).chain(entities),
+            String::from("ext"),
+            false,
+        )
+        .unwrap()
+    }
+
+    fn v1() -> Vec<SqlGraphEntity> {
+        vec![
+            function(
+                "add",
+                vec![("a", used_ty::<i32>()), ("b", used_ty::<i32>())],
+                used_ty::<i32>(),
+                false,
+            ),
+            function("gone", vec![], used_ty::<i32>(), false),
+            color(vec!["Red", "Blue"]),
+        ]
+    }
+
+    fn v2() -> Vec<SqlGraphEntity> {
+        vec![
+            function(
+                "add",
+                vec![("a", used_ty::<i32>()), ("b", used_ty::<i32>())],
+                used_ty::<i32>(),
+                true,
+            ),
+            function("answer", vec![("x", used_ty::<String>())], used_ty::<i64>(), false),
+            color(vec!["Red", "Green", "Blue"]),
+        ]
+    }
+
+    #[test]
+    fn unchanged() {
+        assert!(OgxSqlUpgrade::new(&build(v1()), &build(v1())).unwrap().is_empty());
+    }
+
+    #[test]
+    fn upgrade() {
+        let upgrade = OgxSqlUpgrade::new(&build(v1()), &build(v2())).unwrap();
+        assert_eq!(upgrade.statements[0], "DROP FUNCTION \"gone\"();");
+        // unrelated entities come in no particular order
+        let mut created = upgrade.statements[1..].to_vec();
+        created.sort_by_key(|s| s.lines().last().unwrap().to_string());
+        assert_eq!(created.len(), 2);
+        assert!(created[0].contains("CREATE OR REPLACE FUNCTION \"add\"("));
+        assert!(created[1].contains("CREATE  FUNCTION \"answer\"("));
+        assert_eq!(
+            upgrade.needs_review,
+            vec![
+                "function \"gone\"() was removed",
+                "type Color gained labels, which can't be added in the transaction the upgrade runs in, so add them by hand first:\n\
+                 ALTER TYPE Color ADD VALUE 'Green' AFTER 'Red';",
+            ]
+        );
+    }
+
+    #[test]
+    fn changed_return_type_recreates() {
+        let mut v3 = v2();
+        v3[1] = function("answer", vec![("x", used_ty::<String>())], used_ty::<i32>(), false);
+        let upgrade = OgxSqlUpgrade::new(&build(v2()), &build(v3)).unwrap();
+        assert_eq!(upgrade.statements[0], "DROP FUNCTION \"answer\"(TEXT);");
+        assert!(upgrade.statements[1].contains("CREATE  FUNCTION \"answer\""));
+        assert!(upgrade.statements[1].contains("RETURNS INT /* i32 */"));
+        assert_eq!(
+            upgrade.needs_review,
+            vec!["function \"answer\"(TEXT) changed and must be dropped and recreated"]
+        );
+    }
+
+    #[test]
+    fn recreate_drops_dependents() {
+        let cmp = |returns| {
+            function(
+                "color_cmp",
+                vec![("a", used_ty::<i32>()), ("b", used_ty::<i32>())],
+                returns,
+                false,
+            )
+        };
+        let ord = SqlGraphEntity::Ord(OgOrdEntity {
+            name: "Color",
+            file: "src/lib.rs",
+            line: 1,
+            full_path: "ext::Color",
+            module_path: "ext",
+            id: TypeId::of::<OgxSqlUpgrade>(),
+            to_sql_config: to_sql_config(),
+        });
+        let v1 = vec![cmp(used_ty::<i32>()), ord.clone()];
+        let v2 = vec![cmp(used_ty::<i64>()), ord];
+        let upgrade = OgxSqlUpgrade::new(&build(v1), &build(v2)).unwrap();
+        assert_eq!(
+            upgrade.statements[..2],
+            [
+                "DROP OPERATOR FAMILY Color_btree_ops USING btree;",
+                "DROP FUNCTION \"color_cmp\"(INT, INT);",
+            ]
+        );
+        assert!(upgrade.statements[2].contains("CREATE  FUNCTION \"color_cmp\""));
+        assert!(upgrade.statements[3].contains("CREATE OPERATOR FAMILY Color_btree_ops"));
+        assert_eq!(
+            upgrade.needs_review,
+            vec![
+                "operator family Color_btree_ops USING btree must be dropped and recreated, as it depends on function \"color_cmp\"(INT, INT)",
+                "function \"color_cmp\"(INT, INT) changed and must be dropped and recreated",
+            ]
+        );
+    }
+
+    #[test]
+    fn recreate_with_custom_sql_dependent_is_manual() {
+        let mut v1 = v1();
+        v1.push(custom_sql("add_comment", "ext::add"));
+        let mut v2 = v1.clone();
+        v2[0] = function(
+            "add",
+            vec![("a", used_ty::<i32>()), ("c", used_ty::<i32>())],
+            used_ty::<i32>(),
+            false,
+        );
+        let upgrade = OgxSqlUpgrade::new(&build(v1), &build(v2)).unwrap();
+        assert!(!upgrade.statements.iter().any(|s| s.starts_with("DROP")));
+        assert!(upgrade.statements.iter().any(
+            |s| s.starts_with("-- REVIEW: function \"add\"(INT, INT) must be migrated by hand")
+        ));
+        assert_eq!(
+            upgrade.needs_review,
+            vec!["function \"add\"(INT, INT) changed and must be dropped and recreated, but custom SQL add_comment depends on it"]
+        );
+    }
+
+    #[test]
+    fn removed_enum_label_is_manual() {
+        let mut v3 = v2();
+        v3[2] = color(vec!["Green", "Blue"]);
+        let upgrade = OgxSqlUpgrade::new(&build(v2()), &build(v3)).unwrap();
+        assert!(upgrade.statements[0].starts_with("-- REVIEW: type Color must be migrated by hand"));
+        assert!(upgrade.to_sql().contains("  * type Color changed in a way that can't be"));
+    }
+
+    #[test]
+    fn removed_type_is_manual() {
+        let mut v3 = v2();
+        v3.extend(bounded_text(false));
+        let upgrade = OgxSqlUpgrade::new(&build(v3), &build(v2())).unwrap();
+        assert!(!upgrade.statements.iter().any(|s| s.starts_with("DROP TYPE")));
+        assert!(upgrade.statements.contains(
+            &"-- REVIEW: type BoundedText must be migrated by hand\n-- DROP TYPE BoundedText CASCADE;"
+                .to_string()
+        ));
+        assert!(upgrade.needs_review.contains(
+            &"type BoundedText was removed, but dropping it also drops every column of that type, and the data in them, so it must be dropped by hand"
+                .to_string()
+        ));
+    }
+}
@@ -15,7 +15,9 @@ use syn::parse::{Parse, ParseStream};
 use syn::spanned::Spanned;
 use syn::Token;
 
-use super::metadata::FunctionMetadataTypeEntity;
+use super::metadata::{FunctionMetadataTypeEntity, SqlMapping};
+use super::OgxSql;
+use eyre::eyre;
 
 /// A type, optionally with an overriding composite type name
 #[derive(Debug, Clone)]
@@ -260,6 +262,29 @@ pub struct UsedTypeEntity {
     pub metadata: FunctionMetadataTypeEntity,
 }
 
+impl UsedTypeEntity {
+    /// The SQL type `mapping` maps this type to, without a schema prefix
+    pub fn sql_type(&self, mapping: &SqlMapping, context: &OgxSql) -> eyre::Result<String> {
+        let brackets = |array_brackets: bool| if array_brackets { "[]" } else { "" };
+        match mapping {
+            SqlMapping::As(sql) => Ok(sql.clone()),
+            SqlMapping::Composite { array_brackets } => self
+                .composite_type
+                .map(|composite_type| composite_type.to_string() + brackets(*array_brackets))
+                .ok_or_else(|| {
+                    eyre!("Found a composite type but macro expansion time did not reveal a name, use `ogx::composite_type!()`")
+                }),
+            SqlMapping::Source { array_brackets } => context
+                .source_only_to_sql_type(self.ty_source)
+                .map(|source_type| source_type + brackets(*array_brackets))
+                .ok_or_else(|| {
+                    eyre!("Found a source only mapping but no source mapping exists for this")
+                }),
+            SqlMapping::Skip => Err(eyre!("`{}` has no SQL type", self.full_path)),
+        }
+    }
+}
+
 fn resolve_vec_inner(
     original: syn::TypePath,
 ) -> syn::Result<(syn::Type, Option<CompositeTypeMacro>)> {
@@ -0,0 +1,325 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+
+//! Typed, RAII access to system catalog rows through the syscache, and helpers for resolving
+//! object names to their oids
+use crate::pg_sys::ogx_GETSTRUCT;
+use crate::{
+    direct_function_call, htup::heap_getattr_raw, name_data_to_str, pg_sys, FromDatum, IntoDatum,
+    PgRelation, PgSnapshot,
+};
+use std::marker::PhantomData;
+use std::num::NonZeroUsize;
+use std::ops::Deref;
+
+/// A catalog row struct (`pg_sys::FormData_pg_*`) that can be looked up in the syscache
+///
+/// ## Safety
+///
+/// `CACHE_ID` must name a syscache over the catalog `Self` describes, and `Self` must match
+/// (a prefix of) that catalog's fixed-width columns.
+pub unsafe trait SysCacheForm {
+    /// The key this syscache is searched by
+    type Key: Copy;
+
+    /// The syscache to search
+    const CACHE_ID: pg_sys::SysCacheIdentifier;
+
+    /// Convert `key` into the (up to) four key `Datum`s `SearchSysCache()` expects
+    fn key_datums(key: Self::Key) -> [pg_sys::Datum; 4];
+}
+
+/// A row pinned in the syscache, which derefs to its typed catalog struct.
+///
+/// The row is released back to the syscache via `pg_sys::ReleaseSysCache()` when dropped.
+pub struct SysCacheEntry<F: SysCacheForm> {
+    tuple: pg_sys::HeapTuple,
+    _marker: PhantomData<F>,
+}
+
+/// A row of `pg_type`
+pub type PgTypeEntry = SysCacheEntry<pg_sys::FormData_pg_type>;
+/// A row of `pg_proc`
+pub type PgProcEntry = SysCacheEntry<pg_sys::FormData_pg_proc>;
+/// A row of `pg_class`
+pub type PgClassEntry = SysCacheEntry<pg_sys::FormData_pg_class>;
+/// A row of `pg_namespace`
+pub type PgNamespaceEntry = SysCacheEntry<FormData_pg_namespace>;
+/// A row of `pg_operator`
+pub type PgOperatorEntry = SysCacheEntry<pg_sys::FormData_pg_operator>;
+/// A row of `pg_attribute`
+pub type PgAttributeEntry = SysCacheEntry<pg_sys::FormData_pg_attribute>;
+
+impl<F: SysCacheForm> SysCacheEntry<F> {
+    /// Look up the row identified by `key`, returning `None` if it doesn't exist
+    pub fn search(key: F::Key) -> Option<Self> {
+        let [key1, key2, key3, key4] = F::key_datums(key);
+        let tuple = unsafe { pg_sys::SearchSysCache(F::CACHE_ID as i32, key1, key2, key3, key4) };
+
+        if tuple.is_null() {
+            None
+        } else {
+            Some(SysCacheEntry { tuple, _marker: PhantomData })
+        }
+    }
+
+    /// The row's `oid` system column, or `pg_sys::InvalidOid` if its catalog has none
+    pub fn oid(&self) -> pg_sys::Oid {
+        unsafe { heap_tuple_get_oid(self.tuple) }
+    }
+
+    /// Get the value of attribute number `attno` (1-based), including variable-width columns
+    /// not described by the typed struct.  Returns `None` if the value is NULL.
+    ///
+    /// `T` must own its value, as the row it's read from is released when `self` is dropped
+    pub fn get_attr<T: FromDatum + 'static>(&self, attno: pg_sys::AttrNumber) -> Option<T> {
+        unsafe {
+            let mut is_null = false;
+            let datum =
+                pg_sys::SysCacheGetAttr(F::CACHE_ID as i32, self.tuple, attno, &mut is_null);
+            T::from_datum(datum, is_null)
+        }
+    }
+
+    /// The underlying `pg_sys::HeapTuple`, which remains owned by the syscache
+    pub fn as_ptr(&self) -> pg_sys::HeapTuple {
+        self.tuple
+    }
+}
+
+impl<F: SysCacheForm> Deref for SysCacheEntry<F> {
+    type Target = F;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { (ogx_GETSTRUCT(self.tuple) as *const F).as_ref() }.unwrap()
+    }
+}
+
+impl<F: SysCacheForm> Drop for SysCacheEntry<F> {
+    fn drop(&mut self) {
+        unsafe { pg_sys::ReleaseSysCache(self.tuple) }
+    }
+}
+
+fn oid_key(oid: pg_sys::Oid) -> [pg_sys::Datum; 4] {
+    [oid.into(), 0.into(), 0.into(), 0.into()]
+}
+
+unsafe impl SysCacheForm for pg_sys::FormData_pg_type {
+    type Key = pg_sys::Oid;
+    const CACHE_ID: pg_sys::SysCacheIdentifier = pg_sys::SysCacheIdentifier_TYPEOID;
+
+    fn key_datums(key: Self::Key) -> [pg_sys::Datum; 4] {
+        oid_key(key)
+    }
+}
+
+unsafe impl SysCacheForm for pg_sys::FormData_pg_proc {
+    type Key = pg_sys::Oid;
+    const CACHE_ID: pg_sys::SysCacheIdentifier = pg_sys::SysCacheIdentifier_PROCOID;
+
+    fn key_datums(key: Self::Key) -> [pg_sys::Datum; 4] {
+        oid_key(key)
+    }
+}
+
+unsafe impl SysCacheForm for pg_sys::FormData_pg_class {
+    type Key = pg_sys::Oid;
+    const CACHE_ID: pg_sys::SysCacheIdentifier = pg_sys::SysCacheIdentifier_RELOID;
+
+    fn key_datums(key: Self::Key) -> [pg_sys::Datum; 4] {
+        oid_key(key)
+    }
+}
+
+unsafe impl SysCacheForm for FormData_pg_namespace {
+    type Key = pg_sys::Oid;
+    const CACHE_ID: pg_sys::SysCacheIdentifier = pg_sys::SysCacheIdentifier_NAMESPACEOID;
+
+    fn key_datums(key: Self::Key) -> [pg_sys::Datum; 4] {
+        oid_key(key)
+    }
+}
+
+unsafe impl SysCacheForm for pg_sys::FormData_pg_operator {
+    type Key = pg_sys::Oid;
+    const CACHE_ID: pg_sys::SysCacheIdentifier = pg_sys::SysCacheIdentifier_OPEROID;
+
+    fn key_datums(key: Self::Key) -> [pg_sys::Datum; 4] {
+        oid_key(key)
+    }
+}
+
+/// `pg_attribute` is keyed by the relation's oid and the attribute number
+unsafe impl SysCacheForm for pg_sys::FormData_pg_attribute {
+    type Key = (pg_sys::Oid, pg_sys::AttrNumber);
+    const CACHE_ID: pg_sys::SysCacheIdentifier = pg_sys::SysCacheIdentifier_ATTNUM;
+
+    fn key_datums((relid, attnum): Self::Key) -> [pg_sys::Datum; 4] {
+        [relid.into(), attnum.into(), 0.into(), 0.into()]
+    }
+}
+
+/// The fixed-width prefix of a `pg_namespace` row, which `ogx-pg-sys` doesn't generate
+#[repr(C)]
+#[allow(non_camel_case_types)]
+pub struct FormData_pg_namespace {
+    pub nspname: pg_sys::NameData,
+    pub nspowner: pg_sys::Oid,
+}
+
+impl PgTypeEntry {
+    /// The type's unqualified name
+    pub fn name(&self) -> &str {
+        name_data_to_str(&self.typname)
+    }
+}
+
+impl PgProcEntry {
+    /// The function's unqualified name
+    pub fn name(&self) -> &str {
+        name_data_to_str(&self.proname)
+    }
+}
+
+impl PgClassEntry {
+    /// The relation's unqualified name
+    pub fn name(&self) -> &str {
+        name_data_to_str(&self.relname)
+    }
+}
+
+impl PgNamespaceEntry {
+    /// The schema's name
+    pub fn name(&self) -> &str {
+        name_data_to_str(&self.nspname)
+    }
+}
+
+impl PgOperatorEntry {
+    /// The operator's unqualified name, such as `=`
+    pub fn name(&self) -> &str {
+        name_data_to_str(&self.oprname)
+    }
+}
+
+impl PgAttributeEntry {
+    /// The column's name
+    pub fn name(&self) -> &str {
+        name_data_to_str(&self.attname)
+    }
+}
+
+/// A row of `pg_extension`, describing an installed extension
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgExtension {
+    pub oid: pg_sys::Oid,
+    pub name: String,
+    pub owner: pg_sys::Oid,
+    pub namespace: pg_sys::Oid,
+    pub relocatable: bool,
+    pub version: String,
+}
+
+impl PgExtension {
+    /// Look up the installed extension named `name`, returning `None` if it isn't installed.
+    ///
+    /// `pg_extension` has no syscache, so this scans the catalog itself
+    pub fn lookup(name: &str) -> Option<PgExtension> {
+        PgExtension::all().into_iter().find(|extension| extension.name == name)
+    }
+
+    /// Every extension installed in the current database
+    pub fn all() -> Vec<PgExtension> {
+        let relation = PgRelation::with_lock(
+            pg_sys::ExtensionRelationId,
+            pg_sys::AccessShareLock as pg_sys::LOCKMODE,
+        );
+        let tupdesc = relation.rd_att;
+
+        relation
+            .scan(PgSnapshot::Latest)
+            .map(|tuple| unsafe {
+                let tuple = tuple.as_ptr();
+                let attr = |attno: usize| {
+                    heap_getattr_raw(tuple, NonZeroUsize::new(attno).unwrap(), tupdesc)
+                        .expect("unexpected NULL in pg_extension")
+                };
+
+                PgExtension {
+                    oid: heap_tuple_get_oid(tuple),
+                    name: name_data_to_str(&*attr(1).cast_mut_ptr::<pg_sys::NameData>())
+                        .to_string(),
+                    owner: pg_sys::Oid::from_datum(attr(2), false).unwrap(),
+                    namespace: pg_sys::Oid::from_datum(attr(3), false).unwrap(),
+                    relocatable: bool::from_datum(attr(4), false).unwrap(),
+                    version: String::from_datum(attr(5), false).unwrap(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Resolve a possibly schema-qualified relation name to its oid, via `pg_sys::to_regclass`.
+/// Returns `None` if no such relation exists
+pub fn regclass(name: &str) -> Option<pg_sys::Oid> {
+    to_reg(pg_sys::to_regclass, name)
+}
+
+/// Resolve a possibly schema-qualified type name, such as `integer[]`, to its oid, via
+/// `pg_sys::to_regtype`.  Returns `None` if no such type exists
+pub fn regtype(name: &str) -> Option<pg_sys::Oid> {
+    to_reg(pg_sys::to_regtype, name)
+}
+
+/// Resolve a possibly schema-qualified function name to its oid, via `pg_sys::to_regproc`.
+/// Returns `None` if no such function exists, or if the name is overloaded
+pub fn regproc(name: &str) -> Option<pg_sys::Oid> {
+    to_reg(pg_sys::to_regproc, name)
+}
+
+/// Resolve a function signature, such as `lower(text)`, to its oid, via
+/// `pg_sys::to_regprocedure`.  Returns `None` if no such function exists
+pub fn regprocedure(signature: &str) -> Option<pg_sys::Oid> {
+    to_reg(pg_sys::to_regprocedure, signature)
+}
+
+/// Resolve an operator signature, such as `+(integer,integer)`, to its oid, via
+/// `pg_sys::to_regoperator`.  Returns `None` if no such operator exists
+pub fn regoperator(signature: &str) -> Option<pg_sys::Oid> {
+    to_reg(pg_sys::to_regoperator, signature)
+}
+
+/// Resolve a schema name to its oid, via `pg_sys::to_regnamespace`.  Returns `None` if no such
+/// schema exists
+pub fn regnamespace(name: &str) -> Option<pg_sys::Oid> {
+    to_reg(pg_sys::to_regnamespace, name)
+}
+
+fn to_reg(
+    func: unsafe fn(pg_sys::FunctionCallInfo) -> pg_sys::Datum,
+    name: &str,
+) -> Option<pg_sys::Oid> {
+    unsafe { direct_function_call::<pg_sys::Oid>(func, vec![name.into_datum()]) }
+        .filter(|oid| *oid != pg_sys::InvalidOid)
+}
+
+/// Equivalent to Postgres' `HeapTupleGetOid()` macro
+unsafe fn heap_tuple_get_oid(tuple: pg_sys::HeapTuple) -> pg_sys::Oid {
+    let header = (*tuple).t_data;
+    if (*header).t_infomask as u32 & pg_sys::HEAP_HASOID == 0 {
+        pg_sys::InvalidOid
+    } else {
+        header
+            .cast::<u8>()
+            .add((*header).t_hoff as usize - std::mem::size_of::<pg_sys::Oid>())
+            .cast::<pg_sys::Oid>()
+            .read_unaligned()
+    }
+}
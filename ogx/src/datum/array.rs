@@ -212,6 +212,45 @@ impl<'a, T: FromDatum> Array<'a, T> {
         self.nelems == 0
     }
 
+    /**
+    Borrow the elements of this array as a slice, without decoding each one through [`FromDatum`].
+
+    This is only possible for by-value, fixed-width element types (`int2`, `int4`, `int8`,
+    `float4`, `float8`, `bool`, ...) whose size matches `T`, and only when no element is NULL.
+    A null bitmap by itself is fine, as long as every bit in it marks a present value.
+
+    # Panics
+
+    If the array contains a NULL, or if `T` does not match the element layout.
+    Use [`Array::try_as_slice`] to fall back to [`Array::iter`] instead.
+    */
+    pub fn as_slice(&self) -> &[T] {
+        if self.null_slice.any() {
+            panic!("null detected: can't expose potentially uninit data as a slice!")
+        }
+        self.try_as_slice().expect("no correctly-sized slice exists")
+    }
+
+    /**
+    Borrow the elements of this array as a slice, if they can be read as `&[T]` in place.
+
+    Returns `None` if the array contains a NULL or its elements are not laid out as `T`,
+    in which case the caller should use [`Array::iter`] instead.
+    */
+    pub fn try_as_slice(&self) -> Option<&[T]> {
+        if self.elem_slice.is_empty() {
+            return Some(&[]);
+        }
+        if self.null_slice.any() {
+            return None;
+        }
+        match (self.elem_layout.as_ref()?.size_matches::<T>(), self.raw.as_ref()) {
+            // SAFETY: Rust slice layout matches openGauss data layout and this array is "owned"
+            (Some(_), Some(raw)) => Some(unsafe { raw.assume_init_data_slice::<T>() }),
+            _ => None,
+        }
+    }
+
     #[allow(clippy::option_option)]
     #[inline]
     pub fn get(&self, i: usize) -> Option<Option<T>> {
@@ -267,6 +306,16 @@ impl<'a, T: FromDatum> VariadicArray<'a, T> {
         self.0.is_empty()
     }
 
+    /// See [`Array::as_slice`].
+    pub fn as_slice(&self) -> &[T] {
+        self.0.as_slice()
+    }
+
+    /// See [`Array::try_as_slice`].
+    pub fn try_as_slice(&self) -> Option<&[T]> {
+        self.0.try_as_slice()
+    }
+
     #[allow(clippy::option_option)]
     #[inline]
     pub fn get(&self, i: usize) -> Option<Option<T>> {
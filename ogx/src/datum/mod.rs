@@ -21,6 +21,7 @@ mod into;
 mod item_pointer_data;
 mod json;
 mod numeric;
+mod reg;
 mod time;
 mod time_stamp;
 mod time_stamp_with_timezone;
@@ -43,6 +44,7 @@ pub use into::*;
 pub use item_pointer_data::*;
 pub use json::*;
 pub use numeric::*;
+pub use reg::*;
 use once_cell::sync::Lazy;
 use std::any::TypeId;
 pub use time_stamp::*;
@@ -0,0 +1,185 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+
+//! The `reg*` object identifier alias types, such as `regclass` and `regtype`
+use crate::catalog::{
+    self, PgClassEntry, PgNamespaceEntry, PgOperatorEntry, PgProcEntry, PgTypeEntry,
+};
+use crate::{direct_function_call, pg_sys, FromDatum, IntoDatum, PgRelation};
+use ogx_utils::sql_entity_graph::metadata::{
+    ArgumentError, Returns, ReturnsError, SqlMapping, SqlTranslatable,
+};
+use std::ffi::CStr;
+use std::fmt;
+
+macro_rules! reg_type {
+    ($(#[$meta:meta])* $name:ident, $sql:literal, $typoid:path, $outfunc:path, $resolve:path) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        #[repr(transparent)]
+        pub struct $name(pg_sys::Oid);
+
+        impl $name {
+            /// Wrap an existing oid.  The oid isn't checked to exist
+            pub fn from_oid(oid: pg_sys::Oid) -> Self {
+                $name(oid)
+            }
+
+            #[doc = concat!("Resolve `name` the same way casting it to `", $sql, "` would, ")]
+            /// returning `None` if there's no such object
+            pub fn lookup(name: &str) -> Option<Self> {
+                $resolve(name).map($name)
+            }
+
+            /// The underlying oid
+            pub fn oid(&self) -> pg_sys::Oid {
+                self.0
+            }
+        }
+
+        impl From<$name> for pg_sys::Oid {
+            fn from(val: $name) -> Self {
+                val.0
+            }
+        }
+
+        /// Displays the object's name, quoted and schema-qualified only if needed
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                let name =
+                    unsafe { direct_function_call::<&CStr>($outfunc, vec![Some(self.0.into())]) }
+                        .expect(concat!(stringify!($outfunc), " returned NULL"));
+                f.write_str(name.to_str().expect("object name is not valid UTF8"))
+            }
+        }
+
+        impl FromDatum for $name {
+            unsafe fn from_polymorphic_datum(
+                datum: pg_sys::Datum,
+                is_null: bool,
+                _typoid: pg_sys::Oid,
+            ) -> Option<Self> {
+                if is_null {
+                    None
+                } else {
+                    Some($name(datum.value() as pg_sys::Oid))
+                }
+            }
+        }
+
+        impl IntoDatum for $name {
+            fn into_datum(self) -> Option<pg_sys::Datum> {
+                Some(self.0.into())
+            }
+
+            fn type_oid() -> pg_sys::Oid {
+                $typoid
+            }
+
+            fn is_pass_by_value() -> bool {
+                true
+            }
+        }
+
+        unsafe impl SqlTranslatable for $name {
+            fn argument_sql() -> Result<SqlMapping, ArgumentError> {
+                Ok(SqlMapping::literal($sql))
+            }
+            fn return_sql() -> Result<Returns, ReturnsError> {
+                Ok(Returns::One(SqlMapping::literal($sql)))
+            }
+        }
+    };
+}
+
+reg_type!(
+    /// A relation, as the Postgres `regclass` type
+    RegClass,
+    "regclass",
+    pg_sys::REGCLASSOID,
+    pg_sys::regclassout,
+    catalog::regclass
+);
+
+reg_type!(
+    /// A data type, as the Postgres `regtype` type
+    RegType,
+    "regtype",
+    pg_sys::REGTYPEOID,
+    pg_sys::regtypeout,
+    catalog::regtype
+);
+
+reg_type!(
+    /// A function identified by its name alone, as the Postgres `regproc` type
+    RegProc,
+    "regproc",
+    pg_sys::REGPROCOID,
+    pg_sys::regprocout,
+    catalog::regproc
+);
+
+reg_type!(
+    /// An operator identified by its name and argument types, as the Postgres `regoperator` type
+    RegOperator,
+    "regoperator",
+    pg_sys::REGOPERATOROID,
+    pg_sys::regoperatorout,
+    catalog::regoperator
+);
+
+reg_type!(
+    /// A schema, as the Postgres `regnamespace` type
+    RegNamespace,
+    "regnamespace",
+    pg_sys::REGNAMESPACEOID,
+    pg_sys::regnamespaceout,
+    catalog::regnamespace
+);
+
+impl RegClass {
+    /// Open the relation, taking the lock `lockmode`, which is held until the end of the
+    /// transaction
+    pub fn open(&self, lockmode: pg_sys::LOCKMODE) -> PgRelation {
+        PgRelation::with_lock(self.0, lockmode)
+    }
+
+    /// The relation's `pg_class` row, or `None` if it no longer exists
+    pub fn entry(&self) -> Option<PgClassEntry> {
+        PgClassEntry::search(self.0)
+    }
+}
+
+impl RegType {
+    /// The type's `pg_type` row, or `None` if it no longer exists
+    pub fn entry(&self) -> Option<PgTypeEntry> {
+        PgTypeEntry::search(self.0)
+    }
+}
+
+impl RegProc {
+    /// The function's `pg_proc` row, or `None` if it no longer exists
+    pub fn entry(&self) -> Option<PgProcEntry> {
+        PgProcEntry::search(self.0)
+    }
+}
+
+impl RegOperator {
+    /// The operator's `pg_operator` row, or `None` if it no longer exists
+    pub fn entry(&self) -> Option<PgOperatorEntry> {
+        PgOperatorEntry::search(self.0)
+    }
+}
+
+impl RegNamespace {
+    /// The schema's `pg_namespace` row, or `None` if it no longer exists
+    pub fn entry(&self) -> Option<PgNamespaceEntry> {
+        PgNamespaceEntry::search(self.0)
+    }
+}
@@ -210,6 +210,37 @@ pub unsafe fn pg_getarg_type(fcinfo: pg_sys::FunctionCallInfo, num: usize) -> pg
     pg_sys::get_fn_expr_argtype(fcinfo.as_ref().unwrap().flinfo, num as std::os::raw::c_int)
 }
 
+/// Returns the typmod of the expression the caller passed as argument `num`, or `-1` if it is
+/// unknown, such as when the function wasn't called from a `FuncExpr` or `OpExpr`.
+///
+/// openGauss doesn't keep typmods in function signatures, so this is how a function can see the
+/// `768` of a `vector(768)` argument.
+///
+/// # Safety
+///
+/// The provided `fcinfo` must be valid otherwise this function results in undefined behavior due
+/// to an out of bounds read.
+pub unsafe fn pg_getarg_typmod(fcinfo: pg_sys::FunctionCallInfo, num: usize) -> i32 {
+    let flinfo = fcinfo.as_ref().unwrap().flinfo;
+    if flinfo.is_null() {
+        return -1;
+    }
+
+    let fn_expr = (*flinfo).fn_expr;
+    let args = if crate::is_a(fn_expr, pg_sys::NodeTag_T_FuncExpr) {
+        (*(fn_expr as *mut pg_sys::FuncExpr)).args
+    } else if crate::is_a(fn_expr, pg_sys::NodeTag_T_OpExpr) {
+        (*(fn_expr as *mut pg_sys::OpExpr)).args
+    } else {
+        return -1;
+    };
+
+    match crate::PgList::<pg_sys::Node>::from_pg(args).get_ptr(num) {
+        Some(arg) => pg_sys::exprTypmod(arg),
+        None => -1,
+    }
+}
+
 /// this is intended for Postgres functions that take an actual `cstring` argument, not for getting
 /// a varlena argument type as a CStr.
 #[inline]
@@ -337,6 +337,12 @@ impl<'a, AllocatedBy: WhoAllocated<pg_sys::HeapTupleData>> PgHeapTuple<'a, Alloc
         self.tuple.into_datum()
     }
 
+    /// Returns the underlying [`pg_sys::HeapTuple`] pointer.
+    #[inline]
+    pub(crate) fn as_ptr(&self) -> pg_sys::HeapTuple {
+        self.tuple.as_ptr()
+    }
+
     /// Returns the number of attributes in this [`PgHeapTuple`].
     #[inline]
     pub fn len(&self) -> usize {
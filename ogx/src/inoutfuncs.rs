@@ -72,3 +72,68 @@ pub trait JsonInOutFuncs<'de>: serde::de::Deserialize<'de> + serde::ser::Seriali
     /// error message should be generated?
     const NULL_ERROR_MESSAGE: Option<&'static str> = None;
 }
+
+/// `#[derive(OgType)]` types may implement this trait, along with the `#[sendrecvfuncs]`
+/// attribute, to provide the binary send/receive functions for that type.
+///
+/// These are used by `COPY ... (FORMAT binary)` and by clients speaking the binary protocol,
+/// so values can move without a round trip through the textual representation.
+pub trait SendRecvFuncs {
+    /// Convert `Self` into its binary representation
+    fn send(&self) -> Vec<u8>;
+
+    /// Given the binary representation of `Self`, parse it into `Self`.
+    ///
+    /// It is expected that malformed input will raise an `error!()` or `panic!()`
+    fn recv(buffer: &[u8]) -> Self
+    where
+        Self: Sized;
+}
+
+/// Consume the unread portion of the `StringInfo` openGauss hands to a type's receive function.
+///
+/// ## Safety
+///
+/// `internal` must be the `internal` argument of a `typreceive` function, which points to a
+/// valid [`pg_sys::StringInfoData`].
+#[doc(hidden)]
+pub unsafe fn recv_buffer<'a>(internal: &Internal) -> &'a [u8] {
+    let buf = internal
+        .get_mut::<pg_sys::StringInfoData>()
+        .expect("receive function called without a buffer");
+    let start = buf.cursor as usize;
+    let len = buf.len as usize;
+    // openGauss checks the whole message was consumed once we return
+    buf.cursor = buf.len;
+    if len <= start {
+        &[]
+    } else {
+        std::slice::from_raw_parts(buf.data.add(start) as *const u8, len - start)
+    }
+}
+
+/// `#[derive(OgType)]` types may implement this trait, along with the `#[typmod_inoutfuncs]`
+/// attribute, to accept a type modifier, such as the `768` in `vector(768)`.
+///
+/// A length coercion cast from the type to itself is also created, so values are checked
+/// against (or adjusted to) the modifier of the column or cast they're assigned to.
+pub trait TypmodInOutFuncs {
+    /// Given the comma separated modifiers written after the type name, return the typmod to
+    /// store.  Valid typmods are never negative, as `-1` means "no typmod".
+    ///
+    /// It is expected that malformed modifiers will raise an `error!()` or `panic!()`
+    fn typmod_in(modifiers: &[&str]) -> i32;
+
+    /// Convert a stored typmod back into the text written after the type name, eg `(768)`
+    fn typmod_out(typmod: i32) -> String {
+        format!("({})", typmod)
+    }
+
+    /// Coerce `self` to fit `typmod`, called whenever a value is assigned to a column or cast
+    /// to a type with a typmod.  `explicit` is true for explicit casts.
+    ///
+    /// It is expected that values which can't be coerced will raise an `error!()` or `panic!()`
+    fn typmod_coerce(self, typmod: i32, explicit: bool) -> Self
+    where
+        Self: Sized;
+}
@@ -44,6 +44,22 @@ impl Layout {
             passbyval,
         }
     }
+
+    /**
+    Attempt to discern if a given openGauss and Rust layout are "matching" in some sense.
+    Returns `Some(size)` if both agree on a by-value, fixed-width element of that size,
+    and `None` if they do not, so `&[T]` can't be laid over the datum array.
+    */
+    pub(crate) fn size_matches<T>(&self) -> Option<usize> {
+        const DATUM_SIZE: usize = mem::size_of::<pg_sys::Datum>();
+        match (self.passbyval, self.size.try_as_usize()) {
+            #[allow(unreachable_patterns)] // happens on 32-bit when DATUM_SIZE = 4
+            (true, Some(size @ (1 | 2 | 4 | DATUM_SIZE))) if size == mem::size_of::<T>() => {
+                Some(size)
+            }
+            _ => None,
+        }
+    }
 }
 
 #[repr(usize)]
@@ -35,6 +35,7 @@ pub mod prelude;
 
 pub mod aggregate;
 pub mod callbacks;
+pub mod catalog;
 pub mod datum;
 pub mod enum_helper;
 pub mod fcinfo;
@@ -45,6 +46,7 @@ pub mod inoutfuncs;
 pub mod itemptr;
 pub mod iter;
 pub mod list;
+pub mod lock;
 #[macro_use]
 pub mod log;
 pub mod array;
@@ -55,8 +57,11 @@ pub mod memcxt;
 pub mod misc;
 pub mod namespace;
 pub mod nodes;
+pub mod notify;
 pub mod ogbox;
 pub mod rel;
+pub mod relmodify;
+pub mod relscan;
 pub mod shmem;
 pub mod spi;
 pub mod stringinfo;
@@ -64,6 +69,7 @@ pub mod trigger_support;
 pub mod tupdesc;
 pub mod varlena;
 pub mod wrappers;
+pub mod xact;
 pub mod xid;
 
 #[doc(hidden)]
@@ -83,6 +89,7 @@ pub use htup::*;
 pub use inoutfuncs::*;
 pub use itemptr::*;
 pub use list::*;
+pub use lock::*;
 pub use log::*;
 pub use lwlock::*;
 pub use memcxt::*;
@@ -90,6 +97,8 @@ pub use namespace::*;
 pub use nodes::*;
 pub use ogbox::*;
 pub use rel::*;
+pub use relmodify::*;
+pub use relscan::*;
 pub use shmem::*;
 pub use spi::*;
 pub use stringinfo::*;
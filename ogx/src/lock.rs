@@ -0,0 +1,274 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+
+//! RAII wrappers for Postgres' heavyweight locks: relation locks, database object locks and
+//! advisory locks
+//!
+//! Unlike a [`PgLwLock`][crate::PgLwLock], these are the locks shown in `pg_locks`, are subject
+//! to deadlock detection, and may be held for the rest of the transaction or session.
+use crate::pg_sys;
+
+/// The heavyweight lock modes, from weakest to strongest, as in `LOCK TABLE ... IN <mode> MODE`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PgLockMode {
+    /// `ACCESS SHARE`, taken by `SELECT`
+    AccessShare,
+    /// `ROW SHARE`, taken by `SELECT FOR UPDATE`
+    RowShare,
+    /// `ROW EXCLUSIVE`, taken by `INSERT`, `UPDATE` and `DELETE`
+    RowExclusive,
+    /// `SHARE UPDATE EXCLUSIVE`, taken by `VACUUM` and `ANALYZE`
+    ShareUpdateExclusive,
+    /// `SHARE`, taken by `CREATE INDEX`
+    Share,
+    /// `SHARE ROW EXCLUSIVE`
+    ShareRowExclusive,
+    /// `EXCLUSIVE`
+    Exclusive,
+    /// `ACCESS EXCLUSIVE`, taken by `DROP TABLE` and most `ALTER TABLE` forms
+    AccessExclusive,
+}
+
+impl PgLockMode {
+    /// The equivalent `pg_sys::LOCKMODE`
+    pub fn as_pg(self) -> pg_sys::LOCKMODE {
+        (match self {
+            PgLockMode::AccessShare => pg_sys::AccessShareLock,
+            PgLockMode::RowShare => pg_sys::RowShareLock,
+            PgLockMode::RowExclusive => pg_sys::RowExclusiveLock,
+            PgLockMode::ShareUpdateExclusive => pg_sys::ShareUpdateExclusiveLock,
+            PgLockMode::Share => pg_sys::ShareLock,
+            PgLockMode::ShareRowExclusive => pg_sys::ShareRowExclusiveLock,
+            PgLockMode::Exclusive => pg_sys::ExclusiveLock,
+            PgLockMode::AccessExclusive => pg_sys::AccessExclusiveLock,
+        }) as pg_sys::LOCKMODE
+    }
+}
+
+impl From<PgLockMode> for pg_sys::LOCKMODE {
+    fn from(mode: PgLockMode) -> Self {
+        mode.as_pg()
+    }
+}
+
+/// How long an advisory lock may be held for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PgAdvisoryLockScope {
+    /// Until released, or the session ends, like `pg_advisory_lock()`
+    Session,
+    /// Until released, or the transaction ends, like `pg_advisory_xact_lock()`
+    Transaction,
+}
+
+/// The key identifying an advisory lock: either a single `bigint`, or a pair of `integer`s, as
+/// accepted by the SQL advisory lock functions.  The two key spaces don't overlap
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PgAdvisoryLockKey {
+    Int8(i64),
+    Int4Pair(i32, i32),
+}
+
+impl From<i64> for PgAdvisoryLockKey {
+    fn from(key: i64) -> Self {
+        PgAdvisoryLockKey::Int8(key)
+    }
+}
+
+impl From<(i32, i32)> for PgAdvisoryLockKey {
+    fn from((key1, key2): (i32, i32)) -> Self {
+        PgAdvisoryLockKey::Int4Pair(key1, key2)
+    }
+}
+
+/// A held heavyweight lock, which is released when dropped.
+///
+/// Use [`PgLockGuard::hold()`] instead to keep the lock until the end of the transaction (or of
+/// the session, for session-scoped advisory locks), as Postgres itself usually does.
+#[must_use = "the lock is released as soon as the guard is dropped"]
+pub struct PgLockGuard {
+    tag: pg_sys::LOCKTAG,
+    mode: pg_sys::LOCKMODE,
+    session: bool,
+}
+
+impl PgLockGuard {
+    /// Lock the relation `relid` in `mode`, waiting if necessary, like `LockRelationOid()`.
+    ///
+    /// Raises an ERROR if the relation doesn't exist.
+    pub fn relation(relid: pg_sys::Oid, mode: PgLockMode) -> PgLockGuard {
+        unsafe {
+            // `relation_open()` takes the lock with `LockRelationOid()`, which also processes any
+            // pending invalidations so the relcache reflects the now-locked relation
+            let relation = pg_sys::relation_open(relid, mode.as_pg());
+            let tag = relation_tag((*relation).rd_lockInfo.lockRelId);
+            pg_sys::relation_close(relation, pg_sys::NoLock as pg_sys::LOCKMODE);
+
+            PgLockGuard { tag, mode: mode.as_pg(), session: false }
+        }
+    }
+
+    /// Lock the relation `relid` in `mode` if that's possible without waiting, like
+    /// `ConditionalLockRelationOid()`.
+    ///
+    /// Returns `None` if the lock isn't immediately available, or if the relation doesn't exist.
+    pub fn try_relation(relid: pg_sys::Oid, mode: PgLockMode) -> Option<PgLockGuard> {
+        let tag = unsafe {
+            let relation = pg_sys::RelationIdGetRelation(relid);
+            if relation.is_null() {
+                return None;
+            }
+            let tag = relation_tag((*relation).rd_lockInfo.lockRelId);
+            pg_sys::RelationClose(relation);
+            tag
+        };
+
+        let guard = PgLockGuard::acquire(tag, mode.as_pg(), false, true)?;
+
+        // like `ConditionalLockRelationOid()`, process any pending invalidations so the relcache
+        // reflects the now-locked relation
+        unsafe { pg_sys::AcceptInvalidationMessages() };
+        Some(guard)
+    }
+
+    /// Lock the database object identified by the catalog `classid`, the object's `objid` and
+    /// `objsubid` (typically `0`) in `mode`, waiting if necessary, like `LockDatabaseObject()`
+    pub fn database_object(
+        classid: pg_sys::Oid,
+        objid: pg_sys::Oid,
+        objsubid: u16,
+        mode: PgLockMode,
+    ) -> PgLockGuard {
+        let tag = object_tag(classid, objid, objsubid);
+        PgLockGuard::acquire(tag, mode.as_pg(), false, false).unwrap()
+    }
+
+    /// Lock a database object, as [`PgLockGuard::database_object()`] does, if that's possible
+    /// without waiting.  Returns `None` if the lock isn't immediately available
+    pub fn try_database_object(
+        classid: pg_sys::Oid,
+        objid: pg_sys::Oid,
+        objsubid: u16,
+        mode: PgLockMode,
+    ) -> Option<PgLockGuard> {
+        let tag = object_tag(classid, objid, objsubid);
+        PgLockGuard::acquire(tag, mode.as_pg(), false, true)
+    }
+
+    /// Take the exclusive advisory lock `key`, waiting if necessary, like `pg_advisory_lock()` or
+    /// `pg_advisory_xact_lock()`
+    pub fn advisory_exclusive(
+        key: impl Into<PgAdvisoryLockKey>,
+        scope: PgAdvisoryLockScope,
+    ) -> PgLockGuard {
+        PgLockGuard::advisory(key.into(), pg_sys::ExclusiveLock, scope, false).unwrap()
+    }
+
+    /// Take the shared advisory lock `key`, waiting if necessary, like
+    /// `pg_advisory_lock_shared()` or `pg_advisory_xact_lock_shared()`
+    pub fn advisory_shared(
+        key: impl Into<PgAdvisoryLockKey>,
+        scope: PgAdvisoryLockScope,
+    ) -> PgLockGuard {
+        PgLockGuard::advisory(key.into(), pg_sys::ShareLock, scope, false).unwrap()
+    }
+
+    /// Take the exclusive advisory lock `key` if that's possible without waiting, like
+    /// `pg_try_advisory_lock()` or `pg_try_advisory_xact_lock()`
+    pub fn try_advisory_exclusive(
+        key: impl Into<PgAdvisoryLockKey>,
+        scope: PgAdvisoryLockScope,
+    ) -> Option<PgLockGuard> {
+        PgLockGuard::advisory(key.into(), pg_sys::ExclusiveLock, scope, true)
+    }
+
+    /// Take the shared advisory lock `key` if that's possible without waiting, like
+    /// `pg_try_advisory_lock_shared()` or `pg_try_advisory_xact_lock_shared()`
+    pub fn try_advisory_shared(
+        key: impl Into<PgAdvisoryLockKey>,
+        scope: PgAdvisoryLockScope,
+    ) -> Option<PgLockGuard> {
+        PgLockGuard::advisory(key.into(), pg_sys::ShareLock, scope, true)
+    }
+
+    /// Keep the lock until the end of the transaction, or of the session for a session-scoped
+    /// advisory lock, rather than releasing it when this guard goes out of scope
+    pub fn hold(self) {
+        std::mem::forget(self)
+    }
+
+    fn advisory(
+        key: PgAdvisoryLockKey,
+        mode: u32,
+        scope: PgAdvisoryLockScope,
+        dont_wait: bool,
+    ) -> Option<PgLockGuard> {
+        // the same tag as `SET_LOCKTAG_INT64()` and `SET_LOCKTAG_INT32()`
+        let (field2, field3, field4) = match key {
+            PgAdvisoryLockKey::Int8(key) => ((key >> 32) as u32, key as u32, 1),
+            PgAdvisoryLockKey::Int4Pair(key1, key2) => (key1 as u32, key2 as u32, 2),
+        };
+        let tag = pg_sys::LOCKTAG {
+            locktag_field1: unsafe { pg_sys::MyDatabaseId },
+            locktag_field2: field2,
+            locktag_field3: field3,
+            locktag_field4: field4,
+            locktag_type: pg_sys::LockTagType_LOCKTAG_ADVISORY as u8,
+            locktag_lockmethodid: pg_sys::USER_LOCKMETHOD as u8,
+        };
+
+        let session = scope == PgAdvisoryLockScope::Session;
+        PgLockGuard::acquire(tag, mode as pg_sys::LOCKMODE, session, dont_wait)
+    }
+
+    fn acquire(
+        tag: pg_sys::LOCKTAG,
+        mode: pg_sys::LOCKMODE,
+        session: bool,
+        dont_wait: bool,
+    ) -> Option<PgLockGuard> {
+        let result = unsafe { pg_sys::LockAcquire(&tag, mode, session, dont_wait) };
+        if result == pg_sys::LockAcquireResult_LOCKACQUIRE_NOT_AVAIL {
+            None
+        } else {
+            Some(PgLockGuard { tag, mode, session })
+        }
+    }
+}
+
+impl Drop for PgLockGuard {
+    fn drop(&mut self) {
+        unsafe {
+            pg_sys::LockRelease(&self.tag, self.mode, self.session);
+        }
+    }
+}
+
+/// The same tag as `SET_LOCKTAG_RELATION()`
+fn relation_tag(lock_rel_id: pg_sys::LockRelId) -> pg_sys::LOCKTAG {
+    pg_sys::LOCKTAG {
+        locktag_field1: lock_rel_id.dbId,
+        locktag_field2: lock_rel_id.relId,
+        locktag_field3: 0,
+        locktag_field4: 0,
+        locktag_type: pg_sys::LockTagType_LOCKTAG_RELATION as u8,
+        locktag_lockmethodid: pg_sys::DEFAULT_LOCKMETHOD as u8,
+    }
+}
+
+/// The same tag as `SET_LOCKTAG_OBJECT()`
+fn object_tag(classid: pg_sys::Oid, objid: pg_sys::Oid, objsubid: u16) -> pg_sys::LOCKTAG {
+    pg_sys::LOCKTAG {
+        locktag_field1: unsafe { pg_sys::MyDatabaseId },
+        locktag_field2: classid,
+        locktag_field3: objid,
+        locktag_field4: objsubid,
+        locktag_type: pg_sys::LockTagType_LOCKTAG_OBJECT as u8,
+        locktag_lockmethodid: pg_sys::DEFAULT_LOCKMETHOD as u8,
+    }
+}
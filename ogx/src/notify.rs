@@ -0,0 +1,72 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+
+//! Send `NOTIFY` messages and manage `LISTEN` registrations without building SQL strings
+//!
+//! Like their SQL counterparts, notifications are only sent, and registrations only take effect,
+//! when the current transaction commits.
+//!
+//! Notifications are delivered by openGauss to the client connection of each listening backend.
+//! A backend with no client, such as a background worker, can register to listen but has no way to
+//! receive the notifications.
+use crate::{direct_function_call_as_datum, pg_sys, IntoDatum, Spi};
+use std::ffi::CString;
+
+/// The longest payload openGauss accepts, in bytes, which is one less than
+/// `NOTIFY_PAYLOAD_MAX_LENGTH`
+pub const MAX_PAYLOAD_LEN: usize = pg_sys::NOTIFY_PAYLOAD_MAX_LENGTH as usize - 1;
+
+/// Send a notification with `payload` on `channel`, exactly like `pg_notify(channel, payload)`.
+///
+/// ## Panics
+///
+/// Raises an ERROR if `channel` is empty or too long, or if `payload` is longer than
+/// [`MAX_PAYLOAD_LEN`] bytes.
+pub fn notify(channel: &str, payload: &str) {
+    unsafe {
+        direct_function_call_as_datum(
+            pg_sys::pg_notify,
+            vec![channel.into_datum(), payload.into_datum()],
+        );
+    }
+}
+
+/// Start listening on `channel`, like `LISTEN channel`
+pub fn listen(channel: &str) {
+    let channel = channel_cstring(channel);
+    unsafe { pg_sys::Async_Listen(channel.as_ptr()) }
+}
+
+/// Stop listening on `channel`, like `UNLISTEN channel`
+pub fn unlisten(channel: &str) {
+    let channel = channel_cstring(channel);
+    unsafe { pg_sys::Async_Unlisten(channel.as_ptr()) }
+}
+
+/// Stop listening on every channel, like `UNLISTEN *`
+pub fn unlisten_all() {
+    unsafe { pg_sys::Async_UnlistenAll() }
+}
+
+/// The channels the current session is listening on.  Registrations made by the current
+/// transaction aren't included until it commits
+pub fn listening_channels() -> Vec<String> {
+    Spi::get_one::<Vec<String>>(
+        "SELECT coalesce(array_agg(channel), '{}') FROM pg_catalog.pg_listening_channels() channel",
+    )
+    .unwrap_or_default()
+}
+
+/// Checks `channel` the way `pg_notify()` does, as `LISTEN` on a channel `pg_notify()` rejects
+/// could never receive anything
+fn channel_cstring(channel: &str) -> CString {
+    assert!(!channel.is_empty(), "channel name cannot be empty");
+    assert!(channel.len() < pg_sys::NAMEDATALEN as usize, "channel name too long");
+    CString::new(channel).expect("channel names cannot contain NULL bytes")
+}
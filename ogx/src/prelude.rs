@@ -1,7 +1,7 @@
 // From "external" crates:
 pub use ::ogx_macros::{
-    extension_sql, extension_sql_file, pg_aggregate, og_extern, og_guard, og_schema, og_test,
-    og_trigger, search_path, OgEnum, OgType,
+    extension_sql, extension_sql_file, og_bench, og_cast, og_event_trigger, pg_aggregate,
+    og_extern, og_guard, og_schema, og_test, og_trigger, search_path, OgComposite, OgEnum, OgType,
 };
 pub use ::ogx_pg_sys as pg_sys;
 
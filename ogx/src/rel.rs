@@ -8,9 +8,11 @@ Use of this source code is governed by the MIT license that can be found in the
 */
 
 //! Provides a safe wrapper around Postgres' `pg_sys::RelationData` struct
+use crate::catalog::{PgClassEntry, PgNamespaceEntry};
 use crate::{
-    direct_function_call, name_data_to_str, pg_sys, FromDatum, IntoDatum, OgBox, PgList,
-    PgTupleDesc,
+    direct_function_call, heap_tuple::PgHeapTuple, name_data_to_str, pg_sys, relscan,
+    AllocatedByRust, FromDatum, IndexScan, IntoDatum, OgBox, PgList, PgScanKey, PgSnapshot,
+    PgTableScan, PgTableWriter, PgTupleDesc,
 };
 use ogx_utils::sql_entity_graph::metadata::{
     ArgumentError, Returns, ReturnsError, SqlMapping, SqlTranslatable,
@@ -164,7 +166,11 @@ impl PgRelation {
 
     /// What is the name of the namespace in which this relation is located?
     pub fn namespace(&self) -> &str {
-        unsafe { std::ffi::CStr::from_ptr(pg_sys::get_namespace_name(self.namespace_oid())) }
+        let namespace = PgNamespaceEntry::search(self.namespace_oid())
+            .expect("relation's namespace does not exist");
+
+        // copy the name out of the syscache row, which is released when `namespace` is dropped
+        unsafe { std::ffi::CStr::from_ptr(pg_sys::pstrdup(namespace.nspname.data.as_ptr())) }
             .to_str()
             .expect("unable to convert namespace name to UTF8")
     }
@@ -216,6 +222,96 @@ impl PgRelation {
         PgTupleDesc::from_relation(&self)
     }
 
+    /// Sequentially scan this relation's heap, yielding every tuple visible to `snapshot`
+    ///
+    /// ```rust,no_run
+    /// use ogx::{PgRelation, PgSnapshot};
+    /// let relation = PgRelation::open_with_name_and_share_lock("pets").unwrap();
+    /// for tuple in relation.scan(PgSnapshot::Active) {
+    ///     let name = tuple.get_by_name::<String>("name").unwrap();
+    /// }
+    /// ```
+    ///
+    /// Panics if this relation has no heap, such as a view or an index
+    pub fn scan(&self, snapshot: PgSnapshot) -> PgTableScan {
+        PgTableScan::new(self, snapshot, &[])
+    }
+
+    /// Like [`PgRelation::scan`], but only yields tuples that pass every one of `keys`
+    ///
+    /// ```rust,no_run
+    /// use ogx::{PgRelation, PgScanKey, PgSnapshot};
+    /// let relation = PgRelation::open_with_name_and_share_lock("pets").unwrap();
+    /// let dogs = relation.scan_with_keys(PgSnapshot::Active, &[PgScanKey::equals(2, "dog")]);
+    /// ```
+    pub fn scan_with_keys(&self, snapshot: PgSnapshot, keys: &[PgScanKey]) -> PgTableScan {
+        PgTableScan::new(self, snapshot, keys)
+    }
+
+    /// Start building an [`IndexScan`] of `index`, one of this relation's [`PgRelation::indicies`]
+    pub fn index_scan<'a>(&'a self, index: &'a PgRelation, snapshot: PgSnapshot) -> IndexScan<'a> {
+        IndexScan::new(self, index, snapshot)
+    }
+
+    /// Fetch the tuple at `tid`, if it exists and is visible to `snapshot`
+    pub fn fetch(
+        &self,
+        tid: pg_sys::ItemPointerData,
+        snapshot: PgSnapshot,
+    ) -> Option<PgHeapTuple<AllocatedByRust>> {
+        relscan::fetch(self, tid, snapshot)
+    }
+
+    /// Start a [`PgTableWriter`] that inserts, updates and deletes tuples of this table
+    ///
+    /// The relation should have been opened with at least `RowExclusiveLock`.  Panics if this
+    /// isn't a table, or if it has triggers or foreign keys.
+    pub fn writer(&self) -> PgTableWriter {
+        PgTableWriter::new(self)
+    }
+
+    /// Insert `tuple` into this table, returning its new TID
+    ///
+    /// The table's indexes are updated and its `NOT NULL`, `CHECK` and unique constraints are
+    /// enforced, after checking the current user has `INSERT` permission.  Tables with triggers,
+    /// including foreign key checks, are refused.  Use [`PgRelation::writer`] to insert many.
+    ///
+    /// The relation should have been opened with at least `RowExclusiveLock`.
+    ///
+    /// ```rust,no_run
+    /// use ogx::{pg_sys, IntoDatum, PgHeapTuple, PgRelation};
+    /// let oid = 42;   // a valid pg_class "oid" value
+    /// let relation = PgRelation::with_lock(oid, pg_sys::RowExclusiveLock as pg_sys::LOCKMODE);
+    /// let tuple = PgHeapTuple::from_datums(
+    ///     relation.tuple_desc(),
+    ///     vec![4.into_datum(), "Kiki".into_datum()],
+    /// ).unwrap();
+    /// let tid = relation.insert(tuple);
+    /// ```
+    pub fn insert(&self, tuple: PgHeapTuple<AllocatedByRust>) -> pg_sys::ItemPointerData {
+        self.writer().insert(tuple)
+    }
+
+    /// Replace the tuple at `tid` with `tuple`, returning the TID of the new version
+    ///
+    /// Like [`PgRelation::insert`], with `UPDATE` permission.  Panics if the tuple at `tid` was
+    /// concurrently updated or deleted.
+    pub fn update(
+        &self,
+        tid: pg_sys::ItemPointerData,
+        tuple: PgHeapTuple<AllocatedByRust>,
+    ) -> pg_sys::ItemPointerData {
+        self.writer().update(tid, tuple)
+    }
+
+    /// Delete the tuple at `tid`
+    ///
+    /// Like [`PgRelation::insert`], with `DELETE` permission.  Panics if the tuple at `tid` was
+    /// concurrently updated or deleted.
+    pub fn delete(&self, tid: pg_sys::ItemPointerData) {
+        self.writer().delete(tid)
+    }
+
     /// Number of tuples in this relation (not always up-to-date)
     pub fn reltuples(&self) -> Option<f32> {
         let reltuples = unsafe { self.boxed.rd_rel.as_ref() }.expect("rd_rel is NULL").reltuples;
@@ -227,6 +323,32 @@ impl PgRelation {
         }
     }
 
+    /// Is this relation stored by openGauss' in-place update engine ("ustore") rather than the
+    /// append-only heap ("astore")?  That is recorded by its `storage_type` reloption
+    pub fn is_ustore(&self) -> bool {
+        let reloptions = PgClassEntry::search(self.oid()).and_then(|class| {
+            class.get_attr::<Vec<String>>(pg_sys::Anum_pg_class_reloptions as pg_sys::AttrNumber)
+        });
+
+        reloptions.unwrap_or_default().iter().any(|option| match option.split_once('=') {
+            Some((name, value)) => {
+                name.eq_ignore_ascii_case("storage_type") && value.eq_ignore_ascii_case("ustore")
+            }
+            None => false,
+        })
+    }
+
+    /// Panics, raising an ERROR, if this relation isn't stored in the regular heap that
+    /// `heap_*()` functions read and write
+    pub(crate) fn require_astore(&self, action: &str) {
+        assert!(
+            !self.is_ustore(),
+            "cannot {} relation `{}`, Ustore relations are not supported",
+            action,
+            self.name()
+        );
+    }
+
     pub fn is_table(&self) -> bool {
         let rd_rel: &pg_sys::FormData_pg_class =
             unsafe { self.boxed.rd_rel.as_ref().expect("rd_rel is NULL") };
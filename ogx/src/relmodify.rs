@@ -0,0 +1,222 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+
+//! Insert, update and delete tuples of a [`PgRelation`] directly, without going through SPI
+use crate::{heap_tuple::PgHeapTuple, pg_sys, AllocatedByRust, OgBox, PgRelation};
+
+/// Inserts, updates and deletes tuples of a table, created with [`PgRelation::writer`]
+///
+/// The executor state needed to check the table's `NOT NULL`, `CHECK` and unique constraints and
+/// to maintain its indexes is set up once, so a writer can be reused for many rows.  Each kind of
+/// modification checks the current user's permission the first time it's used.  Everything is
+/// released when this is dropped.
+///
+/// Triggers, including those behind foreign key constraints, are not fired, so tables with
+/// triggers are refused.  So are Ustore relations.
+///
+/// ```rust,no_run
+/// use ogx::{pg_sys, IntoDatum, PgHeapTuple, PgRelation};
+/// let oid = 42;   // a valid pg_class "oid" value
+/// let relation = PgRelation::with_lock(oid, pg_sys::RowExclusiveLock as pg_sys::LOCKMODE);
+/// let mut writer = relation.writer();
+/// for (id, name) in [(4, "Kiki"), (5, "Jiji")] {
+///     let tuple = PgHeapTuple::from_datums(
+///         relation.tuple_desc(),
+///         vec![id.into_datum(), name.into_datum()],
+///     ).unwrap();
+///     writer.insert(tuple);
+/// }
+/// ```
+pub struct PgTableWriter<'a> {
+    relation: &'a PgRelation,
+    estate: *mut pg_sys::EState,
+    result_rel_info: *mut pg_sys::ResultRelInfo,
+    slot: *mut pg_sys::TupleTableSlot,
+    rte: *mut pg_sys::RangeTblEntry,
+    checked: u32,
+}
+
+impl<'a> PgTableWriter<'a> {
+    pub(crate) fn new(relation: &'a PgRelation) -> Self {
+        assert!(
+            relation.is_table(),
+            "cannot modify relation `{}`, it is not a table",
+            relation.name()
+        );
+        relation.require_astore("modify");
+        assert!(
+            relation.trigdesc.is_null(),
+            "cannot modify relation `{}`, it has triggers or foreign keys, which would not be fired",
+            relation.name()
+        );
+
+        unsafe {
+            // `ExecConstraints()` describes failing rows using the range table entry's columns
+            let mut rte =
+                OgBox::<pg_sys::RangeTblEntry>::alloc_node(pg_sys::NodeTag_T_RangeTblEntry);
+            rte.rtekind = pg_sys::RTEKind_RTE_RELATION;
+            rte.relid = relation.oid();
+            rte.relkind = relation.rd_rel.as_ref().unwrap().relkind;
+            let rte = rte.into_pg();
+
+            let estate = pg_sys::CreateExecutorState();
+            (*estate).es_range_table = pg_sys::lappend(std::ptr::null_mut(), rte.cast());
+
+            let result_rel_info =
+                OgBox::<pg_sys::ResultRelInfo>::alloc_node(pg_sys::NodeTag_T_ResultRelInfo)
+                    .into_pg();
+            pg_sys::InitResultRelInfo(
+                result_rel_info,
+                relation.as_ptr(),
+                1,
+                std::ptr::null_mut(),
+                0,
+            );
+            (*estate).es_result_relations = result_rel_info;
+            (*estate).es_num_result_relations = 1;
+            (*estate).es_result_relation_info = result_rel_info;
+            pg_sys::ExecOpenIndices(result_rel_info, false);
+
+            let slot = pg_sys::MakeSingleTupleTableSlot(relation.rd_att);
+
+            PgTableWriter { relation, estate, result_rel_info, slot, rte, checked: 0 }
+        }
+    }
+
+    /// Insert `tuple`, returning its new TID
+    ///
+    /// The table's indexes are updated and its `NOT NULL`, `CHECK` and unique constraints are
+    /// enforced, after checking the current user has `INSERT` permission.
+    pub fn insert(&mut self, tuple: PgHeapTuple<AllocatedByRust>) -> pg_sys::ItemPointerData {
+        self.check_shape(&tuple);
+        self.require_permission(pg_sys::ACL_INSERT);
+        let heap_tuple = tuple.as_ptr();
+
+        self.check_constraints(heap_tuple);
+        unsafe {
+            pg_sys::simple_heap_insert(self.relation.as_ptr(), heap_tuple);
+        }
+        self.insert_index_tuples(heap_tuple);
+
+        unsafe { (*heap_tuple).t_self }
+    }
+
+    /// Replace the tuple at `tid` with `tuple`, returning the TID of the new version
+    ///
+    /// Like [`PgTableWriter::insert`], with `UPDATE` permission.  Panics if the tuple at `tid`
+    /// was concurrently updated or deleted.
+    pub fn update(
+        &mut self,
+        tid: pg_sys::ItemPointerData,
+        tuple: PgHeapTuple<AllocatedByRust>,
+    ) -> pg_sys::ItemPointerData {
+        self.check_shape(&tuple);
+        self.require_permission(pg_sys::ACL_UPDATE);
+        let heap_tuple = tuple.as_ptr();
+        let mut tid = tid;
+
+        self.check_constraints(heap_tuple);
+        unsafe {
+            pg_sys::simple_heap_update(self.relation.as_ptr(), &mut tid, heap_tuple);
+        }
+
+        // a HOT update reuses the existing index entries
+        let heap_only =
+            unsafe { (*(*heap_tuple).t_data).t_infomask2 as u32 & pg_sys::HEAP_ONLY_TUPLE != 0 };
+        if !heap_only {
+            self.insert_index_tuples(heap_tuple);
+        }
+
+        unsafe { (*heap_tuple).t_self }
+    }
+
+    /// Delete the tuple at `tid`
+    ///
+    /// Checks the current user has `DELETE` permission.  Panics if the tuple at `tid` was
+    /// concurrently updated or deleted.
+    pub fn delete(&mut self, tid: pg_sys::ItemPointerData) {
+        self.require_permission(pg_sys::ACL_DELETE);
+        let mut tid = tid;
+
+        unsafe {
+            pg_sys::simple_heap_delete(self.relation.as_ptr(), &mut tid);
+        }
+    }
+
+    /// Checks that the current user has `mode` permission on the table, once per kind of
+    /// modification
+    fn require_permission(&mut self, mode: u32) {
+        if self.checked & mode != 0 {
+            return;
+        }
+
+        unsafe {
+            let result =
+                pg_sys::pg_class_aclcheck(self.relation.oid(), pg_sys::GetUserId(), mode as _);
+            if result != pg_sys::AclResult_ACLCHECK_OK {
+                pg_sys::aclcheck_error(
+                    result,
+                    pg_sys::AclObjectKind_ACL_KIND_CLASS,
+                    self.relation.rd_rel.as_ref().unwrap().relname.data.as_ptr(),
+                );
+            }
+            (*self.rte).requiredPerms |= mode as pg_sys::AclMode;
+        }
+        self.checked |= mode;
+    }
+
+    /// Checks `tuple` against the relation's `NOT NULL` and `CHECK` constraints
+    fn check_constraints(&self, tuple: pg_sys::HeapTuple) {
+        unsafe {
+            pg_sys::ExecStoreTuple(tuple, self.slot, pg_sys::InvalidBuffer as _, false);
+            if !(*self.relation.rd_att).constr.is_null() {
+                pg_sys::ExecConstraints(self.result_rel_info, self.slot, self.estate);
+            }
+        }
+    }
+
+    /// Adds index entries for `tuple`, which must have been stored with `check_constraints()`.
+    /// Uniqueness is enforced here.
+    fn insert_index_tuples(&self, tuple: pg_sys::HeapTuple) {
+        unsafe {
+            if (*self.result_rel_info).ri_NumIndices > 0 {
+                pg_sys::ExecInsertIndexTuples(
+                    self.slot,
+                    &mut (*tuple).t_self,
+                    self.estate,
+                    false,
+                    std::ptr::null_mut(),
+                    std::ptr::null_mut(),
+                );
+            }
+        }
+    }
+
+    fn check_shape(&self, tuple: &PgHeapTuple<'_, AllocatedByRust>) {
+        let natts = self.relation.tuple_desc().len();
+        assert_eq!(
+            tuple.len(),
+            natts,
+            "tuple has {} attributes but relation `{}` has {}",
+            tuple.len(),
+            self.relation.name(),
+            natts
+        );
+    }
+}
+
+impl<'a> Drop for PgTableWriter<'a> {
+    fn drop(&mut self) {
+        unsafe {
+            pg_sys::ExecDropSingleTupleTableSlot(self.slot);
+            pg_sys::ExecCloseIndices(self.result_rel_info);
+            pg_sys::FreeExecutorState(self.estate);
+        }
+    }
+}
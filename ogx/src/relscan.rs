@@ -0,0 +1,432 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+
+//! Provides safe sequential and index scans over a [`PgRelation`], without going through SPI
+use crate::{
+    heap_tuple::PgHeapTuple, pg_sys, AllocatedByOpenGauss, AllocatedByRust, IntoDatum, PgRelation,
+    PgTupleDesc,
+};
+use std::marker::PhantomData;
+
+/// The snapshot a scan uses to decide which tuples are visible
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PgSnapshot {
+    /// The snapshot of the currently-executing query, via `GetActiveSnapshot()`
+    Active,
+    /// The snapshot of the current transaction, via `GetTransactionSnapshot()`
+    Transaction,
+    /// A fresh snapshot that sees everything committed right now, via `GetLatestSnapshot()`
+    Latest,
+    /// Every tuple in the heap, live or dead, via `SnapshotAny`
+    Any,
+}
+
+impl PgSnapshot {
+    /// Returns the underlying snapshot, and whether it is an MVCC snapshot that needs to be
+    /// registered for the duration of a scan
+    fn as_pg(&self) -> (pg_sys::Snapshot, bool) {
+        unsafe {
+            match self {
+                PgSnapshot::Active => (pg_sys::GetActiveSnapshot(), true),
+                PgSnapshot::Transaction => (pg_sys::GetTransactionSnapshot(), true),
+                PgSnapshot::Latest => (pg_sys::GetLatestSnapshot(), true),
+                PgSnapshot::Any => (std::ptr::addr_of_mut!(pg_sys::SnapshotAnyData), false),
+            }
+        }
+    }
+
+    /// Registers the snapshot, if it needs it, so it can't go away while we're using it
+    fn register(&self) -> (pg_sys::Snapshot, bool) {
+        match self.as_pg() {
+            (snapshot, true) => (unsafe { pg_sys::RegisterSnapshot(snapshot) }, true),
+            (snapshot, false) => (snapshot, false),
+        }
+    }
+}
+
+/// A filter applied to each tuple of a [`PgTableScan`], wrapping a `pg_sys::ScanKeyData`
+pub struct PgScanKey {
+    key: pg_sys::ScanKeyData,
+}
+
+impl PgScanKey {
+    /// Compare attribute number `attno` (1-based) against `argument` using the function `procedure`
+    ///
+    /// ## Safety
+    ///
+    /// This function is unsafe as we cannot guarantee that `procedure` is a boolean function
+    /// accepting the attribute's type and the type of `argument`, nor that `argument` lives as
+    /// long as the scan that uses this key
+    pub unsafe fn new(
+        attno: pg_sys::AttrNumber,
+        strategy: pg_sys::StrategyNumber,
+        procedure: pg_sys::RegProcedure,
+        argument: pg_sys::Datum,
+    ) -> Self {
+        let mut key = pg_sys::ScanKeyData::default();
+        pg_sys::ScanKeyInit(&mut key, attno, strategy, procedure, argument);
+        PgScanKey { key }
+    }
+
+    /// Match tuples where attribute number `attno` (1-based) equals `value`, using the default
+    /// equality operator of `T`'s type
+    ///
+    /// Panics if `value` is NULL or if `T`'s type doesn't have an equality operator
+    pub fn equals<T: IntoDatum>(attno: pg_sys::AttrNumber, value: T) -> Self {
+        let typoid = T::type_oid();
+        let argument = value.into_datum().expect("a scan key cannot compare against NULL");
+
+        unsafe {
+            let typentry = pg_sys::lookup_type_cache(typoid, pg_sys::TYPECACHE_EQ_OPR as _);
+            let eq_opr = typentry.as_ref().map(|typentry| typentry.eq_opr);
+            let eq_opr = match eq_opr {
+                Some(eq_opr) if eq_opr != pg_sys::InvalidOid => eq_opr,
+                _ => panic!("type oid {} has no equality operator", typoid),
+            };
+
+            PgScanKey::new(
+                attno,
+                pg_sys::BTEqualStrategyNumber as pg_sys::StrategyNumber,
+                pg_sys::get_opcode(eq_opr),
+                argument,
+            )
+        }
+    }
+}
+
+/// A forward sequential scan over the heap of a [`PgRelation`], created with [`PgRelation::scan`]
+/// or [`PgRelation::scan_with_keys`]
+///
+/// Each tuple is copied out of its shared buffer, so the [`PgHeapTuple`]s it yields may outlive
+/// the scan itself.  The scan is ended when this is dropped.
+///
+/// Only regular heap ("astore") relations are supported, scanning a Ustore relation panics.
+pub struct PgTableScan<'a> {
+    relation: &'a PgRelation,
+    scan: pg_sys::HeapScanDesc,
+    snapshot: pg_sys::Snapshot,
+    registered: bool,
+}
+
+impl<'a> PgTableScan<'a> {
+    pub(crate) fn new(relation: &'a PgRelation, snapshot: PgSnapshot, keys: &[PgScanKey]) -> Self {
+        assert!(
+            relation.is_table() || relation.is_matview() || relation.is_toast_value(),
+            "cannot scan relation `{}`, it has no heap",
+            relation.name()
+        );
+        relation.require_astore("scan");
+
+        let mut keys = keys.iter().map(|key| key.key).collect::<Vec<_>>();
+        let (snapshot, registered) = snapshot.register();
+
+        // SAFETY:  `relation` is a valid, open relation and `heap_beginscan()` copies the keys
+        let scan = unsafe {
+            pg_sys::heap_beginscan(
+                relation.as_ptr(),
+                snapshot,
+                keys.len() as _,
+                if keys.is_empty() { std::ptr::null_mut() } else { keys.as_mut_ptr() },
+            )
+        };
+
+        PgTableScan { relation, scan, snapshot, registered }
+    }
+}
+
+impl<'a> Iterator for PgTableScan<'a> {
+    type Item = PgHeapTuple<'a, AllocatedByRust>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // SAFETY:  `self.scan` was started in `new()` and isn't ended until we're dropped
+        let tuple =
+            unsafe { pg_sys::heap_getnext(self.scan, pg_sys::ScanDirection_ForwardScanDirection) };
+
+        if tuple.is_null() {
+            None
+        } else {
+            // the tuple points into a shared buffer that's only pinned until the next call, so copy it
+            let tupdesc = PgTupleDesc::from_relation(self.relation);
+            Some(unsafe { PgHeapTuple::from_heap_tuple(tupdesc, tuple) }.into_owned())
+        }
+    }
+}
+
+impl<'a> Drop for PgTableScan<'a> {
+    fn drop(&mut self) {
+        unsafe {
+            pg_sys::heap_endscan(self.scan);
+            if self.registered {
+                pg_sys::UnregisterSnapshot(self.snapshot);
+            }
+        }
+    }
+}
+
+/// Fetch the tuple at `tid` from `relation`, if it's visible to `snapshot`
+pub(crate) fn fetch<'a>(
+    relation: &'a PgRelation,
+    tid: pg_sys::ItemPointerData,
+    snapshot: PgSnapshot,
+) -> Option<PgHeapTuple<'a, AllocatedByRust>> {
+    relation.require_astore("fetch from");
+    let (snapshot, registered) = snapshot.register();
+    let mut tuple = pg_sys::HeapTupleData::default();
+    tuple.t_self = tid;
+    let mut buffer = pg_sys::InvalidBuffer as pg_sys::Buffer;
+
+    unsafe {
+        let found = pg_sys::heap_fetch(
+            relation.as_ptr(),
+            snapshot,
+            &mut tuple,
+            &mut buffer,
+            false,
+            std::ptr::null_mut(),
+        );
+
+        let result = if found {
+            // copy the tuple before releasing the buffer it lives in
+            let tupdesc = PgTupleDesc::from_relation(relation);
+            let tuple = PgHeapTuple::<AllocatedByOpenGauss>::from_heap_tuple(tupdesc, &mut tuple)
+                .into_owned();
+            pg_sys::ReleaseBuffer(buffer);
+            Some(tuple)
+        } else {
+            None
+        };
+
+        if registered {
+            pg_sys::UnregisterSnapshot(snapshot);
+        }
+        result
+    }
+}
+
+/// The operator strategy an [`IndexScan`] key compares with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexStrategy {
+    /// btree `<`
+    Less,
+    /// btree `<=`
+    LessEqual,
+    /// btree `=`
+    Equal,
+    /// btree `>=`
+    GreaterEqual,
+    /// btree `>`
+    Greater,
+    /// Any other strategy number, as understood by the index's access method
+    Other(pg_sys::StrategyNumber),
+}
+
+impl IndexStrategy {
+    fn strategy_number(&self) -> pg_sys::StrategyNumber {
+        (match self {
+            IndexStrategy::Less => pg_sys::BTLessStrategyNumber,
+            IndexStrategy::LessEqual => pg_sys::BTLessEqualStrategyNumber,
+            IndexStrategy::Equal => pg_sys::BTEqualStrategyNumber,
+            IndexStrategy::GreaterEqual => pg_sys::BTGreaterEqualStrategyNumber,
+            IndexStrategy::Greater => pg_sys::BTGreaterStrategyNumber,
+            IndexStrategy::Other(strategy) => *strategy as _,
+        }) as pg_sys::StrategyNumber
+    }
+}
+
+/// Builds a scan over one of a [`PgRelation`]'s indexes
+///
+/// Keys are resolved against the operator family of the index column they apply to, so the
+/// Rust value's type must be one the index's operator class knows how to compare.
+///
+/// ```rust,no_run
+/// use ogx::{IndexScan, IndexStrategy, PgRelation, PgSnapshot, pg_sys};
+/// let heap = PgRelation::open_with_name_and_share_lock("pets").unwrap();
+/// let index = PgRelation::open_with_name_and_share_lock("pets_id_idx").unwrap();
+/// let pets = IndexScan::new(&heap, &index, PgSnapshot::Active)
+///     .key(1, IndexStrategy::GreaterEqual, 10)
+///     .key(1, IndexStrategy::Less, 20)
+///     .heap_tuples();
+/// ```
+pub struct IndexScan<'a> {
+    heap: &'a PgRelation,
+    index: &'a PgRelation,
+    snapshot: PgSnapshot,
+    keys: Vec<pg_sys::ScanKeyData>,
+    direction: pg_sys::ScanDirection,
+}
+
+impl<'a> IndexScan<'a> {
+    /// Start building a scan of `index`, which must be an index on `heap`
+    pub fn new(heap: &'a PgRelation, index: &'a PgRelation, snapshot: PgSnapshot) -> Self {
+        assert!(index.is_index(), "relation `{}` is not an index", index.name());
+        assert_eq!(
+            index.heap_relation().map(|relation| relation.oid()),
+            Some(heap.oid()),
+            "index `{}` is not an index on `{}`",
+            index.name(),
+            heap.name()
+        );
+        heap.require_astore("scan");
+
+        IndexScan {
+            heap,
+            index,
+            snapshot,
+            keys: Vec::new(),
+            direction: pg_sys::ScanDirection_ForwardScanDirection,
+        }
+    }
+
+    /// Only return entries where index column `attno` (1-based) compares to `value` per `strategy`
+    ///
+    /// Panics if `value` is NULL or if the column's operator family has no operator for
+    /// `strategy` and `T`'s type
+    pub fn key<T: IntoDatum>(
+        mut self,
+        attno: pg_sys::AttrNumber,
+        strategy: IndexStrategy,
+        value: T,
+    ) -> Self {
+        let natts = self.index.tuple_desc().len();
+        assert!(
+            attno >= 1 && attno as usize <= natts,
+            "index `{}` has no column {}",
+            self.index.name(),
+            attno
+        );
+        let column = attno as usize - 1;
+        let strategy = strategy.strategy_number();
+        let argument = value.into_datum().expect("an index scan key cannot compare against NULL");
+
+        unsafe {
+            let opfamily = *self.index.rd_opfamily.add(column);
+            let opcintype = *self.index.rd_opcintype.add(column);
+            let opno =
+                pg_sys::get_opfamily_member(opfamily, opcintype, T::type_oid(), strategy as _);
+            if opno == pg_sys::InvalidOid {
+                panic!(
+                    "index `{}` cannot compare column {} with type oid {} using strategy {}",
+                    self.index.name(),
+                    attno,
+                    T::type_oid(),
+                    strategy
+                );
+            }
+
+            let mut key = PgScanKey::new(attno, strategy, pg_sys::get_opcode(opno), argument).key;
+            key.sk_subtype = T::type_oid();
+            key.sk_collation = *self.index.rd_indcollation.add(column);
+            self.keys.push(key);
+        }
+        self
+    }
+
+    /// Walk the index in descending order instead
+    pub fn backward(mut self) -> Self {
+        self.direction = pg_sys::ScanDirection_BackwardScanDirection;
+        self
+    }
+
+    /// Run the scan, yielding the TIDs of matching heap tuples
+    ///
+    /// The TIDs are straight from the index and haven't been checked for visibility, use
+    /// [`PgRelation::fetch`] or [`IndexScan::heap_tuples`] for that.
+    pub fn tids(self) -> IndexScanTids<'a> {
+        IndexScanTids { running: self.begin(), relations: PhantomData }
+    }
+
+    /// Run the scan, yielding the heap tuples that match and are visible to the scan's snapshot
+    pub fn heap_tuples(self) -> IndexScanTuples<'a> {
+        let heap = self.heap;
+        IndexScanTuples { running: self.begin(), heap }
+    }
+
+    fn begin(mut self) -> RunningIndexScan {
+        let (snapshot, registered) = self.snapshot.register();
+
+        // SAFETY:  both relations are open and `index_rescan()` copies the keys into the scan
+        let scan = unsafe {
+            let scan = pg_sys::index_beginscan(
+                self.heap.as_ptr(),
+                self.index.as_ptr(),
+                snapshot,
+                self.keys.len() as _,
+                0,
+            );
+            pg_sys::index_rescan(
+                scan,
+                if self.keys.is_empty() { std::ptr::null_mut() } else { self.keys.as_mut_ptr() },
+                self.keys.len() as _,
+                std::ptr::null_mut(),
+                0,
+            );
+            scan
+        };
+
+        RunningIndexScan { scan, snapshot, registered, direction: self.direction }
+    }
+}
+
+struct RunningIndexScan {
+    scan: pg_sys::IndexScanDesc,
+    snapshot: pg_sys::Snapshot,
+    registered: bool,
+    direction: pg_sys::ScanDirection,
+}
+
+impl Drop for RunningIndexScan {
+    fn drop(&mut self) {
+        unsafe {
+            pg_sys::index_endscan(self.scan);
+            if self.registered {
+                pg_sys::UnregisterSnapshot(self.snapshot);
+            }
+        }
+    }
+}
+
+/// An iterator of heap TIDs from an [`IndexScan`], ended when dropped
+pub struct IndexScanTids<'a> {
+    running: RunningIndexScan,
+    relations: PhantomData<&'a PgRelation>,
+}
+
+impl<'a> Iterator for IndexScanTids<'a> {
+    type Item = pg_sys::ItemPointerData;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // SAFETY:  the scan was started in `IndexScan::begin()` and isn't ended until we're dropped
+        let tid = unsafe { pg_sys::index_getnext_tid(self.running.scan, self.running.direction) };
+        unsafe { tid.as_ref() }.copied()
+    }
+}
+
+/// An iterator of heap tuples from an [`IndexScan`], ended when dropped
+///
+/// Like [`PgTableScan`], each tuple is copied out of its shared buffer.
+pub struct IndexScanTuples<'a> {
+    running: RunningIndexScan,
+    heap: &'a PgRelation,
+}
+
+impl<'a> Iterator for IndexScanTuples<'a> {
+    type Item = PgHeapTuple<'a, AllocatedByRust>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // SAFETY:  the scan was started in `IndexScan::begin()` and isn't ended until we're dropped
+        let tuple = unsafe { pg_sys::index_getnext(self.running.scan, self.running.direction) };
+
+        if tuple.is_null() {
+            None
+        } else {
+            let tupdesc = PgTupleDesc::from_relation(self.heap);
+            Some(unsafe { PgHeapTuple::from_heap_tuple(tupdesc, tuple) }.into_owned())
+        }
+    }
+}
@@ -26,6 +26,7 @@ fn trigger_example(trigger: &OgTrigger) -> Result<
 
 Trigger functions only accept one argument, a [`OgTrigger`], and they return a [`Result`][std::result::Result] containing
 either a [`PgHeapTuple`][crate::PgHeapTuple] or any error that implements [`impl std::error::Error`][std::error::Error].
+Statement-level triggers have no row to return, and return `()` instead (see [`OgTriggerReturn`]).
 
 # Use from SQL
 
@@ -197,24 +198,85 @@ fn trigger_safe(trigger: &OgTrigger) -> Result<
     let trigger_safe = unsafe { trigger.to_safe() }?;
     Ok(trigger_safe.current.expect("No current HeapTuple"))
 }
+```
+
+# Transition tables
+
+Statement-level `AFTER` triggers declared with `REFERENCING OLD TABLE AS ...` or
+`REFERENCING NEW TABLE AS ...` can iterate the affected rows directly through
+[`OgTrigger::old_transition_table`] and [`OgTrigger::new_transition_table`], without
+registering them with SPI:
+
+```rust,no_run
+use ogx::{og_trigger, OgTrigger, OgTriggerError};
+
+#[og_trigger]
+fn count_inserted(trigger: &OgTrigger) -> Result<(), OgTriggerError> {
+    if let Some(inserted) = trigger.new_transition_table() {
+        for tuple in inserted {
+            ogx::log!("inserted a row with {} columns", tuple.len());
+        }
+    }
+    Ok(())
+}
+```
+
+```sql
+CREATE TRIGGER count_inserted
+    AFTER INSERT ON test
+    REFERENCING NEW TABLE AS inserted
+    FOR EACH STATEMENT
+    EXECUTE PROCEDURE count_inserted();
+```
+
+# Event triggers
+
+DDL event triggers are written with [`#[og_event_trigger]`][crate::og_event_trigger]. They accept
+one argument, a [`OgEventTrigger`], and return a [`Result`][std::result::Result] of `()` or any error
+that implements [`impl std::error::Error`][std::error::Error]:
+
+```rust,no_run
+use ogx::{og_event_trigger, OgEventTrigger, OgEventTriggerEvent, OgTriggerError};
+
+#[og_event_trigger]
+fn audit_ddl(trigger: &OgEventTrigger) -> Result<(), OgTriggerError> {
+    if trigger.event()? == OgEventTriggerEvent::DdlCommandEnd {
+        ogx::log!("ran `{}`", trigger.tag()?);
+    }
+    Ok(())
+}
+```
+
+This generates a `RETURNS event_trigger` function, which can then be used like so:
+
+```sql
+CREATE EVENT TRIGGER audit_ddl ON ddl_command_end EXECUTE PROCEDURE audit_ddl();
 ```
 
  */
 
+mod og_event_trigger;
+mod og_event_trigger_event;
 mod og_trigger;
 mod og_trigger_error;
 mod og_trigger_level;
 mod og_trigger_option;
+mod og_trigger_return;
 mod og_trigger_safe;
 mod og_trigger_when;
+mod transition_table;
 mod trigger_tuple;
 
+pub use og_event_trigger::OgEventTrigger;
+pub use og_event_trigger_event::OgEventTriggerEvent;
 pub use og_trigger::OgTrigger;
 pub use og_trigger_error::OgTriggerError;
 pub use og_trigger_level::OgTriggerLevel;
 pub use og_trigger_option::OgTriggerOperation;
+pub use og_trigger_return::OgTriggerReturn;
 pub use og_trigger_safe::OgTriggerSafe;
 pub use og_trigger_when::OgTriggerWhen;
+pub use transition_table::TransitionTable;
 pub use trigger_tuple::TriggerTuple;
 
 use crate::{is_a, pg_sys};
@@ -229,6 +291,12 @@ pub unsafe fn called_as_trigger(fcinfo: pg_sys::FunctionCallInfo) -> bool {
     !fcinfo.context.is_null() && is_a(fcinfo.context, pg_sys::NodeTag_T_TriggerData)
 }
 
+#[inline]
+pub unsafe fn called_as_event_trigger(fcinfo: pg_sys::FunctionCallInfo) -> bool {
+    let fcinfo = fcinfo.as_ref().expect("fcinfo was null");
+    !fcinfo.context.is_null() && is_a(fcinfo.context, pg_sys::NodeTag_T_EventTriggerData)
+}
+
 #[inline]
 pub fn trigger_fired_by_insert(event: u32) -> bool {
     event & pg_sys::TRIGGER_EVENT_OPMASK == pg_sys::TRIGGER_EVENT_INSERT
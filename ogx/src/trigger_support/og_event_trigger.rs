@@ -0,0 +1,78 @@
+use crate::ogbox::OgBox;
+use crate::pg_sys;
+use crate::trigger_support::{called_as_event_trigger, OgEventTriggerEvent, OgTriggerError};
+use std::borrow::Borrow;
+use std::ffi::CStr;
+
+/**
+The datatype accepted by an event trigger
+
+A safe structure providing an API similar to the `TG_EVENT` and `TG_TAG` variables provided
+to a PL/pgSQL event trigger, along with the parse tree of the command being run.
+
+Usage examples exist in the module level docs.
+*/
+pub struct OgEventTrigger {
+    event_trigger_data: OgBox<pg_sys::EventTriggerData>,
+    fcinfo: pg_sys::FunctionCallInfo,
+}
+
+impl OgEventTrigger {
+    /// Construct a new [`OgEventTrigger`] from a [`FunctionCallInfo`][pg_sys::FunctionCallInfo]
+    ///
+    /// Generally this would be automatically done for the user in a
+    /// [`#[og_event_trigger]`][crate::og_event_trigger].
+    ///
+    /// # Safety
+    ///
+    /// Users should ensure the provided `fcinfo` is:
+    ///
+    /// * one provided by openGauss during an event trigger invocation,
+    /// * unharmed (the user has not mutated it since openGauss provided it),
+    ///
+    /// If any of these conditions are untrue, this or any other function on this type is
+    /// undefined behavior, hopefully panicking.
+    pub unsafe fn from_fcinfo(fcinfo: pg_sys::FunctionCallInfo) -> Result<Self, OgTriggerError> {
+        if fcinfo.is_null() {
+            return Err(OgTriggerError::NullFunctionCallInfo);
+        }
+        if !called_as_event_trigger(fcinfo) {
+            return Err(OgTriggerError::NotEventTrigger);
+        }
+        let event_trigger_data =
+            OgBox::from_pg((*fcinfo).context as *mut pg_sys::EventTriggerData);
+
+        Ok(Self { event_trigger_data, fcinfo })
+    }
+
+    /// The event the trigger was fired for
+    // Derived from `ogx_pg_sys::EventTriggerData.event`
+    pub fn event(&self) -> Result<OgEventTriggerEvent, OgTriggerError> {
+        let event = unsafe { CStr::from_ptr(self.event_trigger_data.event) }.to_str()?;
+        OgEventTriggerEvent::try_from(event)
+    }
+
+    /// The command tag of the command which fired the trigger, such as `CREATE TABLE`
+    // Derived from `ogx_pg_sys::EventTriggerData.tag`
+    pub fn tag(&self) -> Result<&str, OgTriggerError> {
+        Ok(unsafe { CStr::from_ptr(self.event_trigger_data.tag) }.to_str()?)
+    }
+
+    /// The parse tree of the command which fired the trigger
+    ///
+    /// Use [`is_a`][crate::is_a] to find out which kind of statement it is before casting it.
+    // Derived from `ogx_pg_sys::EventTriggerData.parsetree`
+    pub fn parse_tree(&self) -> OgBox<pg_sys::Node> {
+        unsafe { OgBox::from_pg(self.event_trigger_data.parsetree) }
+    }
+
+    /// A reference to the underlaying [`EventTriggerData`][ogx_pg_sys::EventTriggerData]
+    pub fn event_trigger_data(&self) -> &ogx_pg_sys::EventTriggerData {
+        self.event_trigger_data.borrow()
+    }
+
+    /// A reference to the underlaying fcinfo
+    pub fn fcinfo(&self) -> &pg_sys::FunctionCallInfo {
+        self.fcinfo.borrow()
+    }
+}
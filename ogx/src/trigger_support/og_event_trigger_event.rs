@@ -0,0 +1,41 @@
+use crate::trigger_support::OgTriggerError;
+
+/// The event which fired an event trigger
+///
+/// Maps from the `TEXT` of `TG_EVENT` in a PL/pgSQL event trigger.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OgEventTriggerEvent {
+    /// `ddl_command_start`
+    DdlCommandStart,
+    /// `ddl_command_end`
+    DdlCommandEnd,
+    /// `sql_drop`
+    SqlDrop,
+    /// `table_rewrite`
+    TableRewrite,
+}
+
+impl TryFrom<&str> for OgEventTriggerEvent {
+    type Error = OgTriggerError;
+    fn try_from(event: &str) -> Result<Self, Self::Error> {
+        match event {
+            "ddl_command_start" => Ok(Self::DdlCommandStart),
+            "ddl_command_end" => Ok(Self::DdlCommandEnd),
+            "sql_drop" => Ok(Self::SqlDrop),
+            "table_rewrite" => Ok(Self::TableRewrite),
+            _ => Err(OgTriggerError::InvalidOgEventTriggerEvent),
+        }
+    }
+}
+
+impl ToString for OgEventTriggerEvent {
+    fn to_string(&self) -> String {
+        match self {
+            OgEventTriggerEvent::DdlCommandStart => "ddl_command_start",
+            OgEventTriggerEvent::DdlCommandEnd => "ddl_command_end",
+            OgEventTriggerEvent::SqlDrop => "sql_drop",
+            OgEventTriggerEvent::TableRewrite => "table_rewrite",
+        }
+        .to_string()
+    }
+}
@@ -4,7 +4,7 @@ use crate::ogbox::{AllocatedByOpenGauss, OgBox};
 use crate::rel::PgRelation;
 use crate::trigger_support::{
     called_as_trigger, OgTriggerError, OgTriggerLevel, OgTriggerOperation, OgTriggerSafe,
-    OgTriggerWhen, TriggerEvent, TriggerTuple,
+    OgTriggerWhen, TransitionTable, TriggerEvent, TriggerTuple,
 };
 use cstr_core::c_char;
 use std::borrow::Borrow;
@@ -159,6 +159,30 @@ impl OgTrigger {
             Ok(None)
         }
     }
+    /// The rows as they were before the statement, for a statement-level `AFTER UPDATE` or
+    /// `AFTER DELETE` trigger declared with `REFERENCING OLD TABLE AS ...`
+    // Derived from `ogx_pg_sys::TriggerData.tg_oldtable`
+    pub fn old_transition_table(&self) -> Option<TransitionTable<'_>> {
+        self.transition_table(self.trigger_data.tg_oldtable)
+    }
+    /// The rows as they are after the statement, for a statement-level `AFTER INSERT` or
+    /// `AFTER UPDATE` trigger declared with `REFERENCING NEW TABLE AS ...`
+    // Derived from `ogx_pg_sys::TriggerData.tg_newtable`
+    pub fn new_transition_table(&self) -> Option<TransitionTable<'_>> {
+        self.transition_table(self.trigger_data.tg_newtable)
+    }
+    fn transition_table(
+        &self,
+        tuplestore: *mut pg_sys::Tuplestorestate,
+    ) -> Option<TransitionTable<'_>> {
+        if tuplestore.is_null() {
+            None
+        } else {
+            // Safety: Postgres provided the tuplestore alongside the relation it was built for, and
+            // both live for as long as this `OgTrigger`
+            Some(unsafe { TransitionTable::new(tuplestore, self.relation_data.rd_att) })
+        }
+    }
     /// The `PgRelation` corresponding to the trigger.
     ///
     /// # Panics
@@ -22,4 +22,8 @@ pub enum OgTriggerError {
     NullTriggerData,
     #[error("The `ogx::pg_sys::TriggerData`'s `tg_relation` field was a NULL pointer")]
     NullRelation,
+    #[error("`OgEventTrigger`s can only be built from `FunctionCallInfo` instances which `ogx::trigger_support::called_as_event_trigger(fcinfo)` returns `true`")]
+    NotEventTrigger,
+    #[error("`OgEventTriggerEvent` can only be built from `ddl_command_start`, `ddl_command_end`, `sql_drop` or `table_rewrite`")]
+    InvalidOgEventTriggerEvent,
 }
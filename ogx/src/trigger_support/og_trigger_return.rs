@@ -0,0 +1,28 @@
+use crate::heap_tuple::PgHeapTuple;
+use crate::pg_sys;
+use crate::WhoAllocated;
+
+/**
+The values a [`#[og_trigger]`][crate::og_trigger] function can return
+
+Row-level triggers return a [`PgHeapTuple`].  Statement-level triggers have no row to return, so
+they return `()`.
+*/
+pub trait OgTriggerReturn {
+    /// Convert into the Datum returned to Postgres, where `None` is returned as NULL
+    fn into_trigger_datum(self) -> Option<pg_sys::Datum>;
+}
+
+impl<'a, AllocatedBy: WhoAllocated<pg_sys::HeapTupleData>> OgTriggerReturn
+    for PgHeapTuple<'a, AllocatedBy>
+{
+    fn into_trigger_datum(self) -> Option<pg_sys::Datum> {
+        PgHeapTuple::into_trigger_datum(self)
+    }
+}
+
+impl OgTriggerReturn for () {
+    fn into_trigger_datum(self) -> Option<pg_sys::Datum> {
+        None
+    }
+}
@@ -0,0 +1,71 @@
+use crate::heap_tuple::PgHeapTuple;
+use crate::ogbox::AllocatedByRust;
+use crate::pg_sys;
+use crate::tupdesc::PgTupleDesc;
+use std::marker::PhantomData;
+
+/**
+The `OLD TABLE` or `NEW TABLE` transition table of a statement-level `AFTER` trigger
+
+Iterates the rows of the transition table's tuplestore directly, without registering it with SPI.
+Each row is copied into a [`PgHeapTuple`] shaped like the trigger's relation.
+
+Retrieved from [`OgTrigger::old_transition_table`][crate::OgTrigger::old_transition_table] or
+[`OgTrigger::new_transition_table`][crate::OgTrigger::new_transition_table].  Iteration always
+starts from the first row, and only one iterator over the same transition table should be used at a
+time, as they share the tuplestore's read position.
+*/
+pub struct TransitionTable<'a> {
+    tuplestore: *mut pg_sys::Tuplestorestate,
+    tupdesc: pg_sys::TupleDesc,
+    slot: *mut pg_sys::TupleTableSlot,
+    _marker: PhantomData<&'a pg_sys::TriggerData>,
+}
+
+impl<'a> TransitionTable<'a> {
+    /// ## Safety
+    ///
+    /// `tuplestore` must be one of the transition tables of a trigger on a relation described by
+    /// `tupdesc`, and both must live for `'a`
+    pub(crate) unsafe fn new(
+        tuplestore: *mut pg_sys::Tuplestorestate,
+        tupdesc: pg_sys::TupleDesc,
+    ) -> Self {
+        pg_sys::tuplestore_rescan(tuplestore);
+        let slot = pg_sys::MakeSingleTupleTableSlot(tupdesc);
+        TransitionTable { tuplestore, tupdesc, slot, _marker: PhantomData }
+    }
+
+    /// The number of rows in the transition table
+    pub fn len(&self) -> usize {
+        unsafe { pg_sys::tuplestore_tuple_count(self.tuplestore) as usize }
+    }
+
+    /// Does the transition table have no rows?
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<'a> Iterator for TransitionTable<'a> {
+    type Item = PgHeapTuple<'a, AllocatedByRust>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        unsafe {
+            if !pg_sys::tuplestore_gettupleslot(self.tuplestore, true, false, self.slot) {
+                return None;
+            }
+
+            // the slot's tuple is only valid until the next fetch, so copy it
+            let tuple = pg_sys::ExecFetchSlotTuple(self.slot);
+            let tupdesc = PgTupleDesc::from_pg_unchecked(self.tupdesc);
+            Some(PgHeapTuple::from_heap_tuple(tupdesc, tuple).into_owned())
+        }
+    }
+}
+
+impl<'a> Drop for TransitionTable<'a> {
+    fn drop(&mut self) {
+        unsafe { pg_sys::ExecDropSingleTupleTableSlot(self.slot) }
+    }
+}
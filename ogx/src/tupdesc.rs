@@ -8,9 +8,8 @@ Use of this source code is governed by the MIT license that can be found in the
 */
 
 //! Provides a safe wrapper around Postgres' `pg_sys::TupleDescData` struct
-use crate::{pg_sys, void_mut_ptr, OgBox, PgRelation};
+use crate::{catalog, pg_sys, void_mut_ptr, OgBox, PgRelation};
 
-use ogx_pg_sys::AsPgCStr;
 use std::ops::Deref;
 
 /// This struct is passed around within the backend to describe the structure
@@ -173,17 +172,11 @@ impl<'a> PgTupleDesc<'a> {
     ```
     */
     pub fn for_composite_type(name: &str) -> Option<PgTupleDesc<'a>> {
-        unsafe {
-            let mut typoid = 0;
-            let mut typmod = 0;
-            pg_sys::parseTypeString(name.as_pg_cstr(), &mut typoid, &mut typmod, true);
-
-            if typoid == pg_sys::InvalidOid {
-                return None;
-            }
+        let typoid = catalog::regtype(name)?;
 
+        unsafe {
             // It's important to make a copy of the tupledesc: https://www.postgresql.org/message-id/flat/24471.1136768659%40sss.pgh.pa.us
-            let tuple_desc = pg_sys::lookup_rowtype_tupdesc_copy(typoid, typmod);
+            let tuple_desc = pg_sys::lookup_rowtype_tupdesc_copy(typoid, -1);
 
             Some(PgTupleDesc::from_pg_copy(tuple_desc))
         }
@@ -21,6 +21,23 @@ pub fn regtypein(type_name: &str) -> pg_sys::Oid {
     }
 }
 
+/// The name of the composite type `name` a `#[derive(OgComposite)]` of `extension` created,
+/// qualified with the schema of its `#[og_schema]` module, or else with the schema the extension is
+/// installed in, so it's found whatever the `search_path`
+#[doc(hidden)]
+pub fn composite_type_name(extension: &str, schema: Option<&str>, name: &str) -> String {
+    if let Some(schema) = schema {
+        return format!("{}.{}", schema, name);
+    }
+    let namespace = crate::catalog::PgExtension::lookup(extension)
+        .and_then(|extension| crate::catalog::PgNamespaceEntry::search(extension.namespace));
+    match namespace {
+        Some(namespace) => format!("\"{}\".{}", namespace.name().replace('"', "\"\""), name),
+        // not installed, so leave finding the type to the `search_path`
+        None => name.to_string(),
+    }
+}
+
 /// A helper function for Postgres' `regtypein` function to lookup a type using the name of a Rust type
 ///
 /// We truncate the type name to its last value, unless its a primitive type.
@@ -0,0 +1,121 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+
+//! Run code inside a subtransaction, the internal equivalent of a `SAVEPOINT`
+//!
+//! Where [`register_subxact_callback`][crate::register_subxact_callback] only observes
+//! subtransactions, [`subtransaction()`] creates one, so that a single failing operation can be
+//! rolled back without aborting the surrounding transaction.
+use crate::pg_sys;
+use std::any::Any;
+use std::ffi::CStr;
+use std::panic::{catch_unwind, RefUnwindSafe, UnwindSafe};
+
+/// Why a [`subtransaction()`] was rolled back
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubtransactionError<E> {
+    /// The closure returned `Err`
+    Returned(E),
+    /// The closure raised an openGauss ERROR or panicked, with this message
+    Raised(String),
+}
+
+impl<E: std::fmt::Display> std::fmt::Display for SubtransactionError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SubtransactionError::Returned(e) => write!(f, "{}", e),
+            SubtransactionError::Raised(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl<E: std::fmt::Debug + std::fmt::Display> std::error::Error for SubtransactionError<E> {}
+
+/// Run `f` inside a new subtransaction, as `BeginInternalSubTransaction()` does for PL/pgSQL's
+/// `BEGIN ... EXCEPTION` blocks.
+///
+/// If `f` returns `Ok` the subtransaction is committed into the current transaction.  If it
+/// returns `Err`, raises an ERROR, or panics, everything it did is rolled back, the error is
+/// returned, and the current transaction carries on as if `f` was never called.
+///
+/// `f` runs in the caller's memory context, and the caller's memory context and resource owner
+/// are restored before this function returns either way.
+///
+/// ```rust,no_run
+/// use ogx::prelude::*;
+///
+/// for id in 0..10 {
+///     // a duplicate key only loses its own row, not the whole batch
+///     let result = ogx::xact::subtransaction(|| {
+///         Spi::run(&format!("INSERT INTO things VALUES ({})", id));
+///         Ok::<_, ()>(())
+///     });
+///     if let Err(e) = result {
+///         warning!("skipping {}: {:?}", id, e);
+///     }
+/// }
+/// ```
+pub fn subtransaction<T, E, F>(f: F) -> Result<T, SubtransactionError<E>>
+where
+    F: FnOnce() -> Result<T, E> + UnwindSafe + RefUnwindSafe,
+{
+    unsafe {
+        let oldcontext = pg_sys::CurrentMemoryContext;
+        let oldowner = pg_sys::CurrentResourceOwner;
+
+        pg_sys::BeginInternalSubTransaction(std::ptr::null_mut());
+        // run `f` in the caller's memory context so what it returns survives the subtransaction
+        pg_sys::CurrentMemoryContext = oldcontext;
+
+        let result = match catch_unwind(f) {
+            Ok(Ok(value)) => {
+                pg_sys::ReleaseCurrentSubTransaction();
+                Ok(value)
+            }
+            Ok(Err(e)) => {
+                pg_sys::RollbackAndReleaseCurrentSubTransaction();
+                Err(SubtransactionError::Returned(e))
+            }
+            Err(e) => {
+                pg_sys::CurrentMemoryContext = oldcontext;
+                let message = error_message(e);
+                pg_sys::FlushErrorState();
+                pg_sys::RollbackAndReleaseCurrentSubTransaction();
+                Err(SubtransactionError::Raised(message))
+            }
+        };
+
+        pg_sys::CurrentMemoryContext = oldcontext;
+        pg_sys::CurrentResourceOwner = oldowner;
+        result
+    }
+}
+
+/// The message of a caught openGauss ERROR or Rust panic.  Must be called before the error state
+/// is flushed
+unsafe fn error_message(e: Box<dyn Any + Send>) -> String {
+    if e.downcast_ref::<pg_sys::JumpContext>().is_some() {
+        let edata = pg_sys::CopyErrorData();
+        let message = if (*edata).message.is_null() {
+            "unknown error".to_string()
+        } else {
+            CStr::from_ptr((*edata).message).to_string_lossy().into_owned()
+        };
+        pg_sys::FreeErrorData(edata);
+        message
+    } else if let Some(&s) = e.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = e.downcast_ref::<String>() {
+        s.clone()
+    } else if let Some(s) = e.downcast_ref::<pg_sys::OgxPanic>() {
+        s.message.to_string()
+    } else {
+        "Box<Any>".to_string()
+    }
+}